@@ -1,7 +1,7 @@
 
 
 use super::repl_funcs::{scale_gen_desc, interval_gen};
-use super::repl_funcs::{append, scale_gen, chord_gen, prepend, Context};
+use super::repl_funcs::{append, scale_gen, chord_gen, chord_scales, prepend, Context};
 use repl_rs::Result as ReplResult;
 use repl_rs::{crate_description, crate_name, crate_version};
 use repl_rs::{initialize_repl, Repl};
@@ -27,6 +27,12 @@ pub fn repl_shell() -> ReplResult<()> {
                 .with_parameter(Parameter::new("chord").set_required(true)?)?
                 .with_help("Generate chord"),
         )
+        .add_command(
+            Command::new("chord_scales", chord_scales)
+                .with_parameter(Parameter::new("root").set_required(true)?)?
+                .with_parameter(Parameter::new("chord").set_required(true)?)?
+                .with_help("List scales compatible with chord"),
+        )
         .add_command(
             Command::new("scale", scale_gen)
                 .with_parameter(Parameter::new("tonic").set_required(true)?)?
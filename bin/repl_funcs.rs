@@ -1,6 +1,7 @@
 use mulib::chord::Chord;
 use mulib::chord::ChordQuality;
 use mulib::chord::Inversion;
+use mulib::chord_scale::ScaleToneRole;
 use mulib::interval::Interval;
 use mulib::interval::IntervalType;
 use mulib::pitch::PitchOctave;
@@ -71,6 +72,35 @@ pub fn chord_gen<T>(args: HashMap<String, Value>, _context: &mut T) -> Result<Op
     }))
 }
 
+// List the scales compatible with a chord, marking tensions and avoid notes
+pub fn chord_scales<T>(args: HashMap<String, Value>, _context: &mut T) -> Result<Option<String>> {
+    let root_str: String = args["root"].convert()?;
+    let chord_str: String = args["chord"].convert()?;
+    let root = PitchOctave::from_str(root_str.as_str())?;
+    let chordtype = ChordQuality::from_str(chord_str.as_str())?;
+
+    let chord = Chord::new(root, chordtype);
+
+    Ok(Some(match chord.compatible_scales() {
+        Err(e) => {
+            match e {
+                MuLibErr::OutofBounds => e.to_string(),
+                _ => "Unknown".to_string(),
+            }
+        }
+        Ok(chord_scales) => {
+            chord_scales.iter().map(|chord_scale| {
+                let tones = chord_scale.tones.iter().map(|tone| match tone.role {
+                    ScaleToneRole::ChordTone => tone.pitch.to_string(),
+                    ScaleToneRole::Tension => format!("{}({})", tone.pitch, tone.tension_name().unwrap_or("T")),
+                    ScaleToneRole::Avoid => format!("{}(avoid)", tone.pitch),
+                }).collect::<Vec<String>>().join(" ");
+                format!("{:?}: {}", chord_scale.kind, tones)
+            }).collect::<Vec<String>>().join("\n")
+        },
+    }))
+}
+
 // Generate scale
 pub fn scale_gen<T>(args: HashMap<String, Value>, _context: &mut T) -> Result<Option<String>> {
     let tonic_str: String = args["tonic"].convert()?;
@@ -0,0 +1,803 @@
+use crate::chord::Chord;
+use crate::error::{Error, Result};
+use crate::key::{Key, Mode};
+use crate::meter::{fill_measures, split_tie, MeterType, TimeSignature};
+use crate::pitch::{Alter, Octave, Pitch, PitchOctave, Step};
+use crate::rhythm::{Duration, Event, Note, Rest, Tie, Tuplet};
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_until},
+    character::complete::{char, digit1, multispace1, one_of, space0},
+    combinator::{all_consuming, map, map_res, opt, recognize, value, verify},
+    multi::{many0, many1},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
+use num::rational::Ratio;
+use num::{One, Zero};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// Order in which sharps are added to key signatures, flats being added in reverse
+const SHARP_ORDER: [Step; 7] = [Step::F, Step::C, Step::G, Step::D, Step::A, Step::E, Step::B];
+// Measures written on each line of the tune body
+const MEASURES_PER_LINE: usize = 4;
+// The most measures a single Z rest may last
+const MAX_MULTI_REST: u32 = 1000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tempo {
+    // The beat counted, in quarter note beats
+    pub beat: Ratio<u32>,
+    pub per_minute: u32,
+}
+
+/// A tune in ABC notation. Repeats are kept as written rather than unfolded, and modal keys
+/// such as K:Ddor are read as the major key with the same signature.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AbcTune {
+    // The X: reference number
+    pub reference: u32,
+    pub title: Option<String>,
+    pub key: Key,
+    // None for free meter, M:none
+    pub time_signature: Option<TimeSignature>,
+    // The unit note length in quarter note beats, so L:1/8 is 1/2
+    pub unit: Ratio<u32>,
+    pub tempo: Option<Tempo>,
+    pub events: Vec<Event>,
+    // Chord symbols in quotes, with their offsets in quarter note beats
+    pub chords: Vec<(Ratio<u32>, Chord)>,
+}
+
+impl AbcTune {
+    pub fn new(key: Key, time_signature: Option<TimeSignature>, events: Vec<Event>) -> AbcTune {
+        AbcTune {
+            reference: 1,
+            title: None,
+            key,
+            time_signature,
+            unit: Ratio::new(1, 2),
+            tempo: None,
+            events,
+            chords: vec![],
+        }
+    }
+
+    /// Reads every tune in a file, each starting at its X: field. The file header before the
+    /// first tune, such as the %abc-2.1 version line, is not a tune, but its L:, M: and Q:
+    /// fields are defaults for every tune.
+    pub fn parse_book(input: &str) -> Result<Vec<AbcTune>> {
+        let mut defaults = String::new();
+        let mut tunes = vec![];
+        let mut tune: Option<String> = None;
+        for line in input.lines() {
+            if line.starts_with("X:") {
+                if let Some(tune) = tune.take() {
+                    tunes.push(AbcTune::from_str(&tune)?);
+                }
+                // The tune's own fields follow the defaults and replace them
+                tune = Some(format!("{}\n{}", line, defaults));
+                continue;
+            }
+            match tune.as_mut() {
+                Some(tune) => {
+                    tune.push_str(line);
+                    tune.push('\n');
+                }
+                None if ["L:", "M:", "Q:"].iter().any(|field| line.starts_with(field)) => {
+                    defaults.push_str(line);
+                    defaults.push('\n');
+                }
+                None => {}
+            }
+        }
+        if let Some(tune) = tune {
+            tunes.push(AbcTune::from_str(&tune)?);
+        }
+        Ok(tunes)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct NoteToken {
+    accidental: Option<Alter>,
+    step: Step,
+    // Octave of the note, C4 being written C and C5 c
+    octave: i32,
+    // Length in unit note lengths
+    length: Ratio<u32>,
+    tie: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token<'a> {
+    Note(NoteToken),
+    // The notes with the length they are all multiplied by, and whether all are tied
+    Chord(Vec<NoteToken>, Ratio<u32>, bool),
+    Rest(Ratio<u32>),
+    // Whole measures of rest
+    MultiRest(u32),
+    Symbol(&'a str),
+    Bar,
+    // Notes, the time they fit into, and how many notes follow, as in (3:2:3
+    Tuplet(u32, Option<u32>, Option<u32>),
+    // Dots the first note when true, as in A>B, and the number of marks
+    Broken(bool, usize),
+    Field(char, &'a str),
+    Skip,
+}
+
+impl Token<'_> {
+    fn length_mut(&mut self) -> Option<&mut Ratio<u32>> {
+        match self {
+            Token::Note(note) => Some(&mut note.length),
+            Token::Chord(_, length, _) => Some(length),
+            Token::Rest(length) => Some(length),
+            _ => None,
+        }
+    }
+}
+
+fn parse_number(input: &str) -> IResult<&str, u32> {
+    verify(map_res(digit1, |digits: &str| digits.parse::<u32>()), |number| *number > 0)(input)
+}
+
+// A fraction such as "1/8" or "3/4"
+fn parse_fraction(input: &str) -> IResult<&str, Ratio<u32>> {
+    map(pair(parse_number, preceded(char('/'), parse_number)), |(numerator, denominator)| {
+        Ratio::new(numerator, denominator)
+    })(input)
+}
+
+fn parse_accidental(input: &str) -> IResult<&str, Alter> {
+    alt((
+        value(Alter::DoubleSharp, tag("^^")),
+        value(Alter::HalfSharp, tag("^/")),
+        value(Alter::Sharp, tag("^")),
+        value(Alter::DoubleFlat, tag("__")),
+        value(Alter::HalfFlat, tag("_/")),
+        value(Alter::Flat, tag("_")),
+        value(Alter::None, tag("=")),
+    ))(input)
+}
+
+// Lengths multiply the unit, each slash halving it or dividing by the number after it, so
+// "3/2" is one and a half units and "//" a quarter
+fn parse_length(input: &str) -> IResult<&str, Ratio<u32>> {
+    map(
+        pair(opt(parse_number), many0(preceded(char('/'), opt(parse_number)))),
+        |(multiple, divisors)| {
+            divisors
+                .iter()
+                .fold(Ratio::from_integer(multiple.unwrap_or(1)), |length, divisor| length / divisor.unwrap_or(2))
+        },
+    )(input)
+}
+
+fn parse_note(input: &str) -> IResult<&str, NoteToken> {
+    map(
+        tuple((
+            opt(parse_accidental),
+            one_of("ABCDEFGabcdefg"),
+            many0(one_of("',")),
+            parse_length,
+            opt(char('-')),
+        )),
+        |(accidental, letter, marks, length, tie)| {
+            let raised = marks.iter().filter(|mark| **mark == '\'').count() as i32;
+            let lowered = marks.len() as i32 - raised;
+            let octave = if letter.is_ascii_lowercase() { 5 } else { 4 };
+            NoteToken {
+                accidental,
+                step: Step::from_str(&letter.to_ascii_uppercase().to_string()).unwrap_or_default(),
+                octave: octave + raised - lowered,
+                length,
+                tie: tie.is_some(),
+            }
+        },
+    )(input)
+}
+
+fn parse_bar(input: &str) -> IResult<&str, &str> {
+    alt((
+        recognize(tuple((opt(char('[')), many1(one_of("|:")), opt(char(']')), opt(digit1)))),
+        // The start of a numbered ending, as in [2
+        recognize(pair(char('['), digit1)),
+    ))(input)
+}
+
+fn parse_token(input: &str) -> IResult<&str, Token<'_>> {
+    alt((
+        map(
+            delimited(char('['), pair(one_of("KLMQ"), preceded(char(':'), take_until("]"))), char(']')),
+            |(field, text)| Token::Field(field, text),
+        ),
+        map(
+            tuple((
+                delimited(char('['), many1(preceded(space0, parse_note)), preceded(space0, char(']'))),
+                parse_length,
+                opt(char('-')),
+            )),
+            |(notes, length, tie)| Token::Chord(notes, length, tie.is_some()),
+        ),
+        value(Token::Bar, parse_bar),
+        map(parse_note, Token::Note),
+        map(preceded(one_of("zx"), parse_length), Token::Rest),
+        map(preceded(char('Z'), opt(parse_number)), |bars| Token::MultiRest(bars.unwrap_or(1))),
+        map(delimited(char('"'), take_until("\""), char('"')), Token::Symbol),
+        map(
+            preceded(char('('), tuple((parse_number, opt(preceded(char(':'), opt(parse_number))), opt(preceded(char(':'), parse_number))))),
+            |(notes, time, count)| Token::Tuplet(notes, time.flatten(), count),
+        ),
+        map(many1(char('>')), |marks| Token::Broken(true, marks.len())),
+        map(many1(char('<')), |marks| Token::Broken(false, marks.len())),
+        // Decorations, grace notes, slurs and spacing carry no pitch or time
+        value(Token::Skip, delimited(char('!'), take_until("!"), char('!'))),
+        value(Token::Skip, delimited(char('+'), take_until("+"), char('+'))),
+        value(Token::Skip, delimited(char('{'), take_until("}"), char('}'))),
+        value(Token::Skip, one_of(".~HLMOPSTuv()`\\")),
+        value(Token::Skip, multispace1),
+    ))(input)
+}
+
+fn parse_tonic(input: &str) -> IResult<&str, (char, Option<char>)> {
+    pair(one_of("ABCDEFG"), opt(one_of("#b")))(input)
+}
+
+// Reads a K: field such as "G", "Bbm" or "Ddor"
+fn parse_key(input: &str) -> Result<Key> {
+    let input = input.split_whitespace().next().unwrap_or_default();
+    if input.is_empty() || input == "none" {
+        return Ok(Key::default());
+    }
+    let (mode, (step, alter)) = parse_tonic(input).map_err(|_| Error::Parse)?;
+    let alter = match alter {
+        Some('#') => Alter::Sharp,
+        Some(_) => Alter::Flat,
+        None => Alter::None,
+    };
+    let tonic = PitchOctave::new(Pitch::new(Step::from_str(&step.to_string())?, alter), Octave::default());
+    let mode = mode.to_lowercase();
+    // Modes other than major and minor, by the fifths they lie from the major key on the tonic
+    let offset = match mode.get(..3).unwrap_or(&mode) {
+        "" | "maj" | "ion" => return Ok(Key::new(tonic, Mode::Major)),
+        "m" | "min" | "aeo" => return Ok(Key::new(tonic, Mode::Minor)),
+        "mix" => -1,
+        "dor" => -2,
+        "phr" => -4,
+        "lyd" => 1,
+        "loc" => -5,
+        _ => return Err(Error::Parse),
+    };
+    Key::from_fifths(Key::new(tonic, Mode::Major).fifths() + offset, Mode::Major)
+}
+
+fn parse_meter(input: &str) -> Result<Option<TimeSignature>> {
+    match input.trim() {
+        "none" => Ok(None),
+        "C" => TimeSignature::new(4, 4).map(Some),
+        "C|" => TimeSignature::new(2, 2).map(Some),
+        meter => TimeSignature::from_str(meter).map(Some),
+    }
+}
+
+// Converts the unit of an L: field, or a fraction of a whole note, to quarter note beats
+fn parse_unit(input: &str) -> Result<Ratio<u32>> {
+    all_consuming(parse_fraction)(input.trim())
+        .map(|(_, fraction)| fraction * 4)
+        .map_err(|_| Error::Parse)
+}
+
+// Reads "1/4=120", or a bare count of unit notes per minute
+fn parse_tempo(input: &str, unit: Ratio<u32>) -> Result<Tempo> {
+    let input = input.trim();
+    let (beat, per_minute) = match input.split_once('=') {
+        Some((beat, per_minute)) => {
+            let beat = beat.split_whitespace().last().ok_or(Error::Parse)?;
+            (parse_unit(beat)?, per_minute)
+        }
+        None => (unit, input),
+    };
+    let per_minute = per_minute.split_whitespace().next().unwrap_or_default();
+    Ok(Tempo {
+        beat,
+        per_minute: per_minute.parse().map_err(|_| Error::Parse)?,
+    })
+}
+
+// The alteration a key signature gives a step
+fn key_alter(fifths: i8, step: Step) -> Alter {
+    let count = (fifths.unsigned_abs() as usize).min(SHARP_ORDER.len());
+    if fifths > 0 && SHARP_ORDER[..count].contains(&step) {
+        Alter::Sharp
+    } else if fifths < 0 && SHARP_ORDER[SHARP_ORDER.len() - count..].contains(&step) {
+        Alter::Flat
+    } else {
+        Alter::None
+    }
+}
+
+// The usual number of notes a tuplet of the size fits into
+fn tuplet_normal(actual: u32, meter: Option<&TimeSignature>) -> u32 {
+    match actual {
+        2 | 4 | 8 => 3,
+        3 | 6 => 2,
+        _ if meter.map(|meter| meter.kind()) == Some(MeterType::Compound) => 3,
+        _ => 2,
+    }
+}
+
+// Turns tokens into events, keeping the key signature and accidentals within the bar
+struct AbcReader {
+    key: Key,
+    time_signature: Option<TimeSignature>,
+    unit: Ratio<u32>,
+    accidentals: BTreeMap<(Step, i32), Alter>,
+    onset: Ratio<u32>,
+    tuplet: Option<(Tuplet, u32)>,
+    // Pitches tied into the next event
+    tied: Vec<PitchOctave>,
+    events: Vec<Event>,
+    chords: Vec<(Ratio<u32>, Chord)>,
+}
+
+impl AbcReader {
+    fn pitch(&mut self, note: &NoteToken) -> Result<PitchOctave> {
+        let alter = match note.accidental {
+            Some(alter) => {
+                self.accidentals.insert((note.step, note.octave), alter);
+                alter
+            }
+            None => self
+                .accidentals
+                .get(&(note.step, note.octave))
+                .copied()
+                .unwrap_or_else(|| key_alter(self.key.fifths(), note.step)),
+        };
+        let octave = u8::try_from(note.octave).map_err(|_| Error::OutofBounds)?;
+        if octave > Octave::Octave8 as u8 {
+            return Err(Error::OutofBounds);
+        }
+        Ok(PitchOctave::new(Pitch::new(note.step, alter), Octave::from(octave)))
+    }
+
+    // The durations an event of the length in units takes, tied when no one duration fits
+    fn durations(&mut self, length: Ratio<u32>) -> Result<Vec<Duration>> {
+        let beats = length * self.unit;
+        match self.tuplet.take() {
+            Some((tuplet, remaining)) => {
+                if remaining > 1 {
+                    self.tuplet = Some((tuplet, remaining - 1));
+                }
+                let duration = Duration::from_beats(beats).ok_or(Error::Unsupported)?;
+                Ok(vec![duration.with_tuplet(tuplet)])
+            }
+            None => Duration::tied_from_beats(beats),
+        }
+    }
+
+    fn push(&mut self, events: Vec<Event>) {
+        for event in events {
+            self.onset += event.beats();
+            self.events.push(event);
+        }
+    }
+
+    fn read_notes(&mut self, notes: &[NoteToken], length: Ratio<u32>, tie: bool) -> Result<()> {
+        let first = notes.first().ok_or(Error::Parse)?;
+        let durations = self.durations(first.length * length)?;
+        let mut pitches = vec![];
+        for note in notes {
+            let pitch = self.pitch(note)?;
+            let tie = match (self.tied.contains(&pitch), tie || note.tie) {
+                (false, false) => Tie::None,
+                (false, true) => Tie::Start,
+                (true, false) => Tie::Stop,
+                (true, true) => Tie::Continue,
+            };
+            pitches.push((pitch, tie));
+        }
+        self.tied = pitches
+            .iter()
+            .filter(|(_, tie)| matches!(tie, Tie::Start | Tie::Continue))
+            .map(|(pitch, _)| *pitch)
+            .collect();
+        let count = durations.len();
+        let events = durations
+            .into_iter()
+            .enumerate()
+            .map(|(i, duration)| {
+                let mut notes: Vec<Note> = pitches
+                    .iter()
+                    .map(|(pitch, tie)| Note {
+                        tie: split_tie(*tie, i == 0, i == count - 1),
+                        ..Note::new(*pitch, duration)
+                    })
+                    .collect();
+                if notes.len() == 1 {
+                    Event::Note(notes.remove(0))
+                } else {
+                    Event::Chord(notes)
+                }
+            })
+            .collect();
+        self.push(events);
+        Ok(())
+    }
+
+    fn read_rest(&mut self, beats: Ratio<u32>) -> Result<()> {
+        self.tied.clear();
+        let durations = self.durations(beats / self.unit)?;
+        self.push(durations.into_iter().map(|duration| Event::Rest(Rest::new(duration))).collect());
+        Ok(())
+    }
+
+    fn read_field(&mut self, field: char, text: &str) -> Result<()> {
+        match field {
+            'K' => self.key = parse_key(text)?,
+            'L' => self.unit = parse_unit(text)?,
+            'M' => self.time_signature = parse_meter(text)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn read(&mut self, mut tokens: Vec<Token>) -> Result<()> {
+        // Broken rhythms take time from one note and give it to its neighbour
+        for i in 0..tokens.len() {
+            let Token::Broken(dotted_first, marks) = tokens[i] else {
+                continue;
+            };
+            // The standard defines up to three marks, each halving the shorter note again
+            if marks > 3 {
+                return Err(Error::Parse);
+            }
+            let shortened = Ratio::new(1, 1 << marks);
+            let lengthened = Ratio::from_integer(2) - shortened;
+            let (first, second) = if dotted_first { (lengthened, shortened) } else { (shortened, lengthened) };
+            let before = tokens[..i].iter_mut().rev().find_map(|token| token.length_mut()).ok_or(Error::Parse)?;
+            *before *= first;
+            let after = tokens[i + 1..].iter_mut().find_map(|token| token.length_mut()).ok_or(Error::Parse)?;
+            *after *= second;
+        }
+        for token in tokens {
+            match token {
+                Token::Note(note) => self.read_notes(&[note], Ratio::one(), false)?,
+                Token::Chord(notes, length, tie) => self.read_notes(&notes, length, tie)?,
+                Token::Rest(length) => self.read_rest(length * self.unit)?,
+                Token::MultiRest(bars) => {
+                    if bars > MAX_MULTI_REST {
+                        return Err(Error::OutofBounds);
+                    }
+                    let bar = self.time_signature.as_ref().ok_or(Error::Unsupported)?.bar_beats();
+                    for _ in 0..bars {
+                        self.read_rest(bar)?;
+                    }
+                }
+                Token::Symbol(symbol) => {
+                    // Text annotations start with their placement, and symbols such as N.C. are
+                    // not chords
+                    let is_annotation = symbol.starts_with(['^', '_', '<', '>', '@']);
+                    if let (false, Ok(chord)) = (is_annotation, Chord::from_symbol(symbol, Octave::default())) {
+                        self.chords.push((self.onset, chord));
+                    }
+                }
+                Token::Bar => self.accidentals.clear(),
+                Token::Tuplet(actual, normal, count) => {
+                    let normal = normal.unwrap_or_else(|| tuplet_normal(actual, self.time_signature.as_ref()));
+                    self.tuplet = Some((Tuplet::new(actual, normal)?, count.unwrap_or(actual)));
+                }
+                Token::Field(field, text) => self.read_field(field, text)?,
+                Token::Broken(..) | Token::Skip => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AbcTune {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<AbcTune> {
+        let mut reference = 1;
+        let mut title = None;
+        let mut key = None;
+        let mut time_signature = None;
+        let mut unit = None;
+        let mut tempo = None;
+        let mut body = String::new();
+        for line in input.lines() {
+            // Comments run to the end of the line
+            let line = line.split('%').next().unwrap_or_default().trim_end();
+            let field = terminated(one_of::<_, _, nom::error::Error<&str>>("ABCDFGHIKLMmNOPQRrSsTUVWwXZ"), char(':'))(line);
+            match field {
+                Ok((text, field)) if key.is_none() => {
+                    let text = text.trim();
+                    match field {
+                        'X' => reference = text.parse().map_err(|_| Error::Parse)?,
+                        'T' if title.is_none() => title = Some(text.to_string()),
+                        'M' => time_signature = parse_meter(text)?,
+                        'L' => unit = Some(parse_unit(text)?),
+                        'Q' => tempo = Some(text.to_string()),
+                        'K' => key = Some(parse_key(text)?),
+                        _ => {}
+                    }
+                }
+                // Fields in the body that change the music are kept in place as inline fields
+                Ok((text, field)) if "KLMQ".contains(field) => body.push_str(&format!("[{}:{}]\n", field, text.trim())),
+                Ok(_) => {}
+                Err(_) if key.is_some() => {
+                    body.push_str(line);
+                    body.push('\n');
+                }
+                Err(_) if line.trim().is_empty() => {}
+                Err(_) => return Err(Error::Parse),
+            }
+        }
+        // The header ends at the key, and without a unit length short meters count sixteenths
+        let key = key.ok_or(Error::Parse)?;
+        let unit = unit.unwrap_or_else(|| match &time_signature {
+            Some(meter) if meter.bar_beats() < Ratio::from_integer(3) => Ratio::new(1, 4),
+            _ => Ratio::new(1, 2),
+        });
+        let tempo = tempo.map(|tempo| parse_tempo(&tempo, unit)).transpose()?;
+        let (_, tokens) = all_consuming(many0(parse_token))(body.as_str()).map_err(|_| Error::Parse)?;
+        let mut reader = AbcReader {
+            key,
+            time_signature: time_signature.clone(),
+            unit,
+            accidentals: BTreeMap::new(),
+            onset: Ratio::zero(),
+            tuplet: None,
+            tied: vec![],
+            events: vec![],
+            chords: vec![],
+        };
+        reader.read(tokens)?;
+        Ok(AbcTune {
+            reference,
+            title,
+            key,
+            time_signature,
+            unit,
+            tempo,
+            events: reader.events,
+            chords: reader.chords,
+        })
+    }
+}
+
+fn write_fraction(fraction: Ratio<u32>) -> String {
+    format!("{}/{}", fraction.numer(), fraction.denom())
+}
+
+// Lengths in units leave out a length of one and a numerator of one
+fn write_length(length: Ratio<u32>) -> String {
+    match (*length.numer(), *length.denom()) {
+        (1, 1) => String::new(),
+        (numerator, 1) => numerator.to_string(),
+        (1, denominator) => format!("/{}", denominator),
+        (numerator, denominator) => format!("{}/{}", numerator, denominator),
+    }
+}
+
+// Writes notes with only the accidentals the key signature and earlier notes in the bar don't
+// already give them
+struct AbcWriter<'a> {
+    tune: &'a AbcTune,
+    accidentals: BTreeMap<(Step, i32), Alter>,
+}
+
+impl AbcWriter<'_> {
+    fn pitch(&mut self, pitch: &PitchOctave) -> String {
+        let octave = pitch.octave as i32;
+        let implied = self
+            .accidentals
+            .get(&(pitch.pitch.step, octave))
+            .copied()
+            .unwrap_or_else(|| key_alter(self.tune.key.fifths(), pitch.pitch.step));
+        let accidental = if pitch.pitch.alter == implied {
+            ""
+        } else {
+            self.accidentals.insert((pitch.pitch.step, octave), pitch.pitch.alter);
+            match pitch.pitch.alter {
+                Alter::DoubleFlat => "__",
+                Alter::Flat => "_",
+                Alter::HalfFlat => "_/",
+                Alter::None => "=",
+                Alter::HalfSharp => "^/",
+                Alter::Sharp => "^",
+                Alter::DoubleSharp => "^^",
+            }
+        };
+        let step = pitch.pitch.step.to_string();
+        let name = if octave >= 5 {
+            format!("{}{}", step.to_lowercase(), "'".repeat(octave as usize - 5))
+        } else {
+            format!("{}{}", step, ",".repeat(4 - octave as usize))
+        };
+        format!("{}{}", accidental, name)
+    }
+
+    fn event(&mut self, event: &Event) -> Result<String> {
+        let duration = event.duration()?;
+        // Tuplet notes are written at their plain length
+        let beats = match duration.tuplet() {
+            Some(tuplet) => duration.beats() * Ratio::new(tuplet.actual(), tuplet.normal()),
+            None => duration.beats(),
+        };
+        let length = write_length(beats / self.tune.unit);
+        let tie = |notes: &[Note]| if notes.iter().any(|note| matches!(note.tie, Tie::Start | Tie::Continue)) { "-" } else { "" };
+        Ok(match event {
+            Event::Note(note) => format!("{}{}{}", self.pitch(&note.pitch), length, tie(&[*note])),
+            Event::Chord(notes) => {
+                let pitches: Vec<String> = notes.iter().map(|note| self.pitch(&note.pitch)).collect();
+                format!("[{}]{}{}", pitches.concat(), length, tie(notes))
+            }
+            Event::Rest(_) => format!("z{}", length),
+        })
+    }
+}
+
+impl AbcTune {
+    /// Writes the tune in ABC notation, split into bars of its meter with notes tied over bar lines
+    pub fn to_abc(&self) -> Result<String> {
+        let mut out = format!("X:{}\n", self.reference);
+        if let Some(title) = &self.title {
+            out.push_str(&format!("T:{}\n", title));
+        }
+        match &self.time_signature {
+            Some(time_signature) => out.push_str(&format!("M:{}\n", time_signature)),
+            None => out.push_str("M:none\n"),
+        }
+        out.push_str(&format!("L:{}\n", write_fraction(self.unit / 4)));
+        if let Some(tempo) = self.tempo {
+            out.push_str(&format!("Q:{}={}\n", write_fraction(tempo.beat / 4), tempo.per_minute));
+        }
+        let mode = if self.key.mode == Mode::Minor { "m" } else { "" };
+        out.push_str(&format!("K:{}{}\n", self.key.tonic.pitch, mode));
+
+        let measures = match &self.time_signature {
+            Some(time_signature) if !self.events.is_empty() => {
+                fill_measures(&self.events, time_signature)?.into_iter().map(|measure| measure.events).collect()
+            }
+            _ => vec![self.events.clone()],
+        };
+        let mut writer = AbcWriter {
+            tune: self,
+            accidentals: BTreeMap::new(),
+        };
+        let mut chords = self.chords.iter().peekable();
+        let mut onset = Ratio::zero();
+        let mut tuplet_left = 0;
+        let mut line = vec![];
+        for (number, measure) in measures.iter().enumerate() {
+            let mut words = vec![];
+            for (i, event) in measure.iter().enumerate() {
+                let mut word = String::new();
+                while let Some((_, chord)) = chords.next_if(|(offset, _)| *offset <= onset) {
+                    word.push_str(&format!("\"{}\"", chord.symbol()?));
+                }
+                match event.duration()?.tuplet() {
+                    Some(tuplet) if tuplet_left == 0 => {
+                        // A group split by a bar line, or by a note split at it, holds fewer notes
+                        // than usual, so the count is written out
+                        let in_tuplet =
+                            |event: &&Event| event.duration().ok().and_then(|duration| duration.tuplet()) == Some(tuplet);
+                        let run = measure[i..].iter().take_while(in_tuplet).count() as u32;
+                        let count = run.min(tuplet.actual());
+                        if count < tuplet.actual() {
+                            word.push_str(&format!("({}:{}:{}", tuplet.actual(), tuplet.normal(), count));
+                        } else if tuplet.normal() == tuplet_normal(tuplet.actual(), self.time_signature.as_ref()) {
+                            word.push_str(&format!("({}", tuplet.actual()));
+                        } else {
+                            word.push_str(&format!("({}:{}", tuplet.actual(), tuplet.normal()));
+                        }
+                        tuplet_left = count - 1;
+                    }
+                    Some(_) => tuplet_left -= 1,
+                    None => tuplet_left = 0,
+                }
+                word.push_str(&writer.event(event)?);
+                words.push(word);
+                onset += event.beats();
+            }
+            writer.accidentals.clear();
+            line.push(words.join(" "));
+            let last = number == measures.len() - 1;
+            if last {
+                out.push_str(&format!("{} |]\n", line.join(" | ")));
+            } else if line.len() == MEASURES_PER_LINE {
+                out.push_str(&format!("{} |\n", line.join(" | ")));
+                line.clear();
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rhythm::NoteValue;
+
+    const TUNE: &str = r#"X:7
+T:The Test Reel
+M:4/4
+L:1/8
+Q:1/4=112
+K:G
+% the first bar is a pickup of sorts
+"G"GABc dBG2 | "D7"^c2=c2 F>G (3AGF | "Em"e'4- e'2 z2 | [G,B,D]4 _B,2 d/e/f |]
+"#;
+
+    fn pitch(event: &Event) -> String {
+        event.pitches()[0].to_string()
+    }
+
+    #[test]
+    fn test_parse_abc() {
+        let tune = AbcTune::from_str(TUNE).unwrap();
+        assert_eq!(tune.reference, 7);
+        assert_eq!(tune.title.as_deref(), Some("The Test Reel"));
+        assert_eq!(tune.key.to_string(), "G major");
+        assert_eq!(tune.time_signature.as_ref().unwrap().to_string(), "4/4");
+        assert_eq!(tune.unit, Ratio::new(1, 2));
+        assert_eq!(tune.tempo, Some(Tempo { beat: Ratio::one(), per_minute: 112 }));
+
+        let events = &tune.events;
+        assert_eq!(pitch(&events[0]), "G4");
+        assert_eq!(pitch(&events[3]), "C5");
+        assert_eq!(events[6].duration().unwrap(), Duration::new(NoteValue::Quarter));
+        // The sharp lasts until the natural later in the bar, and the key gives F#
+        assert_eq!(pitch(&events[7]), "C#5");
+        assert_eq!(pitch(&events[8]), "C5");
+        assert_eq!(pitch(&events[9]), "F#4");
+        assert_eq!(events[9].duration().unwrap(), Duration::dotted(NoteValue::Eighth, 1).unwrap());
+        assert_eq!(events[10].duration().unwrap(), Duration::new(NoteValue::Sixteenth));
+        assert_eq!(events[11].duration().unwrap().tuplet(), Some(Tuplet::TRIPLET));
+        assert_eq!(pitch(&events[14]), "E6");
+        let (Event::Note(start), Event::Note(stop)) = (&events[14], &events[15]) else { panic!() };
+        assert_eq!((start.tie, stop.tie), (Tie::Start, Tie::Stop));
+        assert_eq!(events[17].pitches().len(), 3);
+        assert_eq!(pitch(&events[17]), "G3");
+        assert_eq!(pitch(&events[18]), "Bb3");
+        assert_eq!(events[19].duration().unwrap(), Duration::new(NoteValue::Sixteenth));
+        let total: Ratio<u32> = events.iter().map(|event| event.beats()).sum();
+        assert_eq!(total, Ratio::from_integer(16));
+
+        let chords: Vec<(Ratio<u32>, String)> = tune.chords.iter().map(|(at, chord)| (*at, chord.symbol().unwrap())).collect();
+        assert_eq!(chords[1], (Ratio::from_integer(4), "D7".to_string()));
+        assert_eq!(chords.len(), 3);
+        assert_eq!(AbcTune::from_str("X:1\nK:Ddor\nDEF").unwrap().key.to_string(), "C major");
+        assert_eq!(AbcTune::from_str("X:1\nT:No key\nABC"), Err(Error::Parse));
+    }
+
+    #[test]
+    fn test_write_abc() {
+        let tune = AbcTune::from_str(TUNE).unwrap();
+        let written = tune.to_abc().unwrap();
+        assert!(written.starts_with("X:7\nT:The Test Reel\nM:4/4\nL:1/8\nQ:1/4=112\nK:G\n"));
+        assert!(written.contains("\"G\"G A B c d B G2 | \"D7\"^c2 =c2 F3/2 G/2 (3A G F"));
+        assert!(written.contains("\"Em\"e'4- e'2 z2 | [G,B,D]4 _B,2 d/2 e/2 f |]"));
+        let reread = AbcTune::from_str(&written).unwrap();
+        assert_eq!(reread, tune);
+        assert_eq!(AbcTune::parse_book(&format!("{}\n{}", TUNE, written)).unwrap().len(), 2);
+        // The file header is not a tune, and its fields apply to every tune after it
+        let book = AbcTune::parse_book(&format!("%abc-2.1\nL:1/4\n\n{}\nX:8\nK:C\nC\n", TUNE)).unwrap();
+        assert_eq!(book.len(), 2);
+        assert_eq!((book[0].unit, book[1].unit), (Ratio::new(1, 2), Ratio::one()));
+        assert_eq!(AbcTune::from_str("X:1\nM:4/4\nK:C\nZ2|\n").unwrap().events.len(), 2);
+        assert_eq!(AbcTune::from_str("X:1\nM:4/4\nK:C\nZ4000000000|\n"), Err(Error::OutofBounds));
+
+        // A quintuplet crossing the bar line is tied over it, in shorter groups on either side
+        let crossing = AbcTune::from_str("X:1\nM:2/4\nL:1/8\nK:C\nA3 (5ABCDE|\n").unwrap();
+        let written = crossing.to_abc().unwrap();
+        assert!(written.contains("A3 (5:2:2A B (5:4:1C/4- | (5:4:1C/4 (5:2:2D E z3 |]"));
+        let reread = AbcTune::from_str(&written).unwrap();
+        assert_eq!(reread.to_abc().unwrap(), written);
+        let notes = reread.events.iter().filter(|event| !event.pitches().is_empty());
+        assert_eq!(notes.map(pitch).collect::<Vec<String>>(), vec!["A4", "A4", "B4", "C4", "C4", "D4", "E4"]);
+        assert_eq!(AbcTune::from_str(&format!("X:1\nK:C\nA{}B\n", ">".repeat(40))), Err(Error::Parse));
+    }
+}
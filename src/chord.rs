@@ -2,7 +2,7 @@ use crate::interval::{IntervalType, NumericIntervalsSlice};
 use crate::notes::Notes;
 use crate::pitch::{PitchOctave, Pitches};
 use crate::error::{Error, Result};
-use strum::EnumString;
+use strum::{EnumIter, EnumString};
 
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -19,13 +19,22 @@ impl Chord {
     pub fn new_as_inversion(root: PitchOctave, kind: ChordQuality, inversion: Inversion) -> Self {
         Self {root, kind, inversion}
     }
+    /// Transposes the chord up by the interval
+    pub fn checked_add(&self, v: IntervalType) -> Option<Self> {
+        Some(Self {root: self.root.checked_add(v)?, ..self.clone()})
+    }
+    /// Transposes the chord down by the interval
+    pub fn checked_sub(&self, v: IntervalType) -> Option<Self> {
+        Some(Self {root: self.root.checked_sub(v)?, ..self.clone()})
+    }
     fn gen_inversion(&self, inversion: Inversion) -> Result<Pitches> {
         let root_form = self.kind.root_chord_interval();
         match inversion {
             Inversion::Root => {
                 let mut chord  = Pitches(vec![self.root]);
                 for note in root_form {
-                    if let Some(chord_tone) = self.root.checked_add(IntervalType::from(*note)) {
+                    let interval = self.kind.chord_tone_interval(*note);
+                    if let Some(chord_tone) = interval.and_then(|interval| self.root.checked_add(interval)) {
                         chord.0.push(chord_tone);
                     } else {
                         return Err(Error::OutofBounds);
@@ -37,7 +46,8 @@ impl Chord {
                 let mut chord  = Pitches(vec![]);
                 for note in root_form {
                     // The third is the bass note
-                    if let Some(chord_tone) = self.root.checked_add(IntervalType::from(*note)) {
+                    let interval = self.kind.chord_tone_interval(*note);
+                    if let Some(chord_tone) = interval.and_then(|interval| self.root.checked_add(interval)) {
                         chord.0.push(chord_tone);
                     } else {
                         return Err(Error::OutofBounds);
@@ -149,7 +159,7 @@ pub enum TemporalStyle {
     Montuno,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, EnumIter)]
 pub enum ChordQuality {
     Major,
     Minor,
@@ -185,6 +195,9 @@ pub enum ChordQuality {
     Dom7Sharp9,
     Minor7Add11,
     Minor11,
+    ItalianSixth,
+    FrenchSixth,
+    GermanSixth,
 }
 
 impl ChordQuality {
@@ -202,7 +215,7 @@ impl ChordQuality {
     const DOM_7_SHARP_5: [u8; 3] = [4, 8, 10];
     const AUGMENTED: [u8; 2] = [4, 8];
     const MINOR_7_FLAT_5: [u8; 3] = [3, 6, 10];
-    const MAJOR_7_FLAT_5: [u8; 3] = [4, 6, 10];
+    const MAJOR_7_FLAT_5: [u8; 3] = [4, 6, 11];
     const ADD_9: [u8; 3] = [4, 7, 2];
     const MAJOR_9: [u8; 4] = [4, 7, 11, 2];
     const DOM_9: [u8; 4] = [4, 7, 10, 2];
@@ -222,6 +235,10 @@ impl ChordQuality {
     const DOM_7_SHARP_9: [u8; 4] = [4, 7, 10, 3];
     const MINOR_7_ADD_11: [u8; 4] = [3, 7, 10, 5];
     const MINOR_11: [u8; 5] = [3, 7, 10, 2, 5];
+    // Augmented sixth chords, rooted on the lowered sixth degree
+    const ITALIAN_SIXTH: [u8; 2] = [4, 10];
+    const FRENCH_SIXTH: [u8; 3] = [4, 6, 10];
+    const GERMAN_SIXTH: [u8; 3] = [4, 7, 10];
 
     // pub fn get_inversion(&self, inversion: Inversion) -> Option<&[u8]> {
     //     match inversion {
@@ -233,7 +250,7 @@ impl ChordQuality {
     // }
 
     /// Returns the normalized numeric intervals of the chord in close voicing root inversion as a slice
-    fn root_chord_interval(&self) -> &NumericIntervalsSlice {
+    pub fn root_chord_interval(&self) -> &NumericIntervalsSlice {
         match self {
             ChordQuality::Major => &Self::MAJOR_TRIAD,
             ChordQuality::Minor => &Self::MINOR_TRIAD,
@@ -269,13 +286,72 @@ impl ChordQuality {
             ChordQuality::Dom7Sharp9 => &Self::DOM_7_SHARP_9,
             ChordQuality::Minor7Add11 => &Self::MINOR_7_ADD_11,
             ChordQuality::Minor11 => &Self::MINOR_11,
+            ChordQuality::ItalianSixth => &Self::ITALIAN_SIXTH,
+            ChordQuality::FrenchSixth => &Self::FRENCH_SIXTH,
+            ChordQuality::GermanSixth => &Self::GERMAN_SIXTH,
+        }
+    }
+
+    pub fn is_augmented_sixth(&self) -> bool {
+        matches!(
+            self,
+            ChordQuality::ItalianSixth | ChordQuality::FrenchSixth | ChordQuality::GermanSixth
+        )
+    }
+
+    // Returns the number of diatonic steps between the root and a chord tone, so each tone is
+    // spelled as the chord member it represents, a diminished seventh rather than a major sixth
+    fn chord_tone_steps(&self, semitones: u8) -> u8 {
+        let has_fifth = self.root_chord_interval().contains(&7);
+        match semitones {
+            1 | 2 => 1,
+            // A minor third alongside a major third is a sharp ninth
+            3 if self.root_chord_interval().contains(&4) => 1,
+            3 | 4 => 2,
+            5 => 3,
+            // A tritone is a sharp eleventh when the chord has a perfect fifth
+            6 if has_fifth || *self == ChordQuality::FrenchSixth => 3,
+            6 => 4,
+            7 => 4,
+            8 if has_fifth => 5,
+            8 => 4,
+            9 if *self == ChordQuality::Diminished7 => 6,
+            9 => 5,
+            10 if self.is_augmented_sixth() => 5,
+            10 | 11 => 6,
+            _ => 0,
         }
     }
+
+    /// Returns the spelled interval between the root and a chord tone
+    pub fn chord_tone_interval(&self, semitones: u8) -> Option<IntervalType> {
+        IntervalType::from_diatonic_semitones(self.chord_tone_steps(semitones), semitones)
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_major_7_flat_5() {
+        let chord = Chord::new(PitchOctave::from_str("C3").unwrap(), ChordQuality::Major7Flat5);
+        let notes: Vec<String> = chord.notes().unwrap().0.iter().map(|p| p.to_string()).collect();
+        assert_eq!(notes, vec!["C3", "E3", "Gb3", "B3"]);
+    }
+
+    #[test]
+    fn test_chord_root_position() {
+        let chord = Chord::new(PitchOctave::from_str("C3").unwrap(), ChordQuality::Major7);
+        let notes: Vec<String> = chord.notes().unwrap().0.iter().map(|p| p.to_string()).collect();
+        assert_eq!(notes, vec!["C3", "E3", "G3", "B3"]);
+
+        // The seventh of a diminished seventh chord is spelled as a seventh
+        let chord = Chord::new(PitchOctave::from_str("B3").unwrap(), ChordQuality::Diminished7);
+        let notes: Vec<String> = chord.notes().unwrap().0.iter().map(|p| p.to_string()).collect();
+        assert_eq!(notes, vec!["B3", "D4", "F4", "Ab4"]);
+    }
 
     // #[test]
     // fn test_chord_gen() {
@@ -0,0 +1,218 @@
+use crate::chord::{Chord, ChordQuality};
+use crate::error::Result;
+use crate::notes::Notes;
+use crate::pitch::PitchOctave;
+use crate::scale::{Scale, ScaleType};
+
+/// The role a scale tone plays against the chord it is sounded over
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScaleToneRole {
+    ChordTone,
+    Tension,
+    Avoid,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ScaleTone {
+    pub pitch: PitchOctave,
+    // Semitones above the chord root, normalized to a single octave
+    pub semitones: u8,
+    pub role: ScaleToneRole,
+}
+
+impl ScaleTone {
+    /// Returns the conventional tension label (b9, 9, #9, 11, #11, b13, 13) for non chord tones
+    pub fn tension_name(&self) -> Option<&'static str> {
+        if self.role == ScaleToneRole::ChordTone {
+            return None;
+        }
+        match self.semitones {
+            1 => Some("b9"),
+            2 => Some("9"),
+            3 => Some("#9"),
+            5 => Some("11"),
+            6 => Some("#11"),
+            8 => Some("b13"),
+            9 => Some("13"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChordScale {
+    pub kind: ScaleType,
+    pub tones: Vec<ScaleTone>,
+}
+
+impl ChordScale {
+    pub fn tensions(&self) -> Vec<PitchOctave> {
+        self.tones_with_role(ScaleToneRole::Tension)
+    }
+
+    pub fn avoid_notes(&self) -> Vec<PitchOctave> {
+        self.tones_with_role(ScaleToneRole::Avoid)
+    }
+
+    fn tones_with_role(&self, role: ScaleToneRole) -> Vec<PitchOctave> {
+        self.tones
+            .iter()
+            .filter(|tone| tone.role == role)
+            .map(|tone| tone.pitch)
+            .collect()
+    }
+}
+
+impl ChordQuality {
+    /// Returns the scales compatible with the chord quality, most idiomatic first
+    pub fn compatible_scale_types(&self) -> &'static [ScaleType] {
+        match self {
+            ChordQuality::Major => &[ScaleType::Ionian, ScaleType::Lydian],
+            ChordQuality::Minor => &[
+                ScaleType::Dorian,
+                ScaleType::Aeolian,
+                ScaleType::Phrygian,
+                ScaleType::MelodicMinor,
+            ],
+            ChordQuality::Diminished => &[
+                ScaleType::LocrianTwo,
+                ScaleType::Locrian,
+                ScaleType::DiminishedWholeHalf,
+            ],
+            ChordQuality::Sus4 | ChordQuality::Dom7Sus4 => &[ScaleType::Mixolydian, ScaleType::Dorian],
+            ChordQuality::Major7 | ChordQuality::Add9 | ChordQuality::Major9 => {
+                &[ScaleType::Ionian, ScaleType::Lydian, ScaleType::BebopMajor]
+            }
+            ChordQuality::MinorMajor7 => &[
+                ScaleType::MelodicMinor,
+                ScaleType::HarmonicMinor,
+                ScaleType::BebopMelodicMinor,
+            ],
+            ChordQuality::Minor7 => &[
+                ScaleType::Dorian,
+                ScaleType::Aeolian,
+                ScaleType::Phrygian,
+                ScaleType::BebopMinor,
+            ],
+            ChordQuality::Dom7 => &[
+                ScaleType::Mixolydian,
+                ScaleType::LydianDominant,
+                ScaleType::MixolydianFlat6,
+                ScaleType::PhrygianDominant,
+                ScaleType::Altered,
+                ScaleType::WholeTone,
+                ScaleType::DiminishedHalfWhole,
+                ScaleType::BebopDominant,
+            ],
+            ChordQuality::Diminished7 => &[ScaleType::DiminishedWholeHalf, ScaleType::AlteredFlat7],
+            ChordQuality::Major7Sharp5 => &[ScaleType::LydianAugmented, ScaleType::IonianAug],
+            ChordQuality::Dom7Sharp5 => &[ScaleType::WholeTone, ScaleType::Altered],
+            ChordQuality::Augmented => &[
+                ScaleType::WholeTone,
+                ScaleType::LydianAugmented,
+                ScaleType::Augmented,
+            ],
+            ChordQuality::Minor7Flat5 => &[ScaleType::LocrianTwo, ScaleType::Locrian],
+            ChordQuality::Major7Flat5 | ChordQuality::Major7Sharp11 | ChordQuality::Major9Sharp11 => {
+                &[ScaleType::Lydian]
+            }
+            ChordQuality::Dom9 | ChordQuality::Dom13 | ChordQuality::Dom9Thirteenth => {
+                &[ScaleType::Mixolydian, ScaleType::LydianDominant]
+            }
+            ChordQuality::MinorAdd9
+            | ChordQuality::Minor9
+            | ChordQuality::Minor7Add11
+            | ChordQuality::Minor11 => &[ScaleType::Dorian, ScaleType::Aeolian],
+            ChordQuality::MinorMajor7Add9 => &[ScaleType::MelodicMinor],
+            ChordQuality::Dom7Sharp11 | ChordQuality::Dom9Sharp11 => &[ScaleType::LydianDominant],
+            ChordQuality::Dom7Flat9 => &[ScaleType::DiminishedHalfWhole, ScaleType::PhrygianDominant],
+            ChordQuality::Dom7Flat13 => &[ScaleType::MixolydianFlat6, ScaleType::Altered],
+            ChordQuality::Dom7Flat13Flat9 => &[ScaleType::PhrygianDominant, ScaleType::Altered],
+            ChordQuality::Dom11Flat13Flat9 => &[ScaleType::PhrygianDominant],
+            ChordQuality::Dom7Sharp9 => &[ScaleType::Altered, ScaleType::DiminishedHalfWhole],
+            ChordQuality::ItalianSixth | ChordQuality::GermanSixth => &[ScaleType::LydianDominant],
+            ChordQuality::FrenchSixth => &[ScaleType::WholeTone],
+        }
+    }
+
+    /// Returns true for chords with a major third and minor seventh
+    pub fn is_dominant(&self) -> bool {
+        let intervals = self.root_chord_interval();
+        intervals.contains(&4) && intervals.contains(&10)
+    }
+
+    /// Classifies a scale tone, given in semitones above the chord root, against this chord.
+    /// A non chord tone a half step above a chord tone is an avoid note, except for the b9 and
+    /// b13 which are available tensions on dominant chords.
+    pub fn scale_tone_role(&self, semitones: u8) -> ScaleToneRole {
+        let semitones = semitones % 12;
+        let is_chord_tone = |s: u8| s == 0 || self.root_chord_interval().contains(&s);
+        if is_chord_tone(semitones) {
+            ScaleToneRole::ChordTone
+        } else if self.is_dominant() && (semitones == 1 || semitones == 8) {
+            ScaleToneRole::Tension
+        } else if is_chord_tone((semitones + 11) % 12) {
+            ScaleToneRole::Avoid
+        } else {
+            ScaleToneRole::Tension
+        }
+    }
+}
+
+impl Chord {
+    /// Returns the scales compatible with this chord, with each scale tone marked as a chord tone,
+    /// available tension or avoid note
+    pub fn compatible_scales(&self) -> Result<Vec<ChordScale>> {
+        let mut chord_scales = vec![];
+        for kind in self.kind.compatible_scale_types() {
+            let pitches = Scale::new(self.root, *kind).notes()?;
+            let semitones = std::iter::once(&0).chain(kind.scale_interval());
+            let tones = pitches
+                .0
+                .iter()
+                .zip(semitones)
+                .map(|(pitch, semitones)| ScaleTone {
+                    pitch: *pitch,
+                    semitones: *semitones,
+                    role: self.kind.scale_tone_role(*semitones),
+                })
+                .collect();
+            chord_scales.push(ChordScale { kind: *kind, tones });
+        }
+        Ok(chord_scales)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn names(pitches: Vec<PitchOctave>) -> Vec<String> {
+        pitches.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_major7_ionian_avoids_fourth() {
+        let chord = Chord::new(PitchOctave::from_str("C3").unwrap(), ChordQuality::Major7);
+        let scales = chord.compatible_scales().unwrap();
+        assert_eq!(scales[0].kind, ScaleType::Ionian);
+        assert_eq!(names(scales[0].avoid_notes()), vec!["F3"]);
+        assert_eq!(names(scales[0].tensions()), vec!["D3", "A3"]);
+        assert_eq!(scales[1].kind, ScaleType::Lydian);
+        assert!(scales[1].avoid_notes().is_empty());
+    }
+
+    #[test]
+    fn test_dominant_scale_roles() {
+        let chord = Chord::new(PitchOctave::from_str("G3").unwrap(), ChordQuality::Dom7Sharp11);
+        let scales = chord.compatible_scales().unwrap();
+        assert_eq!(scales[0].kind, ScaleType::LydianDominant);
+        assert!(scales[0].avoid_notes().is_empty());
+
+        let half_diminished = Chord::new(PitchOctave::from_str("B3").unwrap(), ChordQuality::Minor7Flat5);
+        assert_eq!(half_diminished.kind.compatible_scale_types()[0], ScaleType::LocrianTwo);
+        assert_eq!(ChordQuality::Dom7.scale_tone_role(1), ScaleToneRole::Tension);
+        assert_eq!(ChordQuality::Minor7.scale_tone_role(1), ScaleToneRole::Avoid);
+    }
+}
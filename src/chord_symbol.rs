@@ -0,0 +1,175 @@
+use crate::chord::{Chord, ChordQuality, Inversion};
+use crate::error::{Error, Result};
+use crate::pitch::{Octave, Pitch, PitchOctave};
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+// Alternative spellings accepted when parsing, in addition to each quality's own symbol
+const SYMBOL_ALIASES: [(&str, ChordQuality); 22] = [
+    ("M", ChordQuality::Major),
+    ("maj", ChordQuality::Major),
+    ("min", ChordQuality::Minor),
+    ("-", ChordQuality::Minor),
+    ("°", ChordQuality::Diminished),
+    ("o", ChordQuality::Diminished),
+    ("+", ChordQuality::Augmented),
+    ("sus", ChordQuality::Sus4),
+    ("7sus", ChordQuality::Dom7Sus4),
+    ("M7", ChordQuality::Major7),
+    ("Δ", ChordQuality::Major7),
+    ("Δ7", ChordQuality::Major7),
+    ("-7", ChordQuality::Minor7),
+    ("min7", ChordQuality::Minor7),
+    ("°7", ChordQuality::Diminished7),
+    ("o7", ChordQuality::Diminished7),
+    ("ø", ChordQuality::Minor7Flat5),
+    ("ø7", ChordQuality::Minor7Flat5),
+    ("-7b5", ChordQuality::Minor7Flat5),
+    ("mMaj7", ChordQuality::MinorMajor7),
+    ("+7", ChordQuality::Dom7Sharp5),
+    ("M9", ChordQuality::Major9),
+];
+
+impl ChordQuality {
+    /// Returns the lead sheet suffix of the chord, such as "m7b5"
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Dom7Sus4 => "7sus4",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::MinorMajor7 => "m(maj7)",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::Dom7 => "7",
+            ChordQuality::Diminished7 => "dim7",
+            ChordQuality::Major7Sharp5 => "maj7#5",
+            ChordQuality::Dom7Sharp5 => "7#5",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Minor7Flat5 => "m7b5",
+            ChordQuality::Major7Flat5 => "maj7b5",
+            ChordQuality::Add9 => "add9",
+            ChordQuality::Major9 => "maj9",
+            ChordQuality::Dom9 => "9",
+            ChordQuality::MinorAdd9 => "madd9",
+            ChordQuality::MinorMajor7Add9 => "m(maj9)",
+            ChordQuality::Minor9 => "m9",
+            ChordQuality::Major7Sharp11 => "maj7#11",
+            ChordQuality::Major9Sharp11 => "maj9#11",
+            ChordQuality::Dom7Sharp11 => "7#11",
+            ChordQuality::Dom9Sharp11 => "9#11",
+            ChordQuality::Dom13 => "7(13)",
+            ChordQuality::Dom9Thirteenth => "13",
+            ChordQuality::Dom7Flat9 => "7b9",
+            ChordQuality::Dom7Flat13 => "7b13",
+            ChordQuality::Dom7Flat13Flat9 => "7b9b13",
+            ChordQuality::Dom11Flat13Flat9 => "11b9b13",
+            ChordQuality::Dom7Sharp9 => "7#9",
+            ChordQuality::Minor7Add11 => "m7add11",
+            ChordQuality::Minor11 => "m11",
+            ChordQuality::ItalianSixth => "It+6",
+            ChordQuality::FrenchSixth => "Fr+6",
+            ChordQuality::GermanSixth => "Ger+6",
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Result<ChordQuality> {
+        ChordQuality::iter()
+            .find(|quality| quality.symbol() == symbol)
+            .or_else(|| {
+                SYMBOL_ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == symbol)
+                    .map(|(_, quality)| *quality)
+            })
+            .ok_or(Error::Parse)
+    }
+}
+
+impl Chord {
+    /// Returns the lowest note of the chord's inversion
+    pub fn bass(&self) -> Result<PitchOctave> {
+        let index = self.inversion as usize;
+        if index == 0 {
+            return Ok(self.root);
+        }
+        let semitones = self
+            .kind
+            .root_chord_interval()
+            .get(index - 1)
+            .ok_or(Error::InvalidInversion)?;
+        self.kind
+            .chord_tone_interval(*semitones)
+            .and_then(|interval| self.root.checked_add(interval))
+            .ok_or(Error::OutofBounds)
+    }
+
+    /// Returns the lead sheet symbol of the chord, with inversions written as slash chords
+    pub fn symbol(&self) -> Result<String> {
+        let symbol = format!("{}{}", self.root.pitch, self.kind.symbol());
+        if self.inversion == Inversion::Root {
+            return Ok(symbol);
+        }
+        Ok(format!("{}/{}", symbol, self.bass()?.pitch))
+    }
+
+    /// Parses a lead sheet symbol such as "Bbm7" or "C/E" with the root in the given octave. The
+    /// bass of a slash chord must be a chord tone.
+    pub fn from_symbol(symbol: &str, octave: Octave) -> Result<Chord> {
+        let (symbol, bass) = match symbol.trim().split_once('/') {
+            Some((symbol, bass)) => (symbol, Some(bass)),
+            None => (symbol.trim(), None),
+        };
+        // No suffix begins with b or #, so the root ends at the first other character
+        let root_len = symbol
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| *c != 'b' && *c != '#')
+            .map(|(i, _)| i)
+            .unwrap_or(symbol.len());
+        let root = Pitch::from_str(symbol.get(..root_len).ok_or(Error::Parse)?)?;
+        let kind = ChordQuality::from_symbol(&symbol[root_len..])?;
+        let mut chord = Chord::new(PitchOctave::new(root, octave), kind);
+        if let Some(bass) = bass {
+            let bass = Pitch::from_str(bass)?;
+            chord.inversion = [
+                Inversion::Root,
+                Inversion::First,
+                Inversion::Second,
+                Inversion::Third,
+                Inversion::Fourth,
+            ]
+            .into_iter()
+            .take(kind.root_chord_interval().len() + 1)
+            .find(|inversion| {
+                let candidate = Chord::new_as_inversion(chord.root, kind, *inversion);
+                candidate.bass().map(|b| b.pitch == bass).unwrap_or(false)
+            })
+            .ok_or(Error::Unsupported)?;
+        }
+        Ok(chord)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_symbols() {
+        for quality in ChordQuality::iter() {
+            assert_eq!(ChordQuality::from_symbol(quality.symbol()).unwrap(), quality);
+        }
+        let chord = Chord::from_symbol("Bbm7b5", Octave::Octave3).unwrap();
+        assert_eq!(chord.root.to_string(), "Bb3");
+        assert_eq!(chord.kind, ChordQuality::Minor7Flat5);
+
+        let chord = Chord::from_symbol("C/E", Octave::Octave3).unwrap();
+        assert_eq!(chord.inversion, Inversion::First);
+        assert_eq!(chord.symbol().unwrap(), "C/E");
+        assert_eq!(Chord::from_symbol("G7/B", Octave::Octave3).unwrap().symbol().unwrap(), "G7/B");
+        assert_eq!(Chord::from_symbol("C/D", Octave::Octave3), Err(Error::Unsupported));
+        assert_eq!(Chord::from_symbol("Cxyz", Octave::Octave3), Err(Error::Parse));
+    }
+}
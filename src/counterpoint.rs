@@ -0,0 +1,472 @@
+use crate::error::{Error, Result};
+use crate::key::Key;
+use crate::pitch::{Octave, PitchOctave, Pitches};
+
+// Harmonic intervals, reduced to an octave, that count as consonant in two voices
+const CONSONANCES: [i8; 6] = [0, 3, 4, 7, 8, 9];
+// Candidate positions explored before generation gives up
+const SEARCH_LIMIT: usize = 200_000;
+// How far a generated line may move beyond the cantus firmus, in semitones
+const GENERATED_RANGE: i8 = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Species {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CounterpointRule {
+    ImperfectBeginning,
+    ImperfectEnding,
+    FinalNotByStep,
+    DissonantDownbeat,
+    DissonantWeakBeat,
+    UnresolvedSuspension,
+    ParallelFifths,
+    ParallelOctaves,
+    HiddenPerfect,
+    Unison,
+    ForbiddenLeap,
+    RepeatedNote,
+    VoiceCrossing,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CounterpointViolation {
+    pub rule: CounterpointRule,
+    // Index of the cantus firmus note, and of the counterpoint note within it
+    pub bar: usize,
+    pub note: usize,
+}
+
+/// A counterpoint line divided into bars, one per cantus firmus note. The notes of a bar split
+/// it evenly. In fourth species the first bar holds only the note after the opening rest, and in
+/// fourth and fifth species a bar starting on the previous bar's last pitch is tied over.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Counterpoint {
+    pub species: Species,
+    pub bars: Vec<Pitches>,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Event {
+    bar: usize,
+    note: usize,
+    pitch: i8,
+    downbeat: bool,
+    tied: bool,
+}
+
+// The number of notes in each bar of a species, for a cantus of the given length
+fn bar_sizes(species: Species, len: usize) -> Vec<usize> {
+    (0..len)
+        .map(|bar| match species {
+            _ if bar == len - 1 => 1,
+            Species::First => 1,
+            Species::Second => 2,
+            Species::Third => 4,
+            Species::Fourth if bar == 0 => 1,
+            Species::Fourth => 2,
+            // Florid counterpoint alternates half and quarter notes, closing on a suspension
+            Species::Fifth if bar % 2 == 1 && bar != len - 2 => 4,
+            Species::Fifth => 2,
+        })
+        .collect()
+}
+
+fn is_consonant(interval: i8) -> bool {
+    CONSONANCES.contains(&(interval.abs() % 12))
+}
+
+fn is_perfect(interval: i8) -> bool {
+    matches!(interval.abs() % 12, 0 | 7)
+}
+
+fn is_step(from: i8, to: i8) -> bool {
+    (1..=2).contains(&(to - from).abs())
+}
+
+struct Checker<'a> {
+    species: Species,
+    cantus: &'a [i8],
+    above: bool,
+    last_bar: usize,
+}
+
+impl Checker<'_> {
+    fn interval(&self, event: &Event) -> i8 {
+        event.pitch - self.cantus[event.bar]
+    }
+
+    // The events compared for parallel motion, the attacks in fourth species and downbeats otherwise
+    fn is_accented(&self, event: &Event) -> bool {
+        match self.species {
+            Species::Fourth => !event.tied,
+            _ => event.downbeat,
+        }
+    }
+
+    // Checks the newest event against those before it. Dissonances that depend on the following
+    // note are judged once it arrives.
+    fn check(&self, events: &[Event], violations: &mut Vec<CounterpointViolation>) {
+        let i = events.len() - 1;
+        let event = events[i];
+        let mut flag = |rule: CounterpointRule, event: &Event| {
+            violations.push(CounterpointViolation {
+                rule,
+                bar: event.bar,
+                note: event.note,
+            })
+        };
+        let interval = self.interval(&event);
+        let is_last = event.bar == self.last_bar;
+
+        if i == 0 && !(is_perfect(interval) && (self.above || interval % 12 == 0)) {
+            flag(CounterpointRule::ImperfectBeginning, &event);
+        }
+        if is_last && interval % 12 != 0 {
+            flag(CounterpointRule::ImperfectEnding, &event);
+        }
+        if (self.above && interval < 0) || (!self.above && interval > 0) {
+            flag(CounterpointRule::VoiceCrossing, &event);
+        }
+        if !is_consonant(interval) {
+            let may_suspend = matches!(self.species, Species::Fourth | Species::Fifth);
+            if event.downbeat && !(event.tied && may_suspend) {
+                flag(CounterpointRule::DissonantDownbeat, &event);
+            }
+            let may_pass = matches!(self.species, Species::Second | Species::Third | Species::Fifth);
+            let passes = may_pass && i > 0 && is_step(events[i - 1].pitch, event.pitch);
+            if !(event.downbeat || passes) {
+                flag(CounterpointRule::DissonantWeakBeat, &event);
+            }
+        }
+        if self.species == Species::First && event.downbeat && interval == 0 && i != 0 && !is_last {
+            flag(CounterpointRule::Unison, &event);
+        }
+
+        let Some(previous) = i.checked_sub(1).map(|previous| events[previous]) else {
+            return;
+        };
+        let previous_interval = self.interval(&previous);
+        if !is_consonant(previous_interval) {
+            if previous.downbeat && previous.tied {
+                // A suspension falls by step to a consonance
+                let falls = (1..=2).contains(&(previous.pitch - event.pitch));
+                if !falls || !is_consonant(interval) {
+                    flag(CounterpointRule::UnresolvedSuspension, &previous);
+                }
+            } else if !previous.downbeat {
+                // Passing tones continue in the same direction, neighbour tones need quarter notes
+                let passing = i > 1 && (previous.pitch - events[i - 2].pitch).signum() == (event.pitch - previous.pitch).signum();
+                let may_turn = matches!(self.species, Species::Third | Species::Fifth);
+                if !is_step(previous.pitch, event.pitch) || !(passing || may_turn) {
+                    flag(CounterpointRule::DissonantWeakBeat, &previous);
+                }
+            }
+        }
+        if is_last && !is_step(previous.pitch, event.pitch) {
+            flag(CounterpointRule::FinalNotByStep, &event);
+        }
+
+        let motion = event.pitch - previous.pitch;
+        if !event.tied {
+            if motion == 0 {
+                flag(CounterpointRule::RepeatedNote, &event);
+            }
+            let leap = motion.abs();
+            if matches!(leap, 6 | 9 | 10 | 11) || leap > 12 || motion == -8 {
+                flag(CounterpointRule::ForbiddenLeap, &event);
+            }
+        }
+
+        if self.is_accented(&event) {
+            let accented = events[..i].iter().rev().find(|e| self.is_accented(e));
+            if let Some(accented) = accented {
+                let before = self.interval(accented);
+                let cantus_moves = self.cantus[accented.bar] != self.cantus[event.bar];
+                let same = before.abs() % 12 == interval.abs() % 12;
+                if is_perfect(interval) && same && event.pitch != accented.pitch && cantus_moves {
+                    let rule = if interval.abs() % 12 == 7 {
+                        CounterpointRule::ParallelFifths
+                    } else {
+                        CounterpointRule::ParallelOctaves
+                    };
+                    flag(rule, &event);
+                    return;
+                }
+            }
+        }
+        // Both voices moving the same way into a perfect interval at a new cantus note
+        if event.downbeat && !event.tied && is_perfect(interval) && previous.bar != event.bar {
+            let cantus_motion = self.cantus[event.bar] - self.cantus[previous.bar];
+            if cantus_motion != 0 && cantus_motion.signum() == motion.signum() {
+                flag(CounterpointRule::HiddenPerfect, &event);
+            }
+        }
+    }
+}
+
+fn midi(pitches: &[PitchOctave]) -> Result<Vec<i8>> {
+    pitches.iter().map(|pitch| pitch.get_semitone_value()).collect()
+}
+
+impl Counterpoint {
+    /// Splits a line into bars against a cantus firmus of the given length. Fifth species has
+    /// no fixed rhythm, so must be built from bars directly.
+    pub fn from_line(species: Species, line: &Pitches, cantus_len: usize) -> Result<Counterpoint> {
+        if species == Species::Fifth || cantus_len == 0 {
+            return Err(Error::Unsupported);
+        }
+        let sizes = bar_sizes(species, cantus_len);
+        let mut notes = line.0.iter();
+        let mut bars = vec![];
+        let mut held: Option<PitchOctave> = None;
+        for (bar, size) in sizes.iter().enumerate() {
+            let mut pitches = vec![];
+            // The syncopated note of the previous bar carries over the bar line
+            if species == Species::Fourth && bar > 0 && bar < cantus_len - 1 {
+                pitches.push(held.ok_or(Error::OutofBounds)?);
+            }
+            while pitches.len() < *size {
+                pitches.push(*notes.next().ok_or(Error::OutofBounds)?);
+            }
+            held = pitches.last().copied();
+            bars.push(Pitches(pitches));
+        }
+        if notes.next().is_some() {
+            return Err(Error::OutofBounds);
+        }
+        Ok(Counterpoint { species, bars })
+    }
+
+    /// Returns the notes of the line in order, with tied notes sounded once
+    pub fn line(&self) -> Pitches {
+        let mut line: Vec<PitchOctave> = vec![];
+        for (bar, pitches) in self.bars.iter().enumerate() {
+            for (i, pitch) in pitches.0.iter().enumerate() {
+                let tied = self.is_tied(bar, i, line.last());
+                if !tied {
+                    line.push(*pitch);
+                }
+            }
+        }
+        Pitches(line)
+    }
+
+    fn is_tied(&self, bar: usize, note: usize, previous: Option<&PitchOctave>) -> bool {
+        let may_tie = matches!(self.species, Species::Fourth | Species::Fifth);
+        let pitch = &self.bars[bar].0[note];
+        may_tie && bar > 0 && note == 0 && previous.map(|previous| previous == pitch).unwrap_or(false)
+    }
+
+    fn events(&self) -> Result<Vec<Event>> {
+        let mut events: Vec<Event> = vec![];
+        let mut previous: Option<PitchOctave> = None;
+        for (bar, pitches) in self.bars.iter().enumerate() {
+            if ![1, 2, 4].contains(&pitches.0.len()) {
+                return Err(Error::Unsupported);
+            }
+            let opening_rest = self.species == Species::Fourth && bar == 0;
+            for (note, pitch) in pitches.0.iter().enumerate() {
+                events.push(Event {
+                    bar,
+                    note,
+                    pitch: pitch.get_semitone_value()?,
+                    downbeat: note == 0 && !opening_rest,
+                    tied: self.is_tied(bar, note, previous.as_ref()),
+                });
+                previous = Some(*pitch);
+            }
+        }
+        Ok(events)
+    }
+
+    /// Checks the counterpoint against a cantus firmus under Fux's rules. Whether the line lies
+    /// above or below the cantus is taken from its first note.
+    pub fn validate(&self, cantus: &Pitches) -> Result<Vec<CounterpointViolation>> {
+        if self.bars.len() != cantus.0.len() || cantus.0.is_empty() {
+            return Err(Error::OutofBounds);
+        }
+        let cantus = midi(&cantus.0)?;
+        let events = self.events()?;
+        let first = events.first().ok_or(Error::OutofBounds)?;
+        let checker = Checker {
+            species: self.species,
+            cantus: &cantus,
+            above: first.pitch >= cantus[first.bar],
+            last_bar: cantus.len() - 1,
+        };
+        let mut violations = vec![];
+        for i in 0..events.len() {
+            checker.check(&events[..=i], &mut violations);
+        }
+        Ok(violations)
+    }
+
+    /// Searches for a counterpoint to the cantus firmus using the notes of the key, above or
+    /// below it, that breaks none of the rules checked by `validate`
+    pub fn generate(cantus: &Pitches, key: &Key, species: Species, above: bool) -> Result<Counterpoint> {
+        if cantus.0.is_empty() {
+            return Err(Error::OutofBounds);
+        }
+        let cantus_midi = midi(&cantus.0)?;
+        let lowest = *cantus_midi.iter().min().ok_or(Error::OutofBounds)?;
+        let highest = *cantus_midi.iter().max().ok_or(Error::OutofBounds)?;
+        let (low, high) = if above {
+            (lowest, highest + GENERATED_RANGE)
+        } else {
+            (lowest - GENERATED_RANGE, highest)
+        };
+
+        // Every spelling of the key's scale within the range
+        let mut candidates: Vec<(i8, PitchOctave)> = vec![];
+        for pitch in key.scale()?.0 {
+            for octave in 0..=8u8 {
+                let candidate = PitchOctave::new(pitch.pitch, Octave::from(octave));
+                if let Ok(value) = candidate.get_semitone_value() {
+                    if (low..=high).contains(&value) && !candidates.iter().any(|(v, _)| *v == value) {
+                        candidates.push((value, candidate));
+                    }
+                }
+            }
+        }
+
+        let sizes = bar_sizes(species, cantus.0.len());
+        let slots: Vec<(usize, usize)> = sizes
+            .iter()
+            .enumerate()
+            .flat_map(|(bar, size)| (0..*size).map(move |note| (bar, note)))
+            .collect();
+        let checker = Checker {
+            species,
+            cantus: &cantus_midi,
+            above,
+            last_bar: cantus.0.len() - 1,
+        };
+        let mut search = Search {
+            checker,
+            slots,
+            candidates,
+            events: vec![],
+            chosen: vec![],
+            visited: 0,
+        };
+        if !search.place() {
+            return Err(Error::Unsupported);
+        }
+        let mut bars: Vec<Pitches> = sizes.iter().map(|_| Pitches(vec![])).collect();
+        for ((bar, _), pitch) in search.slots.iter().zip(search.chosen) {
+            bars[*bar].0.push(pitch);
+        }
+        Ok(Counterpoint { species, bars })
+    }
+}
+
+struct Search<'a> {
+    checker: Checker<'a>,
+    slots: Vec<(usize, usize)>,
+    candidates: Vec<(i8, PitchOctave)>,
+    events: Vec<Event>,
+    chosen: Vec<PitchOctave>,
+    visited: usize,
+}
+
+impl Search<'_> {
+    // Depth first search, trying the smallest melodic motion first
+    fn place(&mut self) -> bool {
+        let i = self.events.len();
+        if i == self.slots.len() {
+            return true;
+        }
+        let (bar, note) = self.slots[i];
+        let species = self.checker.species;
+        let previous = self.events.last().map(|event| event.pitch);
+        let opening_rest = species == Species::Fourth && bar == 0;
+        // Fourth species ties every syncopation over, other than into the final bar
+        let must_tie = species == Species::Fourth && note == 0 && bar > 0 && bar != self.checker.last_bar;
+        let anchor = previous.unwrap_or(self.checker.cantus[0] + if self.checker.above { 12 } else { -12 });
+        let mut order: Vec<(i8, PitchOctave)> = self.candidates.clone();
+        order.sort_by_key(|(value, _)| ((value - anchor).abs(), *value));
+
+        for (value, pitch) in order {
+            if must_tie && Some(value) != previous {
+                continue;
+            }
+            self.visited += 1;
+            if self.visited > SEARCH_LIMIT {
+                return false;
+            }
+            let may_tie = matches!(species, Species::Fourth | Species::Fifth);
+            self.events.push(Event {
+                bar,
+                note,
+                pitch: value,
+                downbeat: note == 0 && !opening_rest,
+                tied: may_tie && bar > 0 && note == 0 && Some(value) == previous,
+            });
+            let mut violations = vec![];
+            self.checker.check(&self.events, &mut violations);
+            if violations.is_empty() {
+                self.chosen.push(pitch);
+                if self.place() {
+                    return true;
+                }
+                self.chosen.pop();
+            }
+            self.events.pop();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn pitches(input: &str) -> Pitches {
+        Pitches(input.split_whitespace().map(|p| PitchOctave::from_str(p).unwrap()).collect())
+    }
+
+    #[test]
+    fn test_first_species_violations() {
+        let cantus = pitches("D3 F3 E3 D3");
+        let counterpoint = Counterpoint::from_line(Species::First, &pitches("A3 C4 B3 D4"), 4).unwrap();
+        let violations: Vec<(CounterpointRule, usize)> = counterpoint
+            .validate(&cantus)
+            .unwrap()
+            .iter()
+            .map(|violation| (violation.rule, violation.bar))
+            .collect();
+        assert_eq!(
+            violations,
+            vec![
+                (CounterpointRule::ParallelFifths, 1),
+                (CounterpointRule::ParallelFifths, 2),
+                (CounterpointRule::FinalNotByStep, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generated_species() {
+        // Fux's cantus firmus in the Dorian mode
+        let cantus = pitches("D3 F3 E3 D3 G3 F3 A3 G3 F3 E3 D3");
+        let key = Key::from_str("C major").unwrap();
+        for species in [Species::First, Species::Second, Species::Third, Species::Fourth, Species::Fifth] {
+            for above in [true, false] {
+                let counterpoint = Counterpoint::generate(&cantus, &key, species, above).unwrap();
+                assert_eq!(counterpoint.bars.len(), cantus.0.len());
+                assert!(counterpoint.validate(&cantus).unwrap().is_empty(), "{:?} {}", species, above);
+            }
+        }
+        let fourth = Counterpoint::generate(&cantus, &key, Species::Fourth, true).unwrap();
+        let line = fourth.line();
+        assert_eq!(Counterpoint::from_line(Species::Fourth, &line, cantus.0.len()).unwrap(), fourth);
+    }
+}
@@ -27,6 +27,8 @@ pub enum Error {
     ItemExists,
     #[error("Not Initialized")]
     NotInitialized,
+    #[error("Invalid Tone Row")]
+    InvalidToneRow,
     #[error("StrumParse {0}")]
     Strum(#[from] strum::ParseError),
 }
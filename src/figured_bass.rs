@@ -0,0 +1,401 @@
+use crate::chord::{Chord, Inversion};
+use crate::error::{Error, Result};
+use crate::interval::IntervalType;
+use crate::key::Key;
+use crate::pitch::{Alter, Octave, Pitch, PitchOctave};
+use crate::pitch_class::PitchClassSet;
+use crate::satb::{check_satb, FourPartChord, Voice};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{one_of, space0},
+    combinator::{all_consuming, map, opt, value},
+    multi::many0,
+    sequence::{delimited, pair, preceded},
+    IResult,
+};
+use std::fmt;
+use std::str::FromStr;
+
+/// A single figure, an interval number above the bass with an optional accidental. A natural
+/// is stored as `Some(Alter::None)`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Figure {
+    pub number: u8,
+    pub alter: Option<Alter>,
+}
+
+impl Figure {
+    pub fn new(number: u8, alter: Option<Alter>) -> Figure {
+        Figure { number, alter }
+    }
+}
+
+/// The figures written under a bass note, such as "6/4" or "#6". An empty figure is a root
+/// position triad.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FiguredBass {
+    pub figures: Vec<Figure>,
+}
+
+impl FiguredBass {
+    /// Returns every interval above the bass the figures imply, with abbreviated figures
+    /// completed, so "6" becomes 6/3 and "4/2" becomes 6/4/2
+    pub fn expanded(&self) -> Vec<Figure> {
+        let mut numbers: Vec<u8> = self.figures.iter().map(|figure| figure.number).collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        let implied: &[u8] = match numbers.as_slice() {
+            [] | [3] | [5] | [3, 5] => &[3, 5],
+            [6] | [3, 6] => &[3, 6],
+            [4, 6] => &[4, 6],
+            [7] | [3, 7] | [5, 7] | [3, 5, 7] => &[3, 5, 7],
+            [5, 6] | [3, 5, 6] => &[3, 5, 6],
+            [3, 4] | [3, 4, 6] => &[3, 4, 6],
+            [2] | [2, 4] | [2, 4, 6] => &[2, 4, 6],
+            _ => &[],
+        };
+        let mut figures: Vec<Figure> = if implied.is_empty() {
+            // Unusual figures are taken as written, over a third unless a suspension replaces it
+            let mut figures = self.figures.clone();
+            if !numbers.iter().any(|number| (2..=4).contains(number)) {
+                figures.push(Figure::new(3, None));
+            }
+            figures
+        } else {
+            implied.iter().map(|number| Figure::new(*number, None)).collect()
+        };
+        for written in &self.figures {
+            if let Some(figure) = figures.iter_mut().find(|figure| figure.number == written.number) {
+                figure.alter = written.alter;
+            }
+        }
+        figures.sort_by_key(|figure| figure.number);
+        figures
+    }
+
+    /// Returns the inversion of a triad or seventh chord figure
+    pub fn inversion(&self) -> Option<Inversion> {
+        let numbers: Vec<u8> = self.expanded().iter().map(|figure| figure.number).collect();
+        match numbers.as_slice() {
+            [3, 5] | [3, 5, 7] => Some(Inversion::Root),
+            [3, 6] | [3, 5, 6] => Some(Inversion::First),
+            [4, 6] | [3, 4, 6] => Some(Inversion::Second),
+            [2, 4, 6] => Some(Inversion::Third),
+            _ => None,
+        }
+    }
+
+    /// Returns the pitches above the bass, in the key unless an accidental alters them
+    pub fn upper_pitches(&self, bass: &PitchOctave, key: &Key) -> Result<Vec<Pitch>> {
+        let scale = key.scale()?;
+        let mut pitches = vec![];
+        for figure in self.expanded() {
+            let mut step = bass.pitch.step;
+            for _ in 1..figure.number {
+                step = step.increment();
+            }
+            let in_key = scale
+                .0
+                .iter()
+                .find(|pitch| pitch.pitch.step == step)
+                .map(|pitch| pitch.pitch.alter)
+                .ok_or(Error::OutofBounds)?;
+            let alter = match figure.alter {
+                Some(Alter::None) => Alter::None,
+                Some(alter) => Alter::from_semitones(i8::from(in_key) + i8::from(alter)).ok_or(Error::OutofBounds)?,
+                None => in_key,
+            };
+            pitches.push(Pitch::new(step, alter));
+        }
+        Ok(pitches)
+    }
+
+    /// Returns the intervals above the bass the figures call for in the key
+    pub fn intervals(&self, bass: &PitchOctave, key: &Key) -> Result<Vec<IntervalType>> {
+        let figures = self.expanded();
+        let pitches = self.upper_pitches(bass, key)?;
+        figures
+            .iter()
+            .zip(pitches)
+            .map(|(figure, pitch)| {
+                let semitones = (pitch.pitch_class() + 12 - bass.pitch.pitch_class()) % 12;
+                IntervalType::from_diatonic_semitones((figure.number - 1) % 7, semitones).ok_or(Error::Unsupported)
+            })
+            .collect()
+    }
+
+    /// Returns the chord the figures describe over the bass
+    pub fn chord(&self, bass: &PitchOctave, key: &Key) -> Result<Chord> {
+        let inversion = self.inversion().ok_or(Error::Unsupported)?;
+        let tones = self
+            .intervals(bass, key)?
+            .into_iter()
+            .map(|interval| bass.checked_add(interval).ok_or(Error::OutofBounds))
+            .collect::<Result<Vec<PitchOctave>>>()?;
+        // The root is the bass, or the sixth, fourth or second above it in the inversions
+        let root = match inversion {
+            Inversion::Root => *bass,
+            Inversion::First => tones[tones.len() - 1],
+            Inversion::Second => tones[tones.len() - 2],
+            Inversion::Third | Inversion::Fourth => tones[0],
+        };
+        let root = if inversion == Inversion::Root {
+            root
+        } else {
+            root.checked_sub(IntervalType::Octave).ok_or(Error::OutofBounds)?
+        };
+        let mut pitch_classes = vec![bass.pitch.pitch_class()];
+        pitch_classes.extend(tones.iter().map(|tone| tone.pitch.pitch_class()));
+        let (_, kind) = PitchClassSet::from_pitch_classes(&pitch_classes)
+            .matching_chords()
+            .into_iter()
+            .find(|(pitch_class, _)| *pitch_class == root.pitch.pitch_class())
+            .ok_or(Error::Unsupported)?;
+        Ok(Chord::new_as_inversion(root, kind, inversion))
+    }
+}
+
+// Every placement of the upper voices over a bass note that is complete, within each voice's
+// range, uncrossed and properly spaced, paired with a penalty for a weaker doubling
+fn voicings(bass: &PitchOctave, upper: &[Pitch]) -> Result<Vec<(u32, FourPartChord)>> {
+    let bass_midi = bass.get_semitone_value()?;
+    let mut distinct: Vec<Pitch> = vec![];
+    for pitch in upper {
+        if pitch.pitch_class() != bass.pitch.pitch_class() && !distinct.contains(pitch) {
+            distinct.push(*pitch);
+        }
+    }
+    // Triads double the bass first, then an upper tone. Larger chords keep their lowest tones.
+    let mut tone_sets: Vec<(u32, Vec<Pitch>)> = vec![];
+    match distinct.len() {
+        0 => return Err(Error::Unsupported),
+        1 => tone_sets.push((1, vec![distinct[0], bass.pitch, bass.pitch])),
+        2 => {
+            tone_sets.push((0, vec![distinct[0], distinct[1], bass.pitch]));
+            tone_sets.push((1, vec![distinct[0], distinct[1], distinct[0]]));
+            tone_sets.push((1, vec![distinct[0], distinct[1], distinct[1]]));
+        }
+        _ => tone_sets.push((0, distinct[..3].to_vec())),
+    }
+
+    let placements = |pitch: &Pitch, voice: Voice| -> Vec<PitchOctave> {
+        let (low, high) = voice.range();
+        (0..=8u8)
+            .map(|octave| PitchOctave::new(*pitch, Octave::from(octave)))
+            .filter(|placed| placed.get_semitone_value().map(|v| (low..=high).contains(&v)).unwrap_or(false))
+            .collect()
+    };
+    let mut voicings = vec![];
+    for (penalty, tones) in tone_sets {
+        for order in [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]] {
+            for soprano in placements(&tones[order[0]], Voice::Soprano) {
+                for alto in placements(&tones[order[1]], Voice::Alto) {
+                    for tenor in placements(&tones[order[2]], Voice::Tenor) {
+                        let (s, a, t) = (
+                            soprano.get_semitone_value()?,
+                            alto.get_semitone_value()?,
+                            tenor.get_semitone_value()?,
+                        );
+                        if s >= a && a >= t && t >= bass_midi && s - a <= 12 && a - t <= 12 {
+                            voicings.push((penalty, FourPartChord::new(soprano, alto, tenor, *bass)));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(voicings)
+}
+
+fn motion(from: &FourPartChord, to: &FourPartChord) -> Result<u32> {
+    let mut total = 0;
+    for voice in [Voice::Soprano, Voice::Alto, Voice::Tenor] {
+        total += (to.voice(voice).get_semitone_value()? - from.voice(voice).get_semitone_value()?).unsigned_abs() as u32;
+    }
+    Ok(total)
+}
+
+// The distance of the upper voices from the middle of their ranges, where the opening chord is
+// placed
+fn opening_cost(voicing: &FourPartChord) -> Result<u32> {
+    [Voice::Soprano, Voice::Alto, Voice::Tenor]
+        .iter()
+        .map(|voice| {
+            let (low, high) = voice.range();
+            Ok((voicing.voice(*voice).get_semitone_value()? - (low + (high - low) / 2)).unsigned_abs() as u32)
+        })
+        .sum()
+}
+
+// The given bass may break rules the upper voices cannot fix, such as its own range
+fn is_valid(chords: &[FourPartChord], key: &Key) -> Result<bool> {
+    let violations = check_satb(chords, key)?;
+    Ok(violations.iter().all(|violation| violation.voices == [Voice::Bass]))
+}
+
+/// Realizes a figured bass in four parts, choosing the smoothest upper voices that break none of
+/// the rules checked by `check_satb`
+pub fn realize(bass_line: &[(PitchOctave, FiguredBass)], key: &Key) -> Result<Vec<FourPartChord>> {
+    let options = bass_line
+        .iter()
+        .map(|(bass, figures)| voicings(bass, &figures.upper_pitches(bass, key)?))
+        .collect::<Result<Vec<Vec<(u32, FourPartChord)>>>>()?;
+    if options.iter().any(|chord_options| chord_options.is_empty()) {
+        return Err(Error::Unsupported);
+    }
+    // The rules only compare neighbouring chords, so the cheapest way to reach each voicing is
+    // found from the voicings of the chord before it. Each entry holds that cost and the
+    // previous voicing it came from.
+    let mut paths: Vec<Vec<Option<(u32, usize)>>> = vec![];
+    for (i, chord_options) in options.iter().enumerate() {
+        let mut row = vec![];
+        for (penalty, voicing) in chord_options {
+            let mut cheapest: Option<(u32, usize)> = None;
+            if i == 0 {
+                if is_valid(&[*voicing], key)? {
+                    cheapest = Some((opening_cost(voicing)?, 0));
+                }
+            } else {
+                for (j, (_, previous)) in options[i - 1].iter().enumerate() {
+                    let Some((cost, _)) = paths[i - 1][j] else { continue };
+                    if !is_valid(&[*previous, *voicing], key)? {
+                        continue;
+                    }
+                    let cost = cost + motion(previous, voicing)?;
+                    if cheapest.map_or(true, |(best, _)| cost < best) {
+                        cheapest = Some((cost, j));
+                    }
+                }
+            }
+            row.push(cheapest.map(|(cost, from)| (cost + penalty * 4, from)));
+        }
+        if row.iter().all(Option::is_none) {
+            return Err(Error::Unsupported);
+        }
+        paths.push(row);
+    }
+
+    let mut chosen = vec![];
+    let mut index = paths
+        .last()
+        .and_then(|row| row.iter().enumerate().filter_map(|(j, entry)| Some((entry.as_ref()?.0, j))).min())
+        .map(|(_, j)| j);
+    for (i, row) in paths.iter().enumerate().rev() {
+        let Some(j) = index else { break };
+        chosen.push(options[i][j].1);
+        index = row[j].map(|(_, from)| from);
+    }
+    chosen.reverse();
+    Ok(chosen)
+}
+
+fn parse_accidental(input: &str) -> IResult<&str, Alter> {
+    alt((
+        value(Alter::Sharp, alt((tag("#"), tag("♯"), tag("+")))),
+        value(Alter::Flat, alt((tag("b"), tag("♭")))),
+        value(Alter::None, alt((tag("n"), tag("♮")))),
+    ))(input)
+}
+
+// A number with an optional accidental before it, or an accidental alone which alters the third
+fn parse_figure(input: &str) -> IResult<&str, Figure> {
+    alt((
+        map(pair(opt(parse_accidental), one_of("23456789")), |(alter, number)| {
+            Figure::new(number as u8 - b'0', alter)
+        }),
+        map(parse_accidental, |alter| Figure::new(3, Some(alter))),
+    ))(input)
+}
+
+// Figures are separated by slashes or written together, as in "6/4" or "64"
+fn parse_figures(input: &str) -> IResult<&str, Vec<Figure>> {
+    many0(preceded(opt(delimited(space0, tag("/"), space0)), parse_figure))(input)
+}
+
+impl FromStr for FiguredBass {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<FiguredBass> {
+        all_consuming(parse_figures)(input.trim())
+            .map(|(_, figures)| FiguredBass { figures })
+            .map_err(|_| Error::Parse)
+    }
+}
+
+impl fmt::Display for FiguredBass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let figures: Vec<String> = self
+            .figures
+            .iter()
+            .map(|figure| {
+                let alter = match figure.alter {
+                    Some(Alter::None) => "n".to_string(),
+                    Some(alter) => alter.to_string(),
+                    None => String::new(),
+                };
+                // A lone accidental stands for the third
+                if figure.number == 3 && figure.alter.is_some() && self.figures.len() == 1 {
+                    alter
+                } else {
+                    format!("{}{}", alter, figure.number)
+                }
+            })
+            .collect();
+        write!(f, "{}", figures.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pitch(input: &str) -> PitchOctave {
+        PitchOctave::from_str(input).unwrap()
+    }
+
+    #[test]
+    fn test_figures() {
+        let key = Key::from_str("C major").unwrap();
+        let figures = FiguredBass::from_str("6/4").unwrap();
+        assert_eq!(figures.inversion(), Some(Inversion::Second));
+        assert_eq!(figures.intervals(&pitch("G2"), &key).unwrap(), vec![IntervalType::Fourth, IntervalType::MajorSixth]);
+        assert_eq!(figures.chord(&pitch("G2"), &key).unwrap().symbol().unwrap(), "C/G");
+
+        let figures = FiguredBass::from_str("4/2").unwrap();
+        let chord = figures.chord(&pitch("F2"), &key).unwrap();
+        assert_eq!(chord.symbol().unwrap(), "G7/F");
+
+        let key = Key::from_str("a minor").unwrap();
+        let figures = FiguredBass::from_str("#").unwrap();
+        assert_eq!(figures.to_string(), "#");
+        assert_eq!(figures.chord(&pitch("E2"), &key).unwrap().symbol().unwrap(), "E");
+        let figures = FiguredBass::from_str("#6").unwrap();
+        assert_eq!(figures.upper_pitches(&pitch("B2"), &key).unwrap()[1].to_string(), "G#");
+        assert_eq!(FiguredBass::from_str("6/x"), Err(Error::Parse));
+    }
+
+    #[test]
+    fn test_realization() {
+        let key = Key::from_str("C major").unwrap();
+        let bass_line: Vec<(PitchOctave, FiguredBass)> = [("C3", ""), ("A2", ""), ("F2", "6"), ("G2", "6/4"), ("G2", "7"), ("C3", "")]
+            .iter()
+            .map(|(bass, figures)| (pitch(bass), FiguredBass::from_str(figures).unwrap()))
+            .collect();
+        let realization = realize(&bass_line, &key).unwrap();
+        assert_eq!(realization.len(), 6);
+        assert!(check_satb(&realization, &key).unwrap().is_empty());
+        // The cadential six-four keeps its fourth and sixth above the bass
+        let six_four: Vec<String> = [realization[3].soprano, realization[3].alto, realization[3].tenor]
+            .iter()
+            .map(|pitch| pitch.pitch.to_string())
+            .collect();
+        assert!(six_four.contains(&"C".to_string()) && six_four.contains(&"E".to_string()));
+
+        // A closing bass above every tenor placement fails without searching the line before it
+        let mut unrealizable = [bass_line.clone(), bass_line.clone()].concat();
+        unrealizable.push((pitch("A4"), FiguredBass::default()));
+        assert_eq!(realize(&unrealizable, &key), Err(Error::Unsupported));
+        let long_line = [bass_line.clone(), bass_line.clone(), bass_line.clone(), bass_line].concat();
+        assert_eq!(realize(&long_line, &key).unwrap().len(), 24);
+    }
+}
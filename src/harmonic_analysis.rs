@@ -0,0 +1,318 @@
+use crate::chord::{Chord, ChordQuality, Inversion};
+use crate::key::{Key, Mode};
+use crate::pitch::Alter;
+use crate::progression::Progression;
+use crate::roman_numeral::RomanNumeral;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HarmonicFunction {
+    Tonic,
+    Predominant,
+    Dominant,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AppliedChordType {
+    SecondaryDominant,
+    SecondaryLeadingTone,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CadenceType {
+    PerfectAuthentic,
+    ImperfectAuthentic,
+    Half,
+    Plagal,
+    Deceptive,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cadence {
+    pub kind: CadenceType,
+    // Index of the chord the cadence arrives on
+    pub index: usize,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyChange {
+    // Index of the first chord heard in the new key
+    pub index: usize,
+    // Index of the chord shared by both keys just before the change, if there is one
+    pub pivot: Option<usize>,
+    pub key: Key,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnalyzedChord {
+    pub chord: Chord,
+    // The key in effect at this chord
+    pub key: Key,
+    // None for chords Roman numerals do not cover, such as extended chords
+    pub numeral: Option<RomanNumeral>,
+    pub function: Option<HarmonicFunction>,
+    pub applied: Option<AppliedChordType>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HarmonicAnalysis {
+    pub chords: Vec<AnalyzedChord>,
+    pub cadences: Vec<Cadence>,
+    pub key_changes: Vec<KeyChange>,
+}
+
+fn is_diatonic(chord: &Chord, key: &Key) -> bool {
+    chord
+        .kind
+        .pitch_class_set()
+        .transpose(chord.root.pitch.pitch_class())
+        .is_subset_of(&key.pitch_class_set())
+}
+
+fn interval_above(from: &Chord, to: &Chord) -> u8 {
+    (to.root.pitch.pitch_class() + 12 - from.root.pitch.pitch_class()) % 12
+}
+
+// Whether the chord is a dominant or leading tone chord resolving to the other
+fn resolves_to(dominant: &Chord, tonic: &Chord) -> bool {
+    match dominant.kind {
+        ChordQuality::Major | ChordQuality::Dom7 => interval_above(dominant, tonic) == 5,
+        ChordQuality::Diminished | ChordQuality::Diminished7 | ChordQuality::Minor7Flat5 => {
+            interval_above(dominant, tonic) == 1
+        }
+        _ => false,
+    }
+}
+
+fn tonic_key(chord: &Chord) -> Option<Key> {
+    match chord.kind {
+        ChordQuality::Major => Some(Key::new(chord.root, Mode::Major)),
+        ChordQuality::Minor => Some(Key::new(chord.root, Mode::Minor)),
+        _ => None,
+    }
+}
+
+fn is_tonic_of(chord: &Chord, key: &Key) -> bool {
+    tonic_key(chord)
+        .map(|tonic| tonic.mode == key.mode && tonic.tonic.pitch.pitch_class() == key.tonic.pitch.pitch_class())
+        .unwrap_or(false)
+}
+
+// A key change is flagged when an authentic cadence arrives on the tonic of another key and the
+// music carries on without returning straight to the previous tonic. A return, or an arrival on
+// the final chord, is read as a tonicization or half cadence instead.
+fn find_key_changes(chords: &[Chord], key: &Key) -> Vec<KeyChange> {
+    let mut key_changes = vec![];
+    let mut current = *key;
+    let mut floor = 0;
+    for arrival in 1..chords.len() {
+        let Some(new_key) = tonic_key(&chords[arrival]) else {
+            continue;
+        };
+        if !resolves_to(&chords[arrival - 1], &chords[arrival]) || is_tonic_of(&chords[arrival], &current) {
+            continue;
+        }
+        if chords.get(arrival + 1).map(|next| is_tonic_of(next, &current)).unwrap_or(true) {
+            continue;
+        }
+        // Walk back over the chords that already belong to the new key
+        let mut start = arrival - 1;
+        while start > floor && is_diatonic(&chords[start - 1], &new_key) {
+            start -= 1;
+        }
+        // The new key must bring in a chord foreign to the old one, otherwise I to IV in a major
+        // key would read as a modulation
+        let Some(index) = (start..=arrival).find(|i| !is_diatonic(&chords[*i], &current)) else {
+            continue;
+        };
+        let pivot = index
+            .checked_sub(1)
+            .filter(|pivot| *pivot >= floor && is_diatonic(&chords[*pivot], &current) && is_diatonic(&chords[*pivot], &new_key));
+        key_changes.push(KeyChange {
+            index,
+            pivot,
+            key: new_key,
+        });
+        current = new_key;
+        floor = arrival;
+    }
+    key_changes
+}
+
+fn harmonic_function(numeral: &RomanNumeral, next: Option<&RomanNumeral>) -> HarmonicFunction {
+    if numeral.secondary.is_some() {
+        return HarmonicFunction::Dominant;
+    }
+    if numeral.quality.is_augmented_sixth() {
+        return HarmonicFunction::Predominant;
+    }
+    // A cadential six-four embellishes the dominant that follows it
+    let next_is_dominant = next.map(|next| next.degree == 5 && next.secondary.is_none()).unwrap_or(false);
+    if numeral.degree == 1 && numeral.inversion == Inversion::Second && next_is_dominant {
+        return HarmonicFunction::Dominant;
+    }
+    match numeral.degree {
+        2 | 4 => HarmonicFunction::Predominant,
+        5 | 7 => HarmonicFunction::Dominant,
+        _ => HarmonicFunction::Tonic,
+    }
+}
+
+fn cadence(previous: &RomanNumeral, arrival: &RomanNumeral, is_last: bool) -> Option<CadenceType> {
+    if arrival.secondary.is_some() {
+        return None;
+    }
+    let arrives_on_dominant = arrival.degree == 5
+        && arrival.alter == Alter::None
+        && matches!(arrival.quality, ChordQuality::Major | ChordQuality::Dom7);
+    if is_last && arrives_on_dominant {
+        return Some(CadenceType::Half);
+    }
+    if previous.secondary.is_some() {
+        return None;
+    }
+    let is_dominant = previous.degree == 5 && matches!(previous.quality, ChordQuality::Major | ChordQuality::Dom7);
+    let is_leading_tone = previous.degree == 7
+        && matches!(
+            previous.quality,
+            ChordQuality::Diminished | ChordQuality::Diminished7 | ChordQuality::Minor7Flat5
+        );
+    let arrives_on = |degree: u8| arrival.degree == degree && arrival.alter == Alter::None;
+    if is_dominant && arrives_on(1) {
+        if previous.inversion == Inversion::Root && arrival.inversion == Inversion::Root {
+            Some(CadenceType::PerfectAuthentic)
+        } else {
+            Some(CadenceType::ImperfectAuthentic)
+        }
+    } else if is_leading_tone && arrives_on(1) {
+        Some(CadenceType::ImperfectAuthentic)
+    } else if previous.degree == 4 && arrives_on(1) {
+        Some(CadenceType::Plagal)
+    } else if is_dominant && arrives_on(6) {
+        Some(CadenceType::Deceptive)
+    } else {
+        None
+    }
+}
+
+/// Analyzes a chord sequence starting in the given key. Authentic, plagal and deceptive
+/// cadences are reported wherever they occur, half cadences only on the final chord.
+pub fn analyze_harmony(chords: &[Chord], key: &Key) -> HarmonicAnalysis {
+    let key_changes = find_key_changes(chords, key);
+    let keys: Vec<Key> = (0..chords.len())
+        .map(|i| {
+            key_changes
+                .iter()
+                .rev()
+                .find(|change| change.index <= i)
+                .map(|change| change.key)
+                .unwrap_or(*key)
+        })
+        .collect();
+    let numerals: Vec<Option<RomanNumeral>> = chords
+        .iter()
+        .zip(&keys)
+        .map(|(chord, key)| chord.roman_numeral(key).ok())
+        .collect();
+
+    let mut analysis = HarmonicAnalysis {
+        key_changes,
+        ..HarmonicAnalysis::default()
+    };
+    for (i, chord) in chords.iter().enumerate() {
+        let numeral = numerals[i].clone();
+        let next = numerals.get(i + 1).and_then(|next| next.as_ref());
+        let function = numeral.as_ref().map(|numeral| harmonic_function(numeral, next));
+        let applied = numeral
+            .as_ref()
+            .filter(|numeral| numeral.secondary.is_some())
+            .map(|numeral| {
+                if numeral.degree == 7 {
+                    AppliedChordType::SecondaryLeadingTone
+                } else {
+                    AppliedChordType::SecondaryDominant
+                }
+            });
+        if i > 0 {
+            if let (Some(previous), Some(arrival)) = (&numerals[i - 1], &numeral) {
+                if let Some(kind) = cadence(previous, arrival, i == chords.len() - 1) {
+                    analysis.cadences.push(Cadence { kind, index: i });
+                }
+            }
+        }
+        analysis.chords.push(AnalyzedChord {
+            chord: chord.clone(),
+            key: keys[i],
+            numeral,
+            function,
+            applied,
+        });
+    }
+    analysis
+}
+
+impl Progression {
+    pub fn harmonic_analysis(&self, key: &Key) -> HarmonicAnalysis {
+        let chords: Vec<Chord> = self.iter().map(|entry| entry.chord.clone()).collect();
+        analyze_harmony(&chords, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn analyze(symbols: &str) -> HarmonicAnalysis {
+        let key = Key::from_str("C major").unwrap();
+        Progression::from_str(symbols).unwrap().harmonic_analysis(&key)
+    }
+
+    #[test]
+    fn test_functions_and_cadences() {
+        let analysis = analyze("C Am Dm7 G7 C");
+        let functions: Vec<HarmonicFunction> = analysis.chords.iter().map(|c| c.function.unwrap()).collect();
+        assert_eq!(
+            functions,
+            vec![
+                HarmonicFunction::Tonic,
+                HarmonicFunction::Tonic,
+                HarmonicFunction::Predominant,
+                HarmonicFunction::Dominant,
+                HarmonicFunction::Tonic,
+            ]
+        );
+        assert_eq!(analysis.cadences, vec![Cadence { kind: CadenceType::PerfectAuthentic, index: 4 }]);
+
+        let cadences = |symbols: &str| -> Vec<CadenceType> { analyze(symbols).cadences.iter().map(|c| c.kind).collect() };
+        assert_eq!(cadences("C F G Am"), vec![CadenceType::Deceptive]);
+        assert_eq!(cadences("C Dm G"), vec![CadenceType::Half]);
+        assert_eq!(cadences("C F C"), vec![CadenceType::Plagal]);
+        assert_eq!(cadences("C/E G7/D C"), vec![CadenceType::ImperfectAuthentic]);
+
+        let analysis = analyze("C C/G G C");
+        assert_eq!(analysis.chords[1].function, Some(HarmonicFunction::Dominant));
+    }
+
+    #[test]
+    fn test_applied_chords_and_key_changes() {
+        // A brief tonicization of the dominant
+        let analysis = analyze("C D7 G C");
+        assert_eq!(analysis.chords[1].applied, Some(AppliedChordType::SecondaryDominant));
+        assert_eq!(analysis.chords[1].numeral.as_ref().unwrap().to_string(), "V7/V");
+        assert!(analysis.key_changes.is_empty());
+
+        // A modulation to the dominant through a pivot chord
+        let analysis = analyze("C Am D7 G Em Am D7 G");
+        assert_eq!(analysis.key_changes.len(), 1);
+        let change = analysis.key_changes[0];
+        assert_eq!((change.index, change.pivot), (2, Some(1)));
+        assert_eq!(change.key.to_string(), "G major");
+        assert_eq!(analysis.chords[5].numeral.as_ref().unwrap().to_string(), "ii");
+        assert_eq!(analysis.cadences.last().unwrap().kind, CadenceType::PerfectAuthentic);
+
+        let analysis = analyze("C F#dim7 G");
+        assert_eq!(analysis.chords[1].applied, Some(AppliedChordType::SecondaryLeadingTone));
+        assert_eq!(analysis.cadences, vec![Cadence { kind: CadenceType::Half, index: 2 }]);
+        assert!(analysis.key_changes.is_empty());
+    }
+}
@@ -64,6 +64,8 @@ pub enum Quality {
 }
 
 pub type NumericIntervalsSlice = [u8];
+// Intervals in cents, for scales whose tones fall outside of 12-TET
+pub type CentsIntervalsSlice = [u16];
 
 #[derive(Debug, Copy, Clone, PartialEq, EnumString)]
 pub enum IntervalType {
@@ -195,7 +197,7 @@ impl IntervalType {
             IntervalType::AugmentedFifth => Self::MINOR_SIXTH_INTERVAL_SEMITONES,
             IntervalType::MinorSixth => Self::MINOR_SIXTH_INTERVAL_SEMITONES,
             IntervalType::MajorSixth => Self::MAJOR_SIXTH_INTERVAL_SEMITONES,
-            IntervalType::AugmentedSixth => Self::MAJOR_SIXTH_INTERVAL_SEMITONES,
+            IntervalType::AugmentedSixth => Self::MINOR_SEVENTH_INTERVAL_SEMITONES,
             IntervalType::DiminishedSeventh => Self::MAJOR_SIXTH_INTERVAL_SEMITONES,
             IntervalType::MinorSeventh => Self::MINOR_SEVENTH_INTERVAL_SEMITONES,
             IntervalType::MajorSeventh => Self::MAJOR_SEVENTH_INTERVAL_SEMITONES,
@@ -205,6 +207,38 @@ impl IntervalType {
         }
     }
 
+    /// Returns the interval spanning the given number of diatonic steps and semitones, which
+    /// determines how the upper note of the interval is spelled
+    pub fn from_diatonic_semitones(diatonic: u8, semitones: u8) -> Option<IntervalType> {
+        match (diatonic, semitones) {
+            (Self::UNISON_DIATONIC_INC, 0) => Some(IntervalType::Unison),
+            (Self::SECOND_DIATONIC_INC, 0) => Some(IntervalType::DiminishedSecond),
+            (Self::SECOND_DIATONIC_INC, 1) => Some(IntervalType::MinorSecond),
+            (Self::SECOND_DIATONIC_INC, 2) => Some(IntervalType::MajorSecond),
+            (Self::SECOND_DIATONIC_INC, 3) => Some(IntervalType::AugmentedSecond),
+            (Self::THIRD_DIATONIC_INC, 2) => Some(IntervalType::DiminishedThird),
+            (Self::THIRD_DIATONIC_INC, 3) => Some(IntervalType::MinorThird),
+            (Self::THIRD_DIATONIC_INC, 4) => Some(IntervalType::MajorThird),
+            (Self::THIRD_DIATONIC_INC, 5) => Some(IntervalType::AugmentedThird),
+            (Self::FOURTH_DIATONIC_INC, 4) => Some(IntervalType::DiminishedFourth),
+            (Self::FOURTH_DIATONIC_INC, 5) => Some(IntervalType::Fourth),
+            (Self::FOURTH_DIATONIC_INC, 6) => Some(IntervalType::AugmentedFourth),
+            (Self::FIFTH_DIATONIC_INC, 6) => Some(IntervalType::DiminishedFifth),
+            (Self::FIFTH_DIATONIC_INC, 7) => Some(IntervalType::Fifth),
+            (Self::FIFTH_DIATONIC_INC, 8) => Some(IntervalType::AugmentedFifth),
+            (Self::SIXTH_DIATONIC_INC, 7) => Some(IntervalType::DiminishedSixth),
+            (Self::SIXTH_DIATONIC_INC, 8) => Some(IntervalType::MinorSixth),
+            (Self::SIXTH_DIATONIC_INC, 9) => Some(IntervalType::MajorSixth),
+            (Self::SIXTH_DIATONIC_INC, 10) => Some(IntervalType::AugmentedSixth),
+            (Self::SEVENTH_DIATONIC_INC, 9) => Some(IntervalType::DiminishedSeventh),
+            (Self::SEVENTH_DIATONIC_INC, 10) => Some(IntervalType::MinorSeventh),
+            (Self::SEVENTH_DIATONIC_INC, 11) => Some(IntervalType::MajorSeventh),
+            (Self::SEVENTH_DIATONIC_INC, 12) => Some(IntervalType::AugmentedSeventh),
+            (Self::EIGHTH_DIATONIC_INC, 11) => Some(IntervalType::DiminishedEighth),
+            (Self::EIGHTH_DIATONIC_INC, 12) => Some(IntervalType::Octave),
+            _ => None,
+        }
+    }
 }
 
 impl From<u8> for IntervalType {
@@ -0,0 +1,398 @@
+use crate::error::{Error, Result};
+use crate::key::{Key, Mode};
+use crate::meter::{split_tie, Measure, TimeSignature};
+use crate::pitch::{Alter, Octave, Pitch, PitchOctave, Step};
+use crate::rhythm::{Duration, Event, Note, Rest, Tie, MAX_DOTS};
+use crate::score::{Part, Score};
+use num::rational::Ratio;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+// The reference record holding the title
+const TITLE_RECORD: &str = "!!!OTL:";
+
+// A note or rest of a kern token, chords holding several separated by spaces
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct KernNote {
+    // None for a rest
+    pitch: Option<PitchOctave>,
+    beats: Ratio<u32>,
+    tie: Tie,
+    grace: bool,
+}
+
+// Letters give the octave by case and repetition: c is C4, cc C5, C C3 and CC C2
+fn kern_pitch(letter: char, count: i32, sharps: u8, flats: u8) -> Result<PitchOctave> {
+    let step = Step::from_str(&letter.to_ascii_uppercase().to_string())?;
+    let alter = match (sharps, flats) {
+        (0, 0) => Alter::None,
+        (1, 0) => Alter::Sharp,
+        (2, 0) => Alter::DoubleSharp,
+        (0, 1) => Alter::Flat,
+        (0, 2) => Alter::DoubleFlat,
+        _ => return Err(Error::Unsupported),
+    };
+    let octave = if letter.is_ascii_lowercase() { 3 + count } else { 4 - count };
+    if !(0..=Octave::Octave8 as i32).contains(&octave) {
+        return Err(Error::OutofBounds);
+    }
+    Ok(PitchOctave::new(Pitch::new(step, alter), Octave::from(octave as u8)))
+}
+
+// Durations are reciprocals of the whole note, so 4 is a quarter, 6 a triplet quarter and 20 a
+// quintuplet sixteenth, with 0 for a breve and "n%m" for m/n of a whole note
+fn kern_beats(reciprocal: &str, multiple: Option<&str>, dots: u32) -> Result<Ratio<u32>> {
+    if dots > MAX_DOTS as u32 {
+        return Err(Error::Unsupported);
+    }
+    let beats = match reciprocal {
+        "0" => Ratio::from_integer(8),
+        "00" => Ratio::from_integer(16),
+        reciprocal => {
+            let reciprocal: u32 = reciprocal.parse().map_err(|_| Error::Parse)?;
+            let multiple: u32 = match multiple {
+                Some(multiple) => multiple.parse().map_err(|_| Error::Parse)?,
+                None => 1,
+            };
+            let whole = multiple.checked_mul(4).filter(|_| multiple > 0 && reciprocal > 0).ok_or(Error::OutofBounds)?;
+            Ratio::new(whole, reciprocal)
+        }
+    };
+    Ok(beats * (Ratio::from_integer(2) - Ratio::new(1, 1 << dots)))
+}
+
+fn parse_kern_note(token: &str) -> Result<KernNote> {
+    let mut reciprocal = String::new();
+    let mut multiple: Option<String> = None;
+    let mut dots = 0;
+    let mut letter: Option<(char, i32)> = None;
+    let (mut sharps, mut flats) = (0, 0);
+    let mut rest = false;
+    let mut tie = Tie::None;
+    let mut grace = false;
+    for c in token.chars() {
+        match c {
+            '0'..='9' => match &mut multiple {
+                Some(multiple) => multiple.push(c),
+                None => reciprocal.push(c),
+            },
+            '%' => multiple = Some(String::new()),
+            '.' => dots += 1,
+            'a'..='g' | 'A'..='G' => {
+                letter = match letter {
+                    None => Some((c, 1)),
+                    Some((previous, count)) if previous == c => Some((c, count + 1)),
+                    Some(_) => return Err(Error::Parse),
+                }
+            }
+            '#' => sharps += 1,
+            '-' => flats += 1,
+            'r' => rest = true,
+            '[' => tie = Tie::Start,
+            '_' => tie = Tie::Continue,
+            ']' => tie = Tie::Stop,
+            'q' | 'Q' => grace = true,
+            // Naturals, articulations, slurs, beams and editorial signs don't change the note
+            _ => {}
+        }
+    }
+    let pitch = match letter {
+        Some((letter, count)) if !rest => Some(kern_pitch(letter, count, sharps, flats)?),
+        None if rest => None,
+        _ => return Err(Error::Parse),
+    };
+    let beats = if grace && reciprocal.is_empty() {
+        Ratio::from_integer(0)
+    } else {
+        kern_beats(&reciprocal, multiple.as_deref(), dots)?
+    };
+    Ok(KernNote { pitch, beats, tie, grace })
+}
+
+// Follows one **kern spine, the first of its sub-spines when it splits into voices
+struct SpineReader {
+    name: String,
+    key: Option<Key>,
+    // The key read from the signature, used when no key is named
+    signature: Option<Key>,
+    time_signature: TimeSignature,
+    events: Vec<Event>,
+    measures: Vec<Measure>,
+}
+
+impl SpineReader {
+    fn new() -> SpineReader {
+        SpineReader {
+            name: String::new(),
+            key: None,
+            signature: None,
+            time_signature: TimeSignature { groups: vec![1; 4], denominator: 4 },
+            events: vec![],
+            measures: vec![],
+        }
+    }
+
+    fn interpret(&mut self, token: &str) -> Result<()> {
+        if let Some(signature) = token.strip_prefix("*k[").and_then(|signature| signature.strip_suffix(']')) {
+            let sharps = signature.matches('#').count() as i8;
+            let flats = signature.matches('-').count() as i8;
+            self.signature = Some(Key::from_fifths(sharps - flats, Mode::Major)?);
+        } else if let Some(meter) =
+            token.strip_prefix("*M").filter(|meter| meter.starts_with(|c: char| c.is_ascii_digit()))
+        {
+            self.time_signature = TimeSignature::from_str(meter)?;
+        } else if let Some(name) = token.strip_prefix("*I\"") {
+            self.name = name.to_string();
+        } else if let Some(key) = token.strip_prefix('*').and_then(|key| key.strip_suffix(':')) {
+            // Keys are named by their tonic, upper case for major, as in *G: or *b-:
+            let mut chars = key.chars();
+            let Some(step) = chars.next().filter(|c| c.is_ascii_alphabetic()) else {
+                return Ok(());
+            };
+            let alter = match chars.as_str() {
+                "" => Alter::None,
+                "#" => Alter::Sharp,
+                "-" => Alter::Flat,
+                _ => return Ok(()),
+            };
+            let mode = if step.is_ascii_uppercase() { Mode::Major } else { Mode::Minor };
+            let tonic = Pitch::new(Step::from_str(&step.to_ascii_uppercase().to_string())?, alter);
+            self.key.get_or_insert(Key::new(PitchOctave::new(tonic, Octave::default()), mode));
+        }
+        Ok(())
+    }
+
+    fn read_token(&mut self, token: &str) -> Result<()> {
+        // A period holds the place of a note still sounding
+        if token == "." {
+            return Ok(());
+        }
+        let notes = token
+            .split(' ')
+            .filter(|note| !note.is_empty())
+            .map(parse_kern_note)
+            .collect::<Result<Vec<KernNote>>>()?;
+        let notes: Vec<KernNote> = notes.into_iter().filter(|note| !note.grace).collect();
+        let Some(first) = notes.first() else {
+            return Ok(());
+        };
+        let durations = match Duration::from_beats(first.beats) {
+            Some(duration) => vec![duration],
+            None => Duration::tied_from_beats(first.beats)?,
+        };
+        let count = durations.len();
+        for (i, duration) in durations.into_iter().enumerate() {
+            let mut sounding: Vec<Note> = notes
+                .iter()
+                .filter_map(|note| {
+                    note.pitch.map(|pitch| Note {
+                        tie: split_tie(note.tie, i == 0, i == count - 1),
+                        ..Note::new(pitch, duration)
+                    })
+                })
+                .collect();
+            self.events.push(match sounding.len() {
+                0 => Event::Rest(Rest::new(duration)),
+                1 => Event::Note(sounding.remove(0)),
+                _ => Event::Chord(sounding),
+            });
+        }
+        Ok(())
+    }
+
+    fn bar(&mut self) {
+        if !self.events.is_empty() {
+            self.measures.push(Measure {
+                time_signature: self.time_signature.clone(),
+                events: std::mem::take(&mut self.events),
+            });
+        }
+    }
+
+    fn finish(mut self, index: usize) -> Part {
+        self.bar();
+        Part {
+            id: format!("spine{}", index + 1),
+            name: self.name,
+            key: self.key.or(self.signature),
+            measures: self.measures,
+            harmonies: vec![],
+        }
+    }
+}
+
+// A column of the file, belonging to a **kern spine or to another kind of spine when None
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Column {
+    spine: Option<usize>,
+    // Whether the column is the one followed when the spine splits
+    primary: bool,
+}
+
+// Applies a line of interpretations, returning the columns of the lines that follow
+fn interpret(tokens: &[&str], columns: &[Column], readers: &mut [SpineReader]) -> Result<Vec<Column>> {
+    let mut next = vec![];
+    let mut i = 0;
+    while i < tokens.len() {
+        let column = columns[i];
+        match tokens[i] {
+            "*^" => {
+                next.push(column);
+                next.push(Column { primary: false, ..column });
+            }
+            "*v" => {
+                let mut joined = column;
+                while tokens.get(i + 1) == Some(&"*v") {
+                    i += 1;
+                    joined.primary |= columns[i].primary;
+                }
+                next.push(joined);
+            }
+            "*-" => {}
+            "*+" | "*x" => return Err(Error::Unsupported),
+            token => {
+                if let (Some(spine), true) = (column.spine, column.primary) {
+                    readers[spine].interpret(token)?;
+                }
+                next.push(column);
+            }
+        }
+        i += 1;
+    }
+    Ok(next)
+}
+
+/// Reads the **kern spines of a Humdrum file as parts, in the order of the file, which is
+/// usually lowest first. Other kinds of spines are skipped, as are the later voices of a spine
+/// that splits.
+pub fn read_kern(input: &str) -> Result<Score> {
+    let mut title = None;
+    let mut readers = vec![];
+    let mut columns: Vec<Column> = vec![];
+    for line in input.lines() {
+        if let Some(record) = line.strip_prefix(TITLE_RECORD) {
+            title.get_or_insert(record.trim().to_string());
+            continue;
+        }
+        if line.is_empty() || line.starts_with('!') {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split('\t').collect();
+        if columns.is_empty() {
+            // The first line names the kind of each spine
+            for token in tokens {
+                if token == "**kern" {
+                    columns.push(Column { spine: Some(readers.len()), primary: true });
+                    readers.push(SpineReader::new());
+                } else if token.starts_with("**") {
+                    columns.push(Column { spine: None, primary: false });
+                } else {
+                    return Err(Error::Parse);
+                }
+            }
+            continue;
+        }
+        if tokens.len() != columns.len() {
+            return Err(Error::Parse);
+        }
+        let followed = columns
+            .iter()
+            .zip(&tokens)
+            .filter_map(|(column, token)| column.spine.filter(|_| column.primary).map(|spine| (spine, *token)));
+        if line.starts_with('*') {
+            columns = interpret(&tokens, &columns, &mut readers)?;
+        } else if line.starts_with('=') {
+            for (spine, _) in followed {
+                readers[spine].bar();
+            }
+        } else {
+            for (spine, token) in followed {
+                readers[spine].read_token(token)?;
+            }
+        }
+    }
+    Ok(Score {
+        title,
+        parts: readers.into_iter().enumerate().map(|(i, reader)| reader.finish(i)).collect(),
+    })
+}
+
+pub fn read_kern_file<P: AsRef<Path>>(path: P) -> Result<Score> {
+    let input = fs::read_to_string(path).map_err(|error| Error::IoKind(error.to_string()))?;
+    read_kern(&input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key_detection::{detect_key_weighted, KeyProfile};
+    use crate::rhythm::NoteValue;
+
+    const CHORALE: &str = "!!!COM: Bach, Johann Sebastian
+!!!OTL: Aus meines Herzens Grunde
+**kern\t**kern\t**dynam
+*I\"Bass\t*I\"Soprano\t*
+*k[f#]\t*k[f#]\t*
+*G:\t*G:\t*
+*M3/4\t*M3/4\t*
+4GG\t4g\tp
+=1\t=1\t=1
+4G\t8.a\t.
+.\t16b\t.
+4F#\t[4cc 4ee\t.
+8E\t8cc]\t.
+8D\t8b\t.
+=2\t=2\t=2
+2.G;\t2.g;\t.
+==\t==\t==
+*-\t*-\t*-
+";
+
+    #[test]
+    fn test_read_kern() {
+        let score = read_kern(CHORALE).unwrap();
+        assert_eq!(score.title.as_deref(), Some("Aus meines Herzens Grunde"));
+        assert_eq!(score.parts.len(), 2);
+        let (bass, soprano) = (&score.parts[0], &score.parts[1]);
+        assert_eq!((bass.name.as_str(), soprano.name.as_str()), ("Bass", "Soprano"));
+        assert_eq!(soprano.key.unwrap().to_string(), "G major");
+        // A pickup, then two full measures
+        assert_eq!(soprano.measures.len(), 3);
+        assert_eq!(soprano.measures[1].time_signature.to_string(), "3/4");
+        assert!(soprano.measures[1..].iter().all(|measure| measure.is_full()));
+        assert_eq!(bass.events()[0].pitches()[0].to_string(), "G2");
+        assert_eq!(bass.events()[2].pitches()[0].to_string(), "F#3");
+
+        let events = &soprano.measures[1].events;
+        assert_eq!(events[0].duration().unwrap(), Duration::dotted(NoteValue::Eighth, 1).unwrap());
+        let Event::Chord(notes) = &events[2] else { panic!() };
+        assert_eq!(notes[0].pitch.to_string(), "C5");
+        assert_eq!((notes[0].tie, notes[1].tie), (Tie::Start, Tie::None));
+        let Event::Note(stop) = events[3] else { panic!() };
+        assert_eq!(stop.tie, Tie::Stop);
+
+        let estimates = detect_key_weighted(&soprano.events(), KeyProfile::KrumhanslKessler).unwrap();
+        assert_eq!(estimates[0].key.to_string(), "G major");
+    }
+
+    #[test]
+    fn test_kern_spine_splits() {
+        let split = "**kern\n*M2/4\n*^\n4c\t4e\n8d\t4f\n8r\t.\n*v\t*v\n4.g\n12a\n=\n*-\n";
+        let score = read_kern(split).unwrap();
+        let events = score.parts[0].events();
+        let pitches: Vec<String> = events.iter().flat_map(|event| event.pitches()).map(|p| p.to_string()).collect();
+        assert_eq!(pitches, vec!["C4", "D4", "G4", "A4"]);
+        assert_eq!(events[2], Event::Rest(Rest::new(Duration::new(NoteValue::Eighth))));
+        assert_eq!(events[4].beats(), Ratio::new(1, 3));
+        // Reciprocals with odd factors other than three are tuplets
+        let score = read_kern("**kern\n20c\n10d\n28e\n*-\n").unwrap();
+        let events = score.parts[0].events();
+        let durations: Vec<String> = events.iter().map(|event| event.duration().unwrap().to_string()).collect();
+        assert_eq!(durations, vec!["16(5:4)", "8(5:4)", "16(7:4)"]);
+        assert_eq!(read_kern(&format!("**kern\n4c{}\n*-\n", ".".repeat(40))), Err(Error::Unsupported));
+        assert_eq!(read_kern("**kern\n1%4000000000c\n"), Err(Error::OutofBounds));
+        assert_eq!(read_kern("**kern\n*+\n"), Err(Error::Unsupported));
+        assert_eq!(read_kern("**kern\n4c\t4d\n"), Err(Error::Parse));
+    }
+}
@@ -0,0 +1,163 @@
+use crate::chord::ChordQuality;
+use crate::error::{Error, Result};
+use crate::interval::IntervalType;
+use crate::notes::Notes;
+use crate::pitch::{Alter, Octave, Pitch, PitchOctave, Pitches, Step};
+use crate::pitch_class::PitchClassSet;
+use crate::scale::{Scale, ScaleType};
+use std::fmt;
+use std::str::FromStr;
+use strum::EnumString;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash, EnumString)]
+#[strum(ascii_case_insensitive)]
+pub enum Mode {
+    #[default]
+    Major,
+    Minor,
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Key {
+    pub tonic: PitchOctave,
+    pub mode: Mode,
+}
+
+impl Key {
+    pub fn new(tonic: PitchOctave, mode: Mode) -> Key {
+        Key { tonic, mode }
+    }
+
+    pub fn scale_type(&self) -> ScaleType {
+        match self.mode {
+            Mode::Major => ScaleType::Ionian,
+            Mode::Minor => ScaleType::Aeolian,
+        }
+    }
+
+    pub fn scale(&self) -> Result<Pitches> {
+        Scale::new(self.tonic, self.scale_type()).notes()
+    }
+
+    /// Returns the semitones above the tonic of a scale degree, counted from 1. In minor keys
+    /// the seventh degree is the leading tone under diminished chords, so vii° needs no accidental.
+    pub fn degree_semitones(&self, degree: u8, quality: ChordQuality) -> u8 {
+        let degree = (degree + 6) % 7;
+        if degree == 0 {
+            return 0;
+        }
+        let semitones = self.scale_type().scale_interval()[degree as usize - 1];
+        let is_diminished = matches!(
+            quality,
+            ChordQuality::Diminished | ChordQuality::Diminished7 | ChordQuality::Minor7Flat5
+        );
+        if self.mode == Mode::Minor && degree == 6 && is_diminished {
+            semitones + 1
+        } else {
+            semitones
+        }
+    }
+
+    /// Returns the root of a chord built on a scale degree, chromatically altered
+    pub fn degree_pitch(&self, degree: u8, alter: Alter, quality: ChordQuality) -> Result<PitchOctave> {
+        if !(1..=7).contains(&degree) {
+            return Err(Error::OutofBounds);
+        }
+        let semitones = self.degree_semitones(degree, quality) as i8 + i8::from(alter);
+        let interval = u8::try_from(semitones)
+            .ok()
+            .and_then(|semitones| IntervalType::from_diatonic_semitones(degree - 1, semitones))
+            .ok_or(Error::OutofBounds)?;
+        self.tonic.checked_add(interval).ok_or(Error::OutofBounds)
+    }
+
+    /// Returns the pitch classes available without chromatic alteration, which in minor keys
+    /// include the leading tone of harmonic minor
+    pub fn pitch_class_set(&self) -> PitchClassSet {
+        let set = self.scale_type().pitch_class_set();
+        let set = match self.mode {
+            Mode::Major => set,
+            Mode::Minor => set.insert(11),
+        };
+        set.transpose(self.tonic.pitch.pitch_class())
+    }
+
+    /// Returns the key signature as a count of sharps, or of flats when negative
+    pub fn fifths(&self) -> i8 {
+        let step = match self.tonic.pitch.step {
+            Step::C => 0,
+            Step::D => 2,
+            Step::E => 4,
+            Step::F => -1,
+            Step::G => 1,
+            Step::A => 3,
+            Step::B => 5,
+        };
+        let fifths = step + 7 * i8::from(self.tonic.pitch.alter);
+        match self.mode {
+            Mode::Major => fifths,
+            Mode::Minor => fifths - 3,
+        }
+    }
+
+    /// Returns the key with the signature, given as a count of sharps or of flats when negative
+    pub fn from_fifths(fifths: i8, mode: Mode) -> Result<Key> {
+        let tonics = match mode {
+            Mode::Major => MAJOR_FIFTHS_TONICS,
+            Mode::Minor => MINOR_FIFTHS_TONICS,
+        };
+        let index = usize::try_from(fifths as i16 + 7).map_err(|_| Error::OutofBounds)?;
+        let tonic = tonics.get(index).ok_or(Error::OutofBounds)?;
+        Ok(Key::new(PitchOctave::new(Pitch::from_str(tonic)?, Octave::default()), mode))
+    }
+}
+
+// Tonics of the keys from seven flats to seven sharps
+const MAJOR_FIFTHS_TONICS: [&str; 15] = ["Cb", "Gb", "Db", "Ab", "Eb", "Bb", "F", "C", "G", "D", "A", "E", "B", "F#", "C#"];
+const MINOR_FIFTHS_TONICS: [&str; 15] = ["Ab", "Eb", "Bb", "F", "C", "G", "D", "A", "E", "B", "F#", "C#", "G#", "D#", "A#"];
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.mode {
+            Mode::Major => "major",
+            Mode::Minor => "minor",
+        };
+        write!(f, "{} {}", self.tonic.pitch, mode)
+    }
+}
+
+// Parses keys such as "Eb major" or "c# minor", with the tonic placed in the default octave
+impl FromStr for Key {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<Key> {
+        let (tonic, mode) = input.trim().split_once(' ').ok_or(Error::Parse)?;
+        let mut chars = tonic.chars();
+        let step = chars.next().ok_or(Error::Parse)?.to_ascii_uppercase();
+        let pitch = Pitch::from_str(&format!("{}{}", step, chars.as_str()))?;
+        let mode = Mode::from_str(mode.trim())?;
+        Ok(Key::new(PitchOctave::new(pitch, Octave::default()), mode))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_degrees() {
+        let key = Key::from_str("Eb major").unwrap();
+        assert_eq!(key.to_string(), "Eb major");
+        let sixth = key.degree_pitch(6, Alter::Flat, ChordQuality::Major).unwrap();
+        assert_eq!(sixth.to_string(), "Cb4");
+        assert_eq!(key.fifths(), -3);
+
+        let key = Key::from_str("c minor").unwrap();
+        assert_eq!(key.degree_pitch(7, Alter::None, ChordQuality::Major).unwrap().to_string(), "Bb3");
+        assert_eq!(key.degree_pitch(7, Alter::None, ChordQuality::Diminished7).unwrap().to_string(), "B3");
+        assert!(key.pitch_class_set().contains(11));
+        assert_eq!(key.fifths(), -3);
+        assert_eq!(Key::from_str("f# minor").unwrap().fifths(), 3);
+        assert_eq!(Key::from_fifths(-3, Mode::Minor).unwrap(), key);
+        assert_eq!(Key::from_fifths(8, Mode::Major), Err(Error::OutofBounds));
+    }
+}
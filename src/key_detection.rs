@@ -0,0 +1,236 @@
+use crate::error::{Error, Result};
+use crate::key::{Key, Mode};
+use crate::melody::Melody;
+use crate::pitch::Pitches;
+use crate::rhythm::Event;
+use num::rational::Ratio;
+use num::{ToPrimitive, Zero};
+use std::str::FromStr;
+
+const MAJOR_TONICS: [&str; 12] = ["C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B"];
+const MINOR_TONICS: [&str; 12] = ["C", "C#", "D", "Eb", "E", "F", "F#", "G", "G#", "A", "Bb", "B"];
+
+const KRUMHANSL_KESSLER_MAJOR: [f64; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const KRUMHANSL_KESSLER_MINOR: [f64; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+// Temperley's profiles drawn from the Kostka-Payne corpus
+const TEMPERLEY_MAJOR: [f64; 12] = [0.748, 0.060, 0.488, 0.082, 0.670, 0.460, 0.096, 0.715, 0.104, 0.366, 0.057, 0.400];
+const TEMPERLEY_MINOR: [f64; 12] = [0.712, 0.084, 0.474, 0.618, 0.049, 0.460, 0.105, 0.747, 0.404, 0.067, 0.133, 0.330];
+// Aarden's profiles drawn from the Essen folksong collection
+const AARDEN_MAJOR: [f64; 12] = [
+    17.7661, 0.145624, 14.9265, 0.160186, 19.8049, 11.3587, 0.291248, 22.062, 0.145624, 8.15494, 0.232998, 4.95122,
+];
+const AARDEN_MINOR: [f64; 12] = [
+    18.2648, 0.737619, 14.0499, 16.8599, 0.702494, 14.4362, 0.702494, 18.6161, 4.56621, 1.93186, 7.37619, 1.75623,
+];
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum KeyProfile {
+    #[default]
+    KrumhanslKessler,
+    Temperley,
+    Aarden,
+}
+
+impl KeyProfile {
+    /// Returns the weight of each pitch class above the tonic
+    pub fn weights(&self, mode: Mode) -> &'static [f64; 12] {
+        match (self, mode) {
+            (KeyProfile::KrumhanslKessler, Mode::Major) => &KRUMHANSL_KESSLER_MAJOR,
+            (KeyProfile::KrumhanslKessler, Mode::Minor) => &KRUMHANSL_KESSLER_MINOR,
+            (KeyProfile::Temperley, Mode::Major) => &TEMPERLEY_MAJOR,
+            (KeyProfile::Temperley, Mode::Minor) => &TEMPERLEY_MINOR,
+            (KeyProfile::Aarden, Mode::Major) => &AARDEN_MAJOR,
+            (KeyProfile::Aarden, Mode::Minor) => &AARDEN_MINOR,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyEstimate {
+    pub key: Key,
+    // Correlation of the pitch distribution with the key's profile, from -1 to 1
+    pub score: f64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KeyWindow {
+    // Start and end of the window in quarter note beats
+    pub start: Ratio<u32>,
+    pub end: Ratio<u32>,
+    pub estimate: KeyEstimate,
+}
+
+fn correlation(a: &[f64; 12], b: &[f64; 12]) -> f64 {
+    let mean_a = a.iter().sum::<f64>() / 12.0;
+    let mean_b = b.iter().sum::<f64>() / 12.0;
+    let mut covariance = 0.0;
+    let mut variance_a = 0.0;
+    let mut variance_b = 0.0;
+    for i in 0..12 {
+        covariance += (a[i] - mean_a) * (b[i] - mean_b);
+        variance_a += (a[i] - mean_a).powi(2);
+        variance_b += (b[i] - mean_b).powi(2);
+    }
+    if variance_a == 0.0 || variance_b == 0.0 {
+        0.0
+    } else {
+        covariance / (variance_a * variance_b).sqrt()
+    }
+}
+
+/// Ranks all 24 major and minor keys by how well the pitch class distribution, such as total
+/// duration per pitch class with C as 0, matches the profile
+pub fn rank_keys(distribution: &[f64; 12], profile: KeyProfile) -> Result<Vec<KeyEstimate>> {
+    if distribution.iter().all(|weight| *weight == 0.0) {
+        return Err(Error::OutofBounds);
+    }
+    let mut estimates = vec![];
+    for mode in [Mode::Major, Mode::Minor] {
+        let weights = profile.weights(mode);
+        let names = match mode {
+            Mode::Major => &MAJOR_TONICS,
+            Mode::Minor => &MINOR_TONICS,
+        };
+        for (tonic, name) in names.iter().enumerate() {
+            let rotated: [f64; 12] = std::array::from_fn(|pitch_class| weights[(pitch_class + 12 - tonic) % 12]);
+            let mode_name = match mode {
+                Mode::Major => "major",
+                Mode::Minor => "minor",
+            };
+            estimates.push(KeyEstimate {
+                key: Key::from_str(&format!("{} {}", name, mode_name))?,
+                score: correlation(distribution, &rotated),
+            });
+        }
+    }
+    estimates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(estimates)
+}
+
+/// Ranks keys for a collection of pitches, each counted once
+pub fn detect_key(pitches: &Pitches, profile: KeyProfile) -> Result<Vec<KeyEstimate>> {
+    let mut distribution = [0.0; 12];
+    for pitch in &pitches.0 {
+        distribution[pitch.pitch.pitch_class() as usize] += 1.0;
+    }
+    rank_keys(&distribution, profile)
+}
+
+// Total duration of each pitch class sounding between the start and end beats
+fn distribution(events: &[Event], start: Ratio<u32>, end: Ratio<u32>) -> [f64; 12] {
+    let mut distribution = [0.0; 12];
+    let mut onset = Ratio::zero();
+    for event in events {
+        let offset = onset + event.beats();
+        let from = if onset > start { onset } else { start };
+        let to = if offset < end { offset } else { end };
+        if from < to {
+            let overlap = (to - from).to_f64().unwrap_or_default();
+            for pitch in event.pitches() {
+                distribution[pitch.pitch.pitch_class() as usize] += overlap;
+            }
+        }
+        onset = offset;
+    }
+    distribution
+}
+
+/// Ranks keys for timed events, weighting each pitch by how long it sounds
+pub fn detect_key_weighted(events: &[Event], profile: KeyProfile) -> Result<Vec<KeyEstimate>> {
+    let total: Ratio<u32> = events.iter().map(|event| event.beats()).sum();
+    rank_keys(&distribution(events, Ratio::zero(), total), profile)
+}
+
+/// Estimates the key of each window of the given length, moving by the hop, to follow
+/// modulations through a piece. The last window is cut short at the end of the piece, and
+/// windows with only rests are skipped.
+pub fn detect_key_windows(
+    events: &[Event],
+    window: Ratio<u32>,
+    hop: Ratio<u32>,
+    profile: KeyProfile,
+) -> Result<Vec<KeyWindow>> {
+    if window.is_zero() || hop.is_zero() {
+        return Err(Error::OutofBounds);
+    }
+    let total: Ratio<u32> = events.iter().map(|event| event.beats()).sum();
+    let mut windows = vec![];
+    let mut start = Ratio::zero();
+    while start < total {
+        let end = start + window;
+        if let Ok(estimates) = rank_keys(&distribution(events, start, end), profile) {
+            windows.push(KeyWindow {
+                start,
+                end: if end < total { end } else { total },
+                estimate: estimates[0],
+            });
+        }
+        // Stop once a window reaches the end rather than shrinking the last few
+        if end >= total {
+            break;
+        }
+        start += hop;
+    }
+    Ok(windows)
+}
+
+impl Melody {
+    pub fn detect_key(&self, profile: KeyProfile) -> Result<Vec<KeyEstimate>> {
+        let events: Vec<Event> = self.notes.iter().map(|note| Event::Note(*note)).collect();
+        detect_key_weighted(&events, profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitch::PitchOctave;
+    use crate::rhythm::{Duration, Note, NoteValue};
+
+    fn events(input: &str) -> Vec<Event> {
+        input
+            .split_whitespace()
+            .map(|token| {
+                // A trailing "-" doubles the length of the quarter note
+                let (pitch, value) = match token.strip_suffix('-') {
+                    Some(pitch) => (pitch, NoteValue::Half),
+                    None => (token, NoteValue::Quarter),
+                };
+                Event::Note(Note::new(PitchOctave::from_str(pitch).unwrap(), Duration::new(value)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect_key() {
+        let scale = Pitches(
+            "C4 D4 E4 F4 G4 A4 B4 C5"
+                .split_whitespace()
+                .map(|p| PitchOctave::from_str(p).unwrap())
+                .collect(),
+        );
+        let estimates = detect_key(&scale, KeyProfile::KrumhanslKessler).unwrap();
+        assert_eq!(estimates.len(), 24);
+        assert_eq!(estimates[0].key.to_string(), "C major");
+        assert_eq!(estimates[1].key.to_string(), "A minor");
+        // Weighted towards the tonic triad every profile agrees
+        let tonal = events("C4- E4 G4 C5- G4 F4 E4 D4 B3 C4-");
+        for profile in [KeyProfile::KrumhanslKessler, KeyProfile::Temperley, KeyProfile::Aarden] {
+            assert_eq!(detect_key_weighted(&tonal, profile).unwrap()[0].key.to_string(), "C major");
+        }
+        let minor = events("A3- C4 E4 G#4 A4- E4 B3 C4 D4 E4- A3-");
+        let estimates = detect_key_weighted(&minor, KeyProfile::KrumhanslKessler).unwrap();
+        assert_eq!(estimates[0].key.to_string(), "A minor");
+        assert!(estimates[0].score > estimates[1].score);
+        assert_eq!(detect_key(&Pitches(vec![]), KeyProfile::default()), Err(Error::OutofBounds));
+    }
+
+    #[test]
+    fn test_key_windows() {
+        let piece = events("C4 E4 G4 C5 F4 A4 G4 E4 D4 F4 B3 C4 G4 B4 D5 G4 C5 A4 F#4 G4 D4 F#4 A4 G4-");
+        let windows = detect_key_windows(&piece, Ratio::from_integer(8), Ratio::from_integer(4), KeyProfile::Temperley).unwrap();
+        assert_eq!(windows[0].estimate.key.to_string(), "C major");
+        assert_eq!(windows[2].estimate.key.to_string(), "G major");
+        assert_eq!(windows.last().unwrap().end, Ratio::from_integer(25));
+    }
+}
@@ -5,3 +5,27 @@ pub mod chord;
 pub mod pitch;
 pub mod scale;
 pub mod chord_find;
+pub mod chord_scale;
+pub mod scale_symmetry;
+pub mod pitch_class;
+pub mod tone_row;
+pub mod neo_riemannian;
+pub mod key;
+pub mod roman_numeral;
+pub mod chord_symbol;
+pub mod progression;
+pub mod harmonic_analysis;
+pub mod reharmonization;
+pub mod voice_leading;
+pub mod satb;
+pub mod counterpoint;
+pub mod figured_bass;
+pub mod rhythm;
+pub mod meter;
+pub mod melody;
+pub mod key_detection;
+pub mod musicxml;
+pub mod lilypond;
+pub mod abc;
+pub mod kern;
+pub mod score;
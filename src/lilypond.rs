@@ -0,0 +1,356 @@
+use crate::chord::{Chord, ChordQuality, Inversion};
+use crate::error::{Error, Result};
+use crate::melody::Melody;
+use crate::notes::Notes;
+use crate::pitch::{Alter, Pitch, PitchOctave, Pitches, Step};
+use crate::rhythm::{Duration, Event, Note, NoteValue, Tie, Tuplet};
+use crate::scale::Scale;
+
+pub const LILYPOND_VERSION: &str = "2.24.0";
+// LilyPond writes the octave below middle C without marks
+const UNMARKED_OCTAVE: i32 = 3;
+
+/// How the octave of each note is written
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OctaveEntry {
+    // Every note carries marks counted from the octave below middle C
+    #[default]
+    Absolute,
+    // Every note is placed within a fourth of the one before, marks moving it further
+    Relative,
+}
+
+pub trait ToLilyPond {
+    /// Writes the music as a LilyPond expression in braces
+    fn to_lilypond(&self, entry: OctaveEntry) -> Result<String>;
+}
+
+impl Pitch {
+    /// Returns the Dutch note name LilyPond uses, such as "fis" or "eses"
+    pub fn lilypond_name(&self) -> String {
+        let step = self.step.to_string().to_lowercase();
+        // E and A drop the vowel of flats, as in "es" and "as"
+        let vowel_step = matches!(self.step, Step::E | Step::A);
+        let suffix = match self.alter {
+            Alter::DoubleFlat if vowel_step => "ses",
+            Alter::DoubleFlat => "eses",
+            Alter::Flat if vowel_step => "s",
+            Alter::Flat => "es",
+            Alter::HalfFlat => "eh",
+            Alter::None => "",
+            Alter::HalfSharp => "ih",
+            Alter::Sharp => "is",
+            Alter::DoubleSharp => "isis",
+        };
+        format!("{}{}", step, suffix)
+    }
+}
+
+fn octave_marks(marks: i32) -> String {
+    if marks >= 0 {
+        "'".repeat(marks as usize)
+    } else {
+        ",".repeat(marks.unsigned_abs() as usize)
+    }
+}
+
+// Counts diatonic steps from C0, so octaves compare by letter regardless of accidentals
+fn diatonic(pitch: &PitchOctave) -> i32 {
+    pitch.octave as i32 * 7 + pitch.pitch.step as i32
+}
+
+impl PitchOctave {
+    /// Returns the pitch in LilyPond's absolute octave entry, such as "ces'" for Cb4
+    pub fn lilypond_name(&self) -> String {
+        format!("{}{}", self.pitch.lilypond_name(), octave_marks(self.octave as i32 - UNMARKED_OCTAVE))
+    }
+
+    /// Returns the pitch in relative octave entry following the previous pitch
+    pub fn lilypond_relative_name(&self, previous: &PitchOctave) -> String {
+        let steps = diatonic(self) - diatonic(previous);
+        let mut nearest = steps.rem_euclid(7);
+        if nearest > 3 {
+            nearest -= 7;
+        }
+        format!("{}{}", self.pitch.lilypond_name(), octave_marks((steps - nearest) / 7))
+    }
+}
+
+// Writes pitches in the entry mode, remembering the pitch the next one is relative to
+struct PitchWriter {
+    entry: OctaveEntry,
+    previous: Option<PitchOctave>,
+}
+
+impl PitchWriter {
+    fn name(&mut self, pitch: &PitchOctave) -> String {
+        let name = match (self.entry, self.previous) {
+            (OctaveEntry::Relative, Some(previous)) => pitch.lilypond_relative_name(&previous),
+            _ => pitch.lilypond_name(),
+        };
+        self.previous = Some(*pitch);
+        name
+    }
+
+    // Notes of a chord follow each other, but the next event follows the chord's first note
+    fn chord(&mut self, pitches: &[PitchOctave]) -> String {
+        let names: Vec<String> = pitches.iter().map(|pitch| self.name(pitch)).collect();
+        self.previous = pitches.first().copied().or(self.previous);
+        format!("<{}>", names.join(" "))
+    }
+}
+
+fn duration_name(duration: &Duration) -> String {
+    format!("{}{}", duration.value().denominator(), ".".repeat(duration.dots() as usize))
+}
+
+/// Writes the events in braces, leaving out durations repeated from the note before and
+/// grouping tuplets
+pub fn lilypond_events(events: &[Event], entry: OctaveEntry) -> Result<String> {
+    let mut pitches = PitchWriter { entry, previous: None };
+    let mut words = vec![];
+    let mut previous_duration: Option<(NoteValue, u8)> = None;
+    let mut open_tuplet: Option<Tuplet> = None;
+    for event in events {
+        let duration = event.duration()?;
+        if open_tuplet.is_some() && open_tuplet != duration.tuplet() {
+            words.push("}".to_string());
+            open_tuplet = None;
+        }
+        if let (None, Some(tuplet)) = (open_tuplet, duration.tuplet()) {
+            words.push(format!("\\tuplet {}/{} {{", tuplet.actual(), tuplet.normal()));
+            open_tuplet = Some(tuplet);
+        }
+        let mut word = match event {
+            Event::Note(note) => pitches.name(&note.pitch),
+            Event::Chord(notes) => pitches.chord(&notes.iter().map(|note| note.pitch).collect::<Vec<_>>()),
+            Event::Rest(_) => "r".to_string(),
+        };
+        if previous_duration != Some((duration.value(), duration.dots())) {
+            word.push_str(&duration_name(&duration));
+            previous_duration = Some((duration.value(), duration.dots()));
+        }
+        let tied = match event {
+            Event::Note(note) => matches!(note.tie, Tie::Start | Tie::Continue),
+            Event::Chord(notes) => notes.iter().any(|note| matches!(note.tie, Tie::Start | Tie::Continue)),
+            Event::Rest(_) => false,
+        };
+        if tied {
+            word.push('~');
+        }
+        words.push(word);
+    }
+    if open_tuplet.is_some() {
+        words.push("}".to_string());
+    }
+    let music = format!("{{ {} }}", words.join(" "));
+    Ok(match entry {
+        OctaveEntry::Absolute => music,
+        // With no reference pitch the first note is read as absolute
+        OctaveEntry::Relative => format!("\\relative {}", music),
+    })
+}
+
+impl ChordQuality {
+    /// Returns the modifiers after the colon in LilyPond's chord mode, such as "m7" or "7.9-"
+    pub fn lilypond_modifier(&self) -> &'static str {
+        match self {
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Dom7Sus4 => "7sus4",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::MinorMajor7 => "m7+",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::Dom7 => "7",
+            ChordQuality::Diminished7 => "dim7",
+            ChordQuality::Major7Sharp5 => "maj7.5+",
+            ChordQuality::Dom7Sharp5 => "7.5+",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Minor7Flat5 => "m7.5-",
+            ChordQuality::Major7Flat5 => "maj7.5-",
+            ChordQuality::Add9 => "5.9",
+            ChordQuality::Major9 => "maj9",
+            ChordQuality::Dom9 => "9",
+            ChordQuality::MinorAdd9 => "m5.9",
+            ChordQuality::MinorMajor7Add9 => "m7+.9",
+            ChordQuality::Minor9 => "m9",
+            ChordQuality::Major7Sharp11 => "maj7.11+",
+            ChordQuality::Major9Sharp11 => "maj9.11+",
+            ChordQuality::Dom7Sharp11 => "7.11+",
+            ChordQuality::Dom9Sharp11 => "9.11+",
+            ChordQuality::Dom13 => "7.13",
+            // LilyPond leaves the eleventh out of thirteenth chords
+            ChordQuality::Dom9Thirteenth => "13",
+            ChordQuality::Dom7Flat9 => "7.9-",
+            ChordQuality::Dom7Flat13 => "7.13-",
+            ChordQuality::Dom7Flat13Flat9 => "7.9-.13-",
+            ChordQuality::Dom11Flat13Flat9 => "7.9-.11.13-",
+            ChordQuality::Dom7Sharp9 => "7.9+",
+            ChordQuality::Minor7Add11 => "m7.11",
+            ChordQuality::Minor11 => "m11",
+            ChordQuality::ItalianSixth => "7^5",
+            ChordQuality::FrenchSixth => "7.5-",
+            ChordQuality::GermanSixth => "7",
+        }
+    }
+}
+
+// The duration goes between the root and the modifiers, as in "g1:7/b"
+fn chordmode_word(chord: &Chord, duration: &str) -> Result<String> {
+    let mut word = format!("{}{}", chord.root.pitch.lilypond_name(), duration);
+    let modifier = chord.kind.lilypond_modifier();
+    if !modifier.is_empty() {
+        word.push(':');
+        word.push_str(modifier);
+    }
+    if chord.inversion != Inversion::Root {
+        word.push('/');
+        word.push_str(&chord.bass()?.pitch.lilypond_name());
+    }
+    Ok(word)
+}
+
+impl Chord {
+    /// Returns the chord as LilyPond chord mode input, such as "g:7/b", with no duration
+    pub fn lilypond_chordmode(&self) -> Result<String> {
+        chordmode_word(self, "")
+    }
+}
+
+/// Writes chord symbols in chord mode, each lasting the duration
+pub fn lilypond_chordmode(chords: &[Chord], duration: Duration) -> Result<String> {
+    if duration.tuplet().is_some() {
+        return Err(Error::Unsupported);
+    }
+    let mut words = vec![];
+    for (i, chord) in chords.iter().enumerate() {
+        // Later chords carry the first one's duration over
+        let duration = if i == 0 { duration_name(&duration) } else { String::new() };
+        words.push(chordmode_word(chord, &duration)?);
+    }
+    Ok(format!("\\chordmode {{ {} }}", words.join(" ")))
+}
+
+/// Wraps music expressions in a file LilyPond can engrave, one staff after another
+pub fn lilypond_document(music: &[String]) -> String {
+    let mut document = format!("\\version \"{}\"\n", LILYPOND_VERSION);
+    for expression in music {
+        document.push('\n');
+        document.push_str(expression);
+        document.push('\n');
+    }
+    document
+}
+
+fn quarter_notes(pitches: &Pitches) -> Vec<Event> {
+    pitches
+        .0
+        .iter()
+        .map(|pitch| Event::Note(Note::new(*pitch, Duration::new(NoteValue::Quarter))))
+        .collect()
+}
+
+impl ToLilyPond for Pitches {
+    fn to_lilypond(&self, entry: OctaveEntry) -> Result<String> {
+        lilypond_events(&quarter_notes(self), entry)
+    }
+}
+
+impl ToLilyPond for Scale {
+    fn to_lilypond(&self, entry: OctaveEntry) -> Result<String> {
+        lilypond_events(&quarter_notes(&self.notes()?), entry)
+    }
+}
+
+impl ToLilyPond for Melody {
+    fn to_lilypond(&self, entry: OctaveEntry) -> Result<String> {
+        let events: Vec<Event> = self.notes.iter().map(|note| Event::Note(*note)).collect();
+        lilypond_events(&events, entry)
+    }
+}
+
+// The chord's notes stacked as a whole note
+impl ToLilyPond for Chord {
+    fn to_lilypond(&self, entry: OctaveEntry) -> Result<String> {
+        let duration = Duration::new(NoteValue::Whole);
+        let notes = self.notes()?.0.iter().map(|pitch| Note::new(*pitch, duration)).collect();
+        lilypond_events(&[Event::Chord(notes)], entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitch::Octave;
+    use crate::scale::ScaleType;
+    use std::str::FromStr;
+
+    fn name(pitch: &str) -> String {
+        PitchOctave::from_str(pitch).unwrap().lilypond_name()
+    }
+
+    #[test]
+    fn test_lilypond_names() {
+        assert_eq!(name("Cb4"), "ces'");
+        assert_eq!(name("F#1"), "fis,,");
+        assert_eq!(name("C3"), "c");
+        assert_eq!(name("Eb5"), "es''");
+        assert_eq!(name("Abb2"), "ases,");
+        assert_eq!(name("Dbb4"), "deses'");
+        assert_eq!(name("G##6"), "gisis'''");
+        // B#3 sounds as C4 but is written in the octave of its letter
+        assert_eq!(name("B#3"), "bis");
+
+        let scale = Scale::new(PitchOctave::from_str("D4").unwrap(), ScaleType::Ionian);
+        assert_eq!(
+            scale.to_lilypond(OctaveEntry::Absolute).unwrap(),
+            "{ d'4 e' fis' g' a' b' cis'' d'' }"
+        );
+        assert_eq!(
+            scale.to_lilypond(OctaveEntry::Relative).unwrap(),
+            "\\relative { d'4 e fis g a b cis d }"
+        );
+    }
+
+    #[test]
+    fn test_lilypond_music() {
+        let leaps = Pitches(["C4", "G4", "C5", "B3"].iter().map(|p| PitchOctave::from_str(p).unwrap()).collect());
+        assert_eq!(leaps.to_lilypond(OctaveEntry::Relative).unwrap(), "\\relative { c'4 g' c b, }");
+
+        let chord = Chord::from_symbol("Bbmaj7", Octave::Octave3).unwrap();
+        assert_eq!(chord.to_lilypond(OctaveEntry::Absolute).unwrap(), "{ <bes d' f' a'>1 }");
+        assert_eq!(chord.to_lilypond(OctaveEntry::Relative).unwrap(), "\\relative { <bes d f a>1 }");
+
+        let triplet = Duration::new(NoteValue::Eighth).with_tuplet(Tuplet::TRIPLET);
+        let pitch = PitchOctave::from_str("E4").unwrap();
+        let melody = Melody::new(vec![
+            Note { tie: Tie::Start, ..Note::new(pitch, Duration::dotted(NoteValue::Quarter, 1).unwrap()) },
+            Note { tie: Tie::Stop, ..Note::new(pitch, Duration::new(NoteValue::Eighth)) },
+            Note::new(pitch, triplet),
+            Note::new(pitch, triplet),
+            Note::new(pitch, triplet),
+        ]);
+        assert_eq!(
+            melody.to_lilypond(OctaveEntry::Absolute).unwrap(),
+            "{ e'4.~ e'8 \\tuplet 3/2 { e' e' e' } }"
+        );
+
+        let progression: Vec<Chord> = ["Cm7", "G7/B", "F#m7b5", "Eb"]
+            .iter()
+            .map(|symbol| Chord::from_symbol(symbol, Octave::Octave3).unwrap())
+            .collect();
+        assert_eq!(
+            lilypond_chordmode(&progression, Duration::new(NoteValue::Whole)).unwrap(),
+            "\\chordmode { c1:m7 g:7/b fis:m7.5- es }"
+        );
+        assert_eq!(lilypond_chordmode(&progression, triplet), Err(Error::Unsupported));
+        assert_eq!(lilypond_events(&[Event::Chord(vec![])], OctaveEntry::Absolute), Err(Error::OutofBounds));
+
+        let quintuplet = Duration::new(NoteValue::Sixteenth).with_tuplet(Tuplet::new(5, 4).unwrap());
+        let run: Vec<Event> = (0..5).map(|_| Event::Note(Note::new(pitch, quintuplet))).collect();
+        assert_eq!(lilypond_events(&run, OctaveEntry::Absolute).unwrap(), "{ \\tuplet 5/4 { e'16 e' e' e' e' } }");
+        assert!(lilypond_document(&[leaps.to_lilypond(OctaveEntry::Absolute).unwrap()]).starts_with("\\version \"2.24.0\""));
+    }
+}
@@ -0,0 +1,306 @@
+use crate::error::{Error, Result};
+use crate::interval::IntervalType;
+use crate::pitch::{PitchOctave, Pitches};
+use crate::rhythm::{Duration, Note, NoteValue};
+use num::rational::Ratio;
+use num::ToPrimitive;
+use std::collections::BTreeMap;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Contour {
+    Up,
+    Down,
+    Same,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MotionStatistics {
+    pub repeats: usize,
+    // Moves of one or two semitones
+    pub steps: usize,
+    pub leaps: usize,
+    // Largest move in semitones
+    pub largest_leap: u8,
+}
+
+/// The pitch a melody dwells around, as MIDI note numbers weighted by duration
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Tessitura {
+    pub mean: f64,
+    pub deviation: f64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MotifMatchType {
+    Exact,
+    Transposed,
+    Inverted,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MotifMatch {
+    // Index of the melody note the motif starts on
+    pub index: usize,
+    pub kind: MotifMatchType,
+    // Semitones from the motif's first note to the match's first note
+    pub transposition: i8,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Melody {
+    pub notes: Vec<Note>,
+}
+
+impl Melody {
+    pub fn new(notes: Vec<Note>) -> Melody {
+        Melody { notes }
+    }
+
+    /// Builds a melody of the pitches, each lasting the same duration
+    pub fn from_pitches(pitches: &Pitches, duration: Duration) -> Melody {
+        Melody::new(pitches.0.iter().map(|pitch| Note::new(*pitch, duration)).collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn pitches(&self) -> Pitches {
+        Pitches(self.notes.iter().map(|note| note.pitch).collect())
+    }
+
+    pub fn total_beats(&self) -> Ratio<u32> {
+        self.notes.iter().map(|note| note.beats()).sum()
+    }
+
+    fn midi(&self) -> Result<Vec<i8>> {
+        self.notes.iter().map(|note| note.pitch.get_semitone_value()).collect()
+    }
+
+    /// Returns the signed semitones between consecutive notes
+    pub fn intervals(&self) -> Result<Vec<i8>> {
+        let midi = self.midi()?;
+        Ok(midi.windows(2).map(|pair| pair[1] - pair[0]).collect())
+    }
+
+    /// Returns the spelled interval between consecutive notes, reduced to an octave, or None
+    /// where no interval type exists for the spelling
+    pub fn spelled_intervals(&self) -> Result<Vec<Option<IntervalType>>> {
+        let mut spelled = vec![];
+        for pair in self.notes.windows(2) {
+            let (low, high) = if pair[1].pitch.get_semitone_value()? >= pair[0].pitch.get_semitone_value()? {
+                (pair[0].pitch, pair[1].pitch)
+            } else {
+                (pair[1].pitch, pair[0].pitch)
+            };
+            let diatonic = |pitch: &PitchOctave| pitch.octave as i32 * 7 + pitch.pitch.step as i32;
+            let steps = (diatonic(&high) - diatonic(&low)).rem_euclid(7) as u8;
+            let semitones = (high.get_semitone_value()? - low.get_semitone_value()?).rem_euclid(12) as u8;
+            spelled.push(IntervalType::from_diatonic_semitones(steps, semitones));
+        }
+        Ok(spelled)
+    }
+
+    pub fn contour(&self) -> Result<Vec<Contour>> {
+        Ok(self
+            .intervals()?
+            .iter()
+            .map(|interval| match interval.signum() {
+                1 => Contour::Up,
+                -1 => Contour::Down,
+                _ => Contour::Same,
+            })
+            .collect())
+    }
+
+    /// Returns the contour segment of the melody, each note ranked among the distinct pitches
+    /// from 0 for the lowest, so the shape can be compared regardless of the intervals
+    pub fn contour_segment(&self) -> Result<Vec<usize>> {
+        let midi = self.midi()?;
+        let mut distinct = midi.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        Ok(midi
+            .iter()
+            .map(|pitch| distinct.iter().position(|p| p == pitch).unwrap_or_default())
+            .collect())
+    }
+
+    /// Returns the lowest and highest notes
+    pub fn range(&self) -> Result<Option<(PitchOctave, PitchOctave)>> {
+        let mut range: Option<((i8, PitchOctave), (i8, PitchOctave))> = None;
+        for note in &self.notes {
+            let value = note.pitch.get_semitone_value()?;
+            range = Some(match range {
+                None => ((value, note.pitch), (value, note.pitch)),
+                Some((low, high)) => (
+                    if value < low.0 { (value, note.pitch) } else { low },
+                    if value > high.0 { (value, note.pitch) } else { high },
+                ),
+            });
+        }
+        Ok(range.map(|(low, high)| (low.1, high.1)))
+    }
+
+    pub fn tessitura(&self) -> Result<Tessitura> {
+        let midi = self.midi()?;
+        let weights: Vec<f64> = self
+            .notes
+            .iter()
+            .map(|note| note.beats().to_f64().unwrap_or_default())
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total == 0.0 {
+            return Err(Error::OutofBounds);
+        }
+        let mean = midi.iter().zip(&weights).map(|(pitch, weight)| *pitch as f64 * weight).sum::<f64>() / total;
+        let variance = midi
+            .iter()
+            .zip(&weights)
+            .map(|(pitch, weight)| (*pitch as f64 - mean).powi(2) * weight)
+            .sum::<f64>()
+            / total;
+        Ok(Tessitura {
+            mean,
+            deviation: variance.sqrt(),
+        })
+    }
+
+    /// Counts each signed interval between consecutive notes
+    pub fn interval_histogram(&self) -> Result<BTreeMap<i8, usize>> {
+        let mut histogram = BTreeMap::new();
+        for interval in self.intervals()? {
+            *histogram.entry(interval).or_insert(0) += 1;
+        }
+        Ok(histogram)
+    }
+
+    pub fn motion_statistics(&self) -> Result<MotionStatistics> {
+        let mut statistics = MotionStatistics::default();
+        for interval in self.intervals()? {
+            let size = interval.unsigned_abs();
+            match size {
+                0 => statistics.repeats += 1,
+                1 | 2 => statistics.steps += 1,
+                _ => statistics.leaps += 1,
+            }
+            statistics.largest_leap = statistics.largest_leap.max(size);
+        }
+        Ok(statistics)
+    }
+
+    /// Finds every occurrence of the motif's interval pattern, optionally at other pitch levels
+    /// and with its intervals inverted. Rhythm is not compared.
+    pub fn find_motif(&self, motif: &Pitches, transposed: bool, inverted: bool) -> Result<Vec<MotifMatch>> {
+        let motif_midi = motif
+            .0
+            .iter()
+            .map(|pitch| pitch.get_semitone_value())
+            .collect::<Result<Vec<i8>>>()?;
+        let Some(first) = motif_midi.first() else {
+            return Err(Error::OutofBounds);
+        };
+        let motif_intervals: Vec<i8> = motif_midi.windows(2).map(|pair| pair[1] - pair[0]).collect();
+        let midi = self.midi()?;
+        let intervals = self.intervals()?;
+        let mut matches = vec![];
+        for index in 0..midi.len() {
+            if index + motif_midi.len() > midi.len() {
+                break;
+            }
+            let segment = &intervals[index..index + motif_intervals.len()];
+            let transposition = midi[index] - first;
+            let kind = if segment == motif_intervals.as_slice() {
+                if transposition == 0 {
+                    Some(MotifMatchType::Exact)
+                } else if transposed {
+                    Some(MotifMatchType::Transposed)
+                } else {
+                    None
+                }
+            } else if inverted && segment.iter().zip(&motif_intervals).all(|(a, b)| *a == -b) {
+                Some(MotifMatchType::Inverted)
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                if kind != MotifMatchType::Inverted || transposed || transposition == 0 {
+                    matches.push(MotifMatch {
+                        index,
+                        kind,
+                        transposition,
+                    });
+                }
+            }
+        }
+        Ok(matches)
+    }
+}
+
+impl From<&Pitches> for Melody {
+    fn from(pitches: &Pitches) -> Melody {
+        Melody::from_pitches(pitches, Duration::new(NoteValue::Quarter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn pitches(input: &str) -> Pitches {
+        Pitches(input.split_whitespace().map(|p| PitchOctave::from_str(p).unwrap()).collect())
+    }
+
+    #[test]
+    fn test_melody_analysis() {
+        let melody = Melody::from(&pitches("C4 D4 E4 C4 G4 G4 F4"));
+        assert_eq!(melody.intervals().unwrap(), vec![2, 2, -4, 7, 0, -2]);
+        assert_eq!(
+            melody.contour().unwrap(),
+            vec![Contour::Up, Contour::Up, Contour::Down, Contour::Up, Contour::Same, Contour::Down]
+        );
+        assert_eq!(melody.contour_segment().unwrap(), vec![0, 1, 2, 0, 4, 4, 3]);
+        let (low, high) = melody.range().unwrap().unwrap();
+        assert_eq!((low.to_string(), high.to_string()), ("C4".to_string(), "G4".to_string()));
+        assert_eq!(
+            melody.motion_statistics().unwrap(),
+            MotionStatistics {
+                repeats: 1,
+                steps: 3,
+                leaps: 2,
+                largest_leap: 7
+            }
+        );
+        assert_eq!(melody.interval_histogram().unwrap()[&2], 2);
+        assert_eq!(melody.spelled_intervals().unwrap()[2], Some(IntervalType::MajorThird));
+        assert_eq!(melody.spelled_intervals().unwrap()[3], Some(IntervalType::Fifth));
+
+        let held = Melody::new(vec![
+            Note::new(PitchOctave::from_str("C4").unwrap(), Duration::new(NoteValue::Whole)),
+            Note::new(PitchOctave::from_str("C5").unwrap(), Duration::new(NoteValue::Quarter)),
+        ]);
+        let tessitura = held.tessitura().unwrap();
+        assert!((tessitura.mean - 62.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_motif_search() {
+        let melody = Melody::from(&pitches("C4 D4 E4 G4 A4 B4 E4 D4 C4"));
+        let motif = pitches("C4 D4 E4");
+        let matches = melody.find_motif(&motif, true, true).unwrap();
+        assert_eq!(
+            matches,
+            vec![
+                MotifMatch { index: 0, kind: MotifMatchType::Exact, transposition: 0 },
+                MotifMatch { index: 3, kind: MotifMatchType::Transposed, transposition: 7 },
+                MotifMatch { index: 6, kind: MotifMatchType::Inverted, transposition: 4 },
+            ]
+        );
+        assert_eq!(melody.find_motif(&motif, false, false).unwrap().len(), 1);
+    }
+}
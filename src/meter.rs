@@ -0,0 +1,329 @@
+use crate::error::{Error, Result};
+use crate::rhythm::{Duration, Event, Note, NoteValue, Rest, Tie};
+use num::rational::Ratio;
+use num::Zero;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MeterType {
+    Simple,
+    Compound,
+    Irregular,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BeatStrength {
+    Strong,
+    Medium,
+    Weak,
+    // Between beats
+    Offbeat,
+}
+
+/// A time signature with its beats grouped, each group counted in units of the denominator, so
+/// 6/8 is two groups of 3 and 7/8 is commonly 2+2+3
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeSignature {
+    pub groups: Vec<u32>,
+    pub denominator: u32,
+}
+
+impl TimeSignature {
+    // The most units of the denominator a bar can hold
+    const MAX_NUMERATOR: u32 = 64;
+
+    /// Groups the beats the usual way for the meter: one unit per beat in simple meters,
+    /// threes in compound meters, and twos closed by a three in odd meters such as 5/4 and 7/8
+    pub fn new(numerator: u32, denominator: u32) -> Result<TimeSignature> {
+        if numerator == 0 || numerator > Self::MAX_NUMERATOR {
+            return Err(Error::OutofBounds);
+        }
+        NoteValue::from_denominator(denominator)?;
+        let groups = if denominator >= 8 && numerator > 3 && numerator % 3 == 0 {
+            vec![3; numerator as usize / 3]
+        } else if numerator > 3 && numerator % 2 != 0 {
+            let mut groups = vec![2; (numerator as usize - 3) / 2];
+            groups.push(3);
+            groups
+        } else {
+            vec![1; numerator as usize]
+        };
+        Ok(TimeSignature { groups, denominator })
+    }
+
+    pub fn with_groups(groups: Vec<u32>, denominator: u32) -> Result<TimeSignature> {
+        let numerator = groups.iter().try_fold(0u32, |sum, group| sum.checked_add(*group));
+        if groups.is_empty() || groups.contains(&0) || numerator.map_or(true, |sum| sum > Self::MAX_NUMERATOR) {
+            return Err(Error::OutofBounds);
+        }
+        NoteValue::from_denominator(denominator)?;
+        Ok(TimeSignature { groups, denominator })
+    }
+
+    pub fn numerator(&self) -> u32 {
+        self.groups.iter().sum()
+    }
+
+    pub fn kind(&self) -> MeterType {
+        if self.groups.iter().all(|group| *group == 1) || self.groups.iter().all(|group| *group == 2) {
+            MeterType::Simple
+        } else if self.groups.iter().all(|group| *group == 3) {
+            MeterType::Compound
+        } else {
+            MeterType::Irregular
+        }
+    }
+
+    /// Returns the length of a bar in quarter note beats
+    pub fn bar_beats(&self) -> Ratio<u32> {
+        Ratio::new(self.numerator() * 4, self.denominator)
+    }
+
+    /// Returns the offset of each beat from the start of the bar, in quarter note beats
+    pub fn beat_offsets(&self) -> Vec<Ratio<u32>> {
+        let unit = Ratio::new(4, self.denominator);
+        let mut offset = Ratio::zero();
+        let mut offsets = vec![];
+        for group in &self.groups {
+            offsets.push(offset);
+            offset += unit * group;
+        }
+        offsets
+    }
+
+    /// Returns the metric weight of a position in the bar, given in quarter note beats. The
+    /// downbeat is strong, and in bars of four or more beats an even number of beats long the
+    /// middle beat is medium.
+    pub fn beat_strength(&self, offset: Ratio<u32>) -> BeatStrength {
+        let bar = self.bar_beats();
+        let offset = offset - (offset / bar).floor() * bar;
+        let beats = self.beat_offsets();
+        match beats.iter().position(|beat| *beat == offset) {
+            Some(0) => BeatStrength::Strong,
+            Some(i) if beats.len() >= 4 && beats.len() % 2 == 0 && i == beats.len() / 2 => BeatStrength::Medium,
+            Some(_) => BeatStrength::Weak,
+            None => BeatStrength::Offbeat,
+        }
+    }
+}
+
+impl fmt::Display for TimeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let is_default = TimeSignature::new(self.numerator(), self.denominator)
+            .map(|default| default.groups == self.groups)
+            .unwrap_or(false);
+        if is_default {
+            write!(f, "{}/{}", self.numerator(), self.denominator)
+        } else {
+            let groups: Vec<String> = self.groups.iter().map(|group| group.to_string()).collect();
+            write!(f, "{}/{}", groups.join("+"), self.denominator)
+        }
+    }
+}
+
+// Parses "3/4", or grouped signatures such as "3+2+2/8"
+impl FromStr for TimeSignature {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<TimeSignature> {
+        let (numerator, denominator) = input.trim().split_once('/').ok_or(Error::Parse)?;
+        let denominator = denominator.trim().parse::<u32>().map_err(|_| Error::Parse)?;
+        let groups = numerator
+            .split('+')
+            .map(|group| group.trim().parse::<u32>().map_err(|_| Error::Parse))
+            .collect::<Result<Vec<u32>>>()?;
+        if groups.len() == 1 {
+            TimeSignature::new(groups[0], denominator)
+        } else {
+            TimeSignature::with_groups(groups, denominator)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Measure {
+    pub time_signature: TimeSignature,
+    pub events: Vec<Event>,
+}
+
+impl Measure {
+    pub fn new(time_signature: TimeSignature) -> Measure {
+        Measure {
+            time_signature,
+            events: vec![],
+        }
+    }
+
+    pub fn beats(&self) -> Ratio<u32> {
+        self.events.iter().map(|event| event.beats()).sum()
+    }
+
+    /// Returns the beats left before the measure is full
+    pub fn remaining(&self) -> Ratio<u32> {
+        let bar = self.time_signature.bar_beats();
+        let beats = self.beats();
+        if beats < bar {
+            bar - beats
+        } else {
+            Ratio::zero()
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Returns each event with its offset from the start of the measure
+    pub fn offsets(&self) -> Vec<(Ratio<u32>, &Event)> {
+        let mut offset = Ratio::zero();
+        self.events
+            .iter()
+            .map(|event| {
+                let start = offset;
+                offset += event.beats();
+                (start, event)
+            })
+            .collect()
+    }
+}
+
+// The tie of one piece of a note split into several
+pub(crate) fn split_tie(original: Tie, first: bool, last: bool) -> Tie {
+    let from_previous = !first || matches!(original, Tie::Stop | Tie::Continue);
+    let into_next = !last || matches!(original, Tie::Start | Tie::Continue);
+    match (from_previous, into_next) {
+        (false, false) => Tie::None,
+        (false, true) => Tie::Start,
+        (true, false) => Tie::Stop,
+        (true, true) => Tie::Continue,
+    }
+}
+
+// Splits an event into tied pieces lasting the given lengths
+fn split_event(event: &Event, lengths: &[Ratio<u32>]) -> Result<Vec<Event>> {
+    let mut durations = vec![];
+    for length in lengths {
+        durations.extend(Duration::tied_from_beats(*length)?);
+    }
+    let count = durations.len();
+    let retie = |note: &Note, i: usize, duration: Duration| Note {
+        duration,
+        tie: split_tie(note.tie, i == 0, i == count - 1),
+        ..*note
+    };
+    Ok(durations
+        .into_iter()
+        .enumerate()
+        .map(|(i, duration)| match event {
+            Event::Note(note) => Event::Note(retie(note, i, duration)),
+            Event::Chord(notes) => Event::Chord(notes.iter().map(|note| retie(note, i, duration)).collect()),
+            Event::Rest(_) => Event::Rest(Rest::new(duration)),
+        })
+        .collect())
+}
+
+/// Fills measures of the time signature with the events, splitting notes that cross a bar line
+/// into tied notes, and pads the last measure with rests
+pub fn fill_measures(events: &[Event], time_signature: &TimeSignature) -> Result<Vec<Measure>> {
+    let mut measures = vec![Measure::new(time_signature.clone())];
+    for event in events {
+        let mut remaining = event.beats();
+        if remaining.is_zero() {
+            return Err(Error::OutofBounds);
+        }
+        // Work out how much of the event lands in each measure, then split it once so the ties
+        // run through every piece
+        let mut lengths = vec![];
+        let mut space = measures.last().map(|measure| measure.remaining()).unwrap_or_default();
+        if space.is_zero() {
+            space = time_signature.bar_beats();
+        }
+        while !remaining.is_zero() {
+            let length = if remaining < space { remaining } else { space };
+            lengths.push(length);
+            remaining -= length;
+            space = time_signature.bar_beats();
+        }
+        let pieces = if lengths.len() == 1 { vec![event.clone()] } else { split_event(event, &lengths)? };
+
+        let mut pieces = pieces.into_iter();
+        for length in lengths {
+            let mut filled = Ratio::zero();
+            while filled < length {
+                let piece = pieces.next().ok_or(Error::OutofBounds)?;
+                filled += piece.beats();
+                if measures.last().map(|measure| measure.is_full()).unwrap_or(true) {
+                    measures.push(Measure::new(time_signature.clone()));
+                }
+                if let Some(measure) = measures.last_mut() {
+                    measure.events.push(piece);
+                }
+            }
+        }
+    }
+    if let Some(measure) = measures.last_mut() {
+        let remaining = measure.remaining();
+        if !remaining.is_zero() {
+            for duration in Duration::tied_from_beats(remaining)? {
+                measure.events.push(Event::Rest(Rest::new(duration)));
+            }
+        }
+    }
+    Ok(measures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitch::PitchOctave;
+
+    #[test]
+    fn test_time_signatures() {
+        let seven = TimeSignature::from_str("7/8").unwrap();
+        assert_eq!(seven.groups, vec![2, 2, 3]);
+        assert_eq!(seven.kind(), MeterType::Irregular);
+        assert_eq!(seven.to_string(), "7/8");
+        let grouped = TimeSignature::from_str("3+2+2/8").unwrap();
+        assert_eq!(grouped.to_string(), "3+2+2/8");
+        assert_eq!(grouped.beat_offsets(), vec![Ratio::zero(), Ratio::new(3, 2), Ratio::new(5, 2)]);
+
+        let six = TimeSignature::from_str("6/8").unwrap();
+        assert_eq!(six.kind(), MeterType::Compound);
+        assert_eq!(six.bar_beats(), Ratio::new(3, 1));
+        assert_eq!(six.beat_strength(Ratio::new(3, 2)), BeatStrength::Weak);
+        assert_eq!(six.beat_strength(Ratio::new(1, 2)), BeatStrength::Offbeat);
+
+        let common = TimeSignature::from_str("4/4").unwrap();
+        assert_eq!(common.kind(), MeterType::Simple);
+        assert_eq!(common.beat_strength(Ratio::zero()), BeatStrength::Strong);
+        assert_eq!(common.beat_strength(Ratio::from_integer(2)), BeatStrength::Medium);
+        assert_eq!(common.beat_strength(Ratio::from_integer(7)), BeatStrength::Weak);
+        assert_eq!(TimeSignature::from_str("4/5"), Err(Error::OutofBounds));
+
+        // Odd meters are irregular whatever the denominator, and bars are capped
+        let five = TimeSignature::from_str("5/4").unwrap();
+        assert_eq!((five.groups.clone(), five.kind()), (vec![2, 3], MeterType::Irregular));
+        assert_eq!(five.to_string(), "5/4");
+        assert_eq!(TimeSignature::from_str("3/4").unwrap().kind(), MeterType::Simple);
+        assert_eq!(TimeSignature::from_str("64/16").unwrap().numerator(), 64);
+        assert_eq!(TimeSignature::from_str("4000000000/4"), Err(Error::OutofBounds));
+        assert_eq!(TimeSignature::from_str("32+33/8"), Err(Error::OutofBounds));
+        assert_eq!(TimeSignature::from_str("4294967295+2/8"), Err(Error::OutofBounds));
+    }
+
+    #[test]
+    fn test_fill_measures() {
+        let pitch = PitchOctave::from_str("C4").unwrap();
+        let dotted_half = Event::Note(Note::new(pitch, Duration::dotted(NoteValue::Half, 1).unwrap()));
+        let time_signature = TimeSignature::from_str("4/4").unwrap();
+        let measures = fill_measures(&[dotted_half.clone(), dotted_half], &time_signature).unwrap();
+        assert_eq!(measures.len(), 2);
+        assert!(measures.iter().all(|measure| measure.is_full()));
+        // The second note is split over the bar line
+        let Event::Note(first) = measures[0].events[1] else { panic!() };
+        let Event::Note(second) = measures[1].events[0] else { panic!() };
+        assert_eq!((first.duration, first.tie), (Duration::new(NoteValue::Quarter), Tie::Start));
+        assert_eq!((second.duration, second.tie), (Duration::new(NoteValue::Half), Tie::Stop));
+        assert_eq!(measures[1].events[1], Event::Rest(Rest::new(Duration::new(NoteValue::Half))));
+    }
+}
@@ -0,0 +1,869 @@
+use crate::chord::{Chord, ChordQuality, Inversion};
+use crate::error::{Error, Result};
+use crate::key::{Key, Mode};
+use crate::melody::Melody;
+use crate::meter::{fill_measures, split_tie, Measure, TimeSignature};
+use crate::notes::Notes;
+use crate::pitch::{Alter, Octave, Pitch, PitchOctave, Pitches, Step};
+use crate::rhythm::{Duration, Event, Note, NoteValue, Rest, Tie, Tuplet};
+use crate::scale::Scale;
+pub use crate::score::{Part, Score};
+use num::integer::lcm;
+use num::rational::Ratio;
+use num::Zero;
+use roxmltree::{Document, Node as XmlNode, ParsingOptions};
+use std::fs;
+use std::io::{Cursor, Read};
+use std::path::Path;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use zip::ZipArchive;
+
+const DOCTYPE: &str = "<!DOCTYPE score-partwise PUBLIC \"-//Recordare//DTD MusicXML 4.0 Partwise//EN\" \"http://www.musicxml.org/dtds/partwise.dtd\">";
+const PART_ID: &str = "P1";
+
+/// Settings for the score the music is written into
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MusicXmlOptions {
+    pub title: Option<String>,
+    pub part_name: String,
+    // Written as the key signature, C major when None
+    pub key: Option<Key>,
+    pub time_signature: TimeSignature,
+}
+
+impl Default for MusicXmlOptions {
+    fn default() -> MusicXmlOptions {
+        MusicXmlOptions {
+            title: None,
+            part_name: "Music".to_string(),
+            key: None,
+            time_signature: TimeSignature { groups: vec![1; 4], denominator: 4 },
+        }
+    }
+}
+
+pub trait ToMusicXml {
+    /// Writes a MusicXML 4.0 partwise score with a single part
+    fn to_musicxml(&self, options: &MusicXmlOptions) -> Result<String>;
+}
+
+// Indents each element on its own line, as notation programs write it
+struct XmlWriter {
+    out: String,
+    depth: usize,
+}
+
+impl XmlWriter {
+    fn new() -> XmlWriter {
+        XmlWriter {
+            out: "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n".to_string(),
+            depth: 0,
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        self.out.push_str(&"  ".repeat(self.depth));
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn open(&mut self, tag: &str) {
+        self.line(&format!("<{}>", tag));
+        self.depth += 1;
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.depth -= 1;
+        self.line(&format!("</{}>", tag));
+    }
+
+    fn element(&mut self, tag: &str, text: &str) {
+        let name = tag.split_whitespace().next().unwrap_or(tag);
+        self.line(&format!("<{}>{}</{}>", tag, escape(text), name));
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// MusicXML alters count semitones, so quarter tones are written as halves
+fn alter_value(alter: Alter) -> Option<String> {
+    match alter {
+        Alter::None => None,
+        alter => {
+            let quarter_tones = alter as i8;
+            if quarter_tones % 2 == 0 {
+                Some((quarter_tones / 2).to_string())
+            } else {
+                Some(format!("{}", quarter_tones as f32 / 2.0))
+            }
+        }
+    }
+}
+
+fn type_name(value: NoteValue) -> &'static str {
+    match value {
+        NoteValue::Whole => "whole",
+        NoteValue::Half => "half",
+        NoteValue::Quarter => "quarter",
+        NoteValue::Eighth => "eighth",
+        NoteValue::Sixteenth => "16th",
+        NoteValue::ThirtySecond => "32nd",
+        NoteValue::SixtyFourth => "64th",
+        NoteValue::HundredTwentyEighth => "128th",
+    }
+}
+
+// The MusicXML kind closest to the quality, with degrees for the tones it leaves out, as
+// (value, alter, type)
+fn harmony_kind(quality: ChordQuality) -> (&'static str, Vec<(u8, i8, &'static str)>) {
+    match quality {
+        ChordQuality::Major => ("major", vec![]),
+        ChordQuality::Minor => ("minor", vec![]),
+        ChordQuality::Diminished => ("diminished", vec![]),
+        ChordQuality::Sus4 => ("suspended-fourth", vec![]),
+        ChordQuality::Dom7Sus4 => ("dominant", vec![(3, 0, "subtract"), (4, 0, "add")]),
+        ChordQuality::Major7 => ("major-seventh", vec![]),
+        ChordQuality::MinorMajor7 => ("major-minor", vec![]),
+        ChordQuality::Minor7 => ("minor-seventh", vec![]),
+        ChordQuality::Dom7 => ("dominant", vec![]),
+        ChordQuality::Diminished7 => ("diminished-seventh", vec![]),
+        ChordQuality::Major7Sharp5 => ("major-seventh", vec![(5, 1, "alter")]),
+        ChordQuality::Dom7Sharp5 => ("augmented-seventh", vec![]),
+        ChordQuality::Augmented => ("augmented", vec![]),
+        ChordQuality::Minor7Flat5 => ("half-diminished", vec![]),
+        ChordQuality::Major7Flat5 => ("major-seventh", vec![(5, -1, "alter")]),
+        ChordQuality::Add9 => ("major", vec![(9, 0, "add")]),
+        ChordQuality::Major9 => ("major-ninth", vec![]),
+        ChordQuality::Dom9 => ("dominant-ninth", vec![]),
+        ChordQuality::MinorAdd9 => ("minor", vec![(9, 0, "add")]),
+        ChordQuality::MinorMajor7Add9 => ("major-minor", vec![(9, 0, "add")]),
+        ChordQuality::Minor9 => ("minor-ninth", vec![]),
+        ChordQuality::Major7Sharp11 => ("major-seventh", vec![(11, 1, "add")]),
+        ChordQuality::Major9Sharp11 => ("major-ninth", vec![(11, 1, "add")]),
+        ChordQuality::Dom7Sharp11 => ("dominant", vec![(11, 1, "add")]),
+        ChordQuality::Dom9Sharp11 => ("dominant-ninth", vec![(11, 1, "add")]),
+        ChordQuality::Dom13 => ("dominant", vec![(13, 0, "add")]),
+        ChordQuality::Dom9Thirteenth => ("dominant-ninth", vec![(13, 0, "add")]),
+        ChordQuality::Dom7Flat9 => ("dominant", vec![(9, -1, "add")]),
+        ChordQuality::Dom7Flat13 => ("dominant", vec![(13, -1, "add")]),
+        ChordQuality::Dom7Flat13Flat9 => ("dominant", vec![(9, -1, "add"), (13, -1, "add")]),
+        ChordQuality::Dom11Flat13Flat9 => ("dominant-11th", vec![(9, -1, "alter"), (13, -1, "add")]),
+        ChordQuality::Dom7Sharp9 => ("dominant", vec![(9, 1, "add")]),
+        ChordQuality::Minor7Add11 => ("minor-seventh", vec![(11, 0, "add")]),
+        ChordQuality::Minor11 => ("minor-11th", vec![]),
+        ChordQuality::ItalianSixth => ("Italian", vec![]),
+        ChordQuality::FrenchSixth => ("French", vec![]),
+        ChordQuality::GermanSixth => ("German", vec![]),
+    }
+}
+
+fn write_harmony(xml: &mut XmlWriter, chord: &Chord) -> Result<()> {
+    let step_alter = |xml: &mut XmlWriter, prefix: &str, pitch: Pitch| {
+        xml.element(&format!("{}-step", prefix), &pitch.step.to_string());
+        if let Some(alter) = alter_value(pitch.alter) {
+            xml.element(&format!("{}-alter", prefix), &alter);
+        }
+    };
+    let (kind, degrees) = harmony_kind(chord.kind);
+    xml.open("harmony");
+    xml.open("root");
+    step_alter(xml, "root", chord.root.pitch);
+    xml.close("root");
+    xml.element(&format!("kind text=\"{}\"", escape(chord.kind.symbol())), kind);
+    if chord.inversion != Inversion::Root {
+        xml.open("bass");
+        step_alter(xml, "bass", chord.bass()?.pitch);
+        xml.close("bass");
+    }
+    for (value, alter, kind) in degrees {
+        xml.open("degree");
+        xml.element("degree-value", &value.to_string());
+        xml.element("degree-alter", &alter.to_string());
+        xml.element("degree-type", kind);
+        xml.close("degree");
+    }
+    xml.close("harmony");
+    Ok(())
+}
+
+fn write_pitch(xml: &mut XmlWriter, pitch: &PitchOctave) {
+    xml.open("pitch");
+    xml.element("step", &pitch.pitch.step.to_string());
+    if let Some(alter) = alter_value(pitch.pitch.alter) {
+        xml.element("alter", &alter);
+    }
+    xml.element("octave", &pitch.octave.to_string());
+    xml.close("pitch");
+}
+
+// Writes a note, a rest when there is no pitch, sharing the duration of the chord it stacks on
+fn write_note(xml: &mut XmlWriter, note: Option<&Note>, duration: Duration, divisions: u32, in_chord: bool) {
+    let length = (duration.beats() * divisions).to_integer();
+    let ties: &[&str] = match note.map(|note| note.tie).unwrap_or_default() {
+        Tie::None => &[],
+        Tie::Start => &["start"],
+        Tie::Stop => &["stop"],
+        Tie::Continue => &["stop", "start"],
+    };
+    xml.open("note");
+    if in_chord {
+        xml.line("<chord/>");
+    }
+    match note {
+        Some(note) => write_pitch(xml, &note.pitch),
+        None => xml.line("<rest/>"),
+    }
+    xml.element("duration", &length.to_string());
+    for tie in ties {
+        xml.line(&format!("<tie type=\"{}\"/>", tie));
+    }
+    xml.element("voice", "1");
+    xml.element("type", type_name(duration.value()));
+    for _ in 0..duration.dots() {
+        xml.line("<dot/>");
+    }
+    if let Some(tuplet) = duration.tuplet() {
+        xml.open("time-modification");
+        xml.element("actual-notes", &tuplet.actual().to_string());
+        xml.element("normal-notes", &tuplet.normal().to_string());
+        xml.close("time-modification");
+    }
+    if !ties.is_empty() {
+        xml.open("notations");
+        for tie in ties {
+            xml.line(&format!("<tied type=\"{}\"/>", tie));
+        }
+        xml.close("notations");
+    }
+    xml.close("note");
+}
+
+/// Writes the events as a single part score, split into measures of the options' time signature
+/// with notes tied over bar lines. Each harmony is placed at the first event starting at or
+/// after its offset, given in quarter note beats from the start.
+pub fn write_musicxml(events: &[Event], harmonies: &[(Ratio<u32>, Chord)], options: &MusicXmlOptions) -> Result<String> {
+    let measures = fill_measures(events, &options.time_signature)?;
+    // Divisions of the quarter note fine enough for every duration
+    let divisions = measures
+        .iter()
+        .flat_map(|measure| measure.events.iter())
+        .fold(1, |divisions, event| lcm(divisions, event.beats().denom().to_owned()));
+    let midi = events
+        .iter()
+        .flat_map(|event| event.pitches())
+        .map(|pitch| pitch.get_semitone_value())
+        .collect::<Result<Vec<i8>>>()?;
+    let mean = midi.iter().map(|pitch| *pitch as i32).sum::<i32>() / (midi.len().max(1) as i32);
+    let (clef, clef_line) = if !midi.is_empty() && mean < 60 { ("F", "4") } else { ("G", "2") };
+
+    let mut xml = XmlWriter::new();
+    xml.line(DOCTYPE);
+    xml.open("score-partwise version=\"4.0\"");
+    if let Some(title) = &options.title {
+        xml.open("work");
+        xml.element("work-title", title);
+        xml.close("work");
+    }
+    xml.open("part-list");
+    xml.open(&format!("score-part id=\"{}\"", PART_ID));
+    xml.element("part-name", &options.part_name);
+    xml.close("score-part");
+    xml.close("part-list");
+    xml.open(&format!("part id=\"{}\"", PART_ID));
+
+    let mut harmonies: Vec<&(Ratio<u32>, Chord)> = harmonies.iter().collect();
+    harmonies.sort_by_key(|(offset, _)| *offset);
+    let mut harmonies = harmonies.into_iter().peekable();
+    let mut onset = Ratio::zero();
+    for (number, measure) in measures.iter().enumerate() {
+        xml.open(&format!("measure number=\"{}\"", number + 1));
+        if number == 0 {
+            xml.open("attributes");
+            xml.element("divisions", &divisions.to_string());
+            xml.open("key");
+            xml.element("fifths", &options.key.map(|key| key.fifths()).unwrap_or(0).to_string());
+            if let Some(key) = options.key {
+                xml.element("mode", if key.mode == Mode::Major { "major" } else { "minor" });
+            }
+            xml.close("key");
+            xml.open("time");
+            xml.element("beats", &options.time_signature.numerator().to_string());
+            xml.element("beat-type", &options.time_signature.denominator.to_string());
+            xml.close("time");
+            xml.open("clef");
+            xml.element("sign", clef);
+            xml.element("line", clef_line);
+            xml.close("clef");
+            xml.close("attributes");
+        }
+        for event in &measure.events {
+            while let Some((_, chord)) = harmonies.next_if(|(offset, _)| *offset <= onset) {
+                write_harmony(&mut xml, chord)?;
+            }
+            let duration = event.duration()?;
+            match event {
+                Event::Note(note) => write_note(&mut xml, Some(note), duration, divisions, false),
+                Event::Chord(notes) => {
+                    for (i, note) in notes.iter().enumerate() {
+                        write_note(&mut xml, Some(note), duration, divisions, i > 0);
+                    }
+                }
+                Event::Rest(_) => write_note(&mut xml, None, duration, divisions, false),
+            }
+            onset += event.beats();
+        }
+        xml.close("measure");
+    }
+    if harmonies.next().is_some() {
+        // Harmonies after the last event have nowhere to go
+        return Err(Error::OutofBounds);
+    }
+    xml.close("part");
+    xml.close("score-partwise");
+    Ok(xml.out)
+}
+
+fn quarter_notes(pitches: &Pitches) -> Vec<Event> {
+    pitches
+        .0
+        .iter()
+        .map(|pitch| Event::Note(Note::new(*pitch, Duration::new(NoteValue::Quarter))))
+        .collect()
+}
+
+impl ToMusicXml for Pitches {
+    fn to_musicxml(&self, options: &MusicXmlOptions) -> Result<String> {
+        write_musicxml(&quarter_notes(self), &[], options)
+    }
+}
+
+impl ToMusicXml for Scale {
+    fn to_musicxml(&self, options: &MusicXmlOptions) -> Result<String> {
+        write_musicxml(&quarter_notes(&self.notes()?), &[], options)
+    }
+}
+
+impl ToMusicXml for Melody {
+    fn to_musicxml(&self, options: &MusicXmlOptions) -> Result<String> {
+        let events: Vec<Event> = self.notes.iter().map(|note| Event::Note(*note)).collect();
+        write_musicxml(&events, &[], options)
+    }
+}
+
+// The chord fills a bar, or a whole note when no single duration lasts the bar
+impl ToMusicXml for Chord {
+    fn to_musicxml(&self, options: &MusicXmlOptions) -> Result<String> {
+        let bar = options.time_signature.bar_beats();
+        let duration = Duration::from_beats(bar).unwrap_or(Duration::new(NoteValue::Whole));
+        let notes = self.notes()?.0.iter().map(|pitch| Note::new(*pitch, duration)).collect();
+        write_musicxml(&[Event::Chord(notes)], &[(Ratio::zero(), self.clone())], options)
+    }
+}
+
+impl ToMusicXml for [Chord] {
+    fn to_musicxml(&self, options: &MusicXmlOptions) -> Result<String> {
+        let bar = options.time_signature.bar_beats();
+        let duration = Duration::from_beats(bar).unwrap_or(Duration::new(NoteValue::Whole));
+        let mut events = vec![];
+        let mut harmonies = vec![];
+        for chord in self {
+            harmonies.push((Ratio::from_integer(events.len() as u32) * duration.beats(), chord.clone()));
+            events.push(Event::Chord(chord.notes()?.0.iter().map(|pitch| Note::new(*pitch, duration)).collect()));
+        }
+        write_musicxml(&events, &harmonies, options)
+    }
+}
+
+fn child<'a, 'input>(node: XmlNode<'a, 'input>, name: &str) -> Option<XmlNode<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn child_text<'a>(node: XmlNode<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name).and_then(|child| child.text()).map(|text| text.trim())
+}
+
+fn parse_number<T: FromStr>(text: Option<&str>) -> Result<T> {
+    text.ok_or(Error::Parse)?.parse::<T>().map_err(|_| Error::Parse)
+}
+
+fn parse_alter(text: Option<&str>) -> Result<Alter> {
+    let Some(text) = text else {
+        return Ok(Alter::None);
+    };
+    let semitones = text.parse::<f32>().map_err(|_| Error::Parse)?;
+    match (semitones * 2.0).round() as i8 {
+        -4 => Ok(Alter::DoubleFlat),
+        -2 => Ok(Alter::Flat),
+        -1 => Ok(Alter::HalfFlat),
+        0 => Ok(Alter::None),
+        1 => Ok(Alter::HalfSharp),
+        2 => Ok(Alter::Sharp),
+        4 => Ok(Alter::DoubleSharp),
+        _ => Err(Error::Unsupported),
+    }
+}
+
+// Reads a step and alter written with the prefix, as in <root-step> and <root-alter>
+fn parse_step_alter(node: XmlNode, prefix: &str) -> Result<Pitch> {
+    let step = Step::from_str(child_text(node, &format!("{}-step", prefix)).ok_or(Error::Parse)?)?;
+    Ok(Pitch::new(step, parse_alter(child_text(node, &format!("{}-alter", prefix)))?))
+}
+
+fn parse_pitch(node: XmlNode) -> Result<PitchOctave> {
+    let step = Step::from_str(child_text(node, "step").ok_or(Error::Parse)?)?;
+    let alter = parse_alter(child_text(node, "alter"))?;
+    let octave: u8 = parse_number(child_text(node, "octave"))?;
+    if octave > Octave::Octave8 as u8 {
+        return Err(Error::OutofBounds);
+    }
+    Ok(PitchOctave::new(Pitch::new(step, alter), Octave::from(octave)))
+}
+
+fn note_value(name: &str) -> Result<NoteValue> {
+    NoteValue::iter().find(|value| type_name(*value) == name).ok_or(Error::Parse)
+}
+
+// The chord a harmony element spells, or None for kinds no quality matches, such as "none"
+fn parse_harmony(node: XmlNode) -> Result<Option<Chord>> {
+    let root = parse_step_alter(child(node, "root").ok_or(Error::Parse)?, "root")?;
+    let kind = child_text(node, "kind").ok_or(Error::Parse)?;
+    let kind = if kind == "dominant-seventh" { "dominant" } else { kind };
+    let mut degrees = vec![];
+    for degree in node.children().filter(|child| child.has_tag_name("degree")) {
+        let value: u8 = parse_number(child_text(degree, "degree-value"))?;
+        let alter: i8 = parse_number(child_text(degree, "degree-alter")).unwrap_or(0);
+        let kind = child_text(degree, "degree-type").ok_or(Error::Parse)?;
+        degrees.push((value, alter, kind));
+    }
+    let quality = ChordQuality::iter()
+        .find(|quality| harmony_kind(*quality) == (kind, degrees.clone()))
+        .or_else(|| ChordQuality::iter().find(|quality| harmony_kind(*quality) == (kind, vec![])));
+    let Some(quality) = quality else {
+        return Ok(None);
+    };
+    let symbol = match child(node, "bass") {
+        Some(bass) => format!("{}{}/{}", root, quality.symbol(), parse_step_alter(bass, "bass")?),
+        None => format!("{}{}", root, quality.symbol()),
+    };
+    Chord::from_symbol(&symbol, Octave::default()).map(Some)
+}
+
+// Follows one voice of a part through its measures
+struct PartReader {
+    divisions: u32,
+    time_signature: TimeSignature,
+    key: Option<Key>,
+    voice: Option<String>,
+    onset: Ratio<u32>,
+    measures: Vec<Measure>,
+    harmonies: Vec<(Ratio<u32>, Chord)>,
+}
+
+impl PartReader {
+    fn new() -> PartReader {
+        PartReader {
+            divisions: 1,
+            time_signature: MusicXmlOptions::default().time_signature,
+            key: None,
+            voice: None,
+            onset: Ratio::zero(),
+            measures: vec![],
+            harmonies: vec![],
+        }
+    }
+
+    fn beats(&self, divisions: u32) -> Ratio<u32> {
+        Ratio::new(divisions, self.divisions)
+    }
+
+    // Keeps the first voice met, so notes of other voices and the backups to them are skipped
+    fn is_followed(&mut self, node: XmlNode) -> bool {
+        let voice = child_text(node, "voice").unwrap_or("1");
+        match &self.voice {
+            Some(followed) => followed == voice,
+            None => {
+                self.voice = Some(voice.to_string());
+                true
+            }
+        }
+    }
+
+    fn read_attributes(&mut self, node: XmlNode) -> Result<()> {
+        if let Some(divisions) = child_text(node, "divisions") {
+            self.divisions = parse_number(Some(divisions))?;
+            if self.divisions == 0 {
+                return Err(Error::OutofBounds);
+            }
+        }
+        if let Some(key) = child(node, "key") {
+            let mode = if child_text(key, "mode") == Some("minor") { Mode::Minor } else { Mode::Major };
+            if let Some(fifths) = child_text(key, "fifths") {
+                let key = Key::from_fifths(parse_number(Some(fifths))?, mode)?;
+                self.key.get_or_insert(key);
+            }
+        }
+        if let Some(time) = child(node, "time") {
+            let beats = child_text(time, "beats").ok_or(Error::Parse)?;
+            let beat_type = child_text(time, "beat-type").ok_or(Error::Parse)?;
+            self.time_signature = TimeSignature::from_str(&format!("{}/{}", beats, beat_type))?;
+        }
+        Ok(())
+    }
+
+    fn read_note(&mut self, node: XmlNode, events: &mut Vec<Event>) -> Result<()> {
+        if child(node, "grace").is_some() || child(node, "cue").is_some() || !self.is_followed(node) {
+            return Ok(());
+        }
+        let rest = child(node, "rest");
+        let durations = match child_text(node, "type") {
+            Some(name) if rest.and_then(|rest| rest.attribute("measure")) != Some("yes") => {
+                let dots = node.children().filter(|child| child.has_tag_name("dot")).count();
+                let dots = u8::try_from(dots).map_err(|_| Error::Unsupported)?;
+                let mut duration = Duration::dotted(note_value(name)?, dots)?;
+                if let Some(modification) = child(node, "time-modification") {
+                    duration = duration.with_tuplet(Tuplet::new(
+                        parse_number(child_text(modification, "actual-notes"))?,
+                        parse_number(child_text(modification, "normal-notes"))?,
+                    )?);
+                }
+                vec![duration]
+            }
+            _ => Duration::tied_from_beats(self.beats(parse_number(child_text(node, "duration"))?))?,
+        };
+        let pitch = match child(node, "pitch") {
+            Some(pitch) if rest.is_none() => Some(parse_pitch(pitch)?),
+            _ => None,
+        };
+        let Some(pitch) = pitch else {
+            for duration in durations {
+                self.onset += duration.beats();
+                events.push(Event::Rest(Rest::new(duration)));
+            }
+            return Ok(());
+        };
+        let ties: Vec<&str> = node
+            .children()
+            .filter(|child| child.has_tag_name("tie"))
+            .filter_map(|tie| tie.attribute("type"))
+            .collect();
+        let tie = match (ties.contains(&"stop"), ties.contains(&"start")) {
+            (false, false) => Tie::None,
+            (false, true) => Tie::Start,
+            (true, false) => Tie::Stop,
+            (true, true) => Tie::Continue,
+        };
+        let count = durations.len();
+        // A note too long for one duration becomes tied notes
+        let mut notes = durations.into_iter().enumerate().map(|(i, duration)| Note {
+            tie: split_tie(tie, i == 0, i == count - 1),
+            ..Note::new(pitch, duration)
+        });
+        if child(node, "chord").is_some() {
+            // Stacked notes share the duration of the note they stack on
+            let note = notes.next().ok_or(Error::Parse)?;
+            match events.pop() {
+                Some(Event::Note(first)) => events.push(Event::Chord(vec![first, note])),
+                Some(Event::Chord(mut stacked)) => {
+                    stacked.push(note);
+                    events.push(Event::Chord(stacked));
+                }
+                _ => return Err(Error::Parse),
+            }
+            return Ok(());
+        }
+        for note in notes {
+            self.onset += note.beats();
+            events.push(Event::Note(note));
+        }
+        Ok(())
+    }
+
+    fn read_measure(&mut self, node: XmlNode) -> Result<()> {
+        let mut events = vec![];
+        for element in node.children().filter(|child| child.is_element()) {
+            match element.tag_name().name() {
+                "attributes" => self.read_attributes(element)?,
+                "note" => self.read_note(element, &mut events)?,
+                "harmony" => {
+                    let offset: i32 = parse_number(child_text(element, "offset")).unwrap_or(0);
+                    let onset = if offset >= 0 { self.onset + self.beats(offset as u32) } else { self.onset };
+                    if let Some(chord) = parse_harmony(element)? {
+                        self.harmonies.push((onset, chord));
+                    }
+                }
+                "forward" if self.is_followed(element) => {
+                    for duration in Duration::tied_from_beats(self.beats(parse_number(child_text(element, "duration"))?))? {
+                        self.onset += duration.beats();
+                        events.push(Event::Rest(Rest::new(duration)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.measures.push(Measure {
+            time_signature: self.time_signature.clone(),
+            events,
+        });
+        Ok(())
+    }
+}
+
+/// Reads a MusicXML score, partwise or timewise. Each part keeps a single voice, the first in
+/// the part, and grace and cue notes are skipped.
+pub fn read_musicxml(xml: &str) -> Result<Score> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(xml, options).map_err(|_| Error::Parse)?;
+    let root = document.root_element();
+    let title = child(root, "work")
+        .and_then(|work| child_text(work, "work-title"))
+        .or_else(|| child_text(root, "movement-title"))
+        .map(|title| title.to_string());
+    let part_list = child(root, "part-list").ok_or(Error::Parse)?;
+    let mut parts = vec![];
+    for score_part in part_list.children().filter(|child| child.has_tag_name("score-part")) {
+        let id = score_part.attribute("id").ok_or(Error::Parse)?;
+        // Timewise scores hold the parts inside each measure, the other way around
+        let measures: Vec<XmlNode> = match root.tag_name().name() {
+            "score-partwise" => root
+                .children()
+                .find(|part| part.has_tag_name("part") && part.attribute("id") == Some(id))
+                .ok_or(Error::Parse)?
+                .children()
+                .filter(|measure| measure.has_tag_name("measure"))
+                .collect(),
+            "score-timewise" => root
+                .children()
+                .filter(|measure| measure.has_tag_name("measure"))
+                .filter_map(|measure| measure.children().find(|part| part.has_tag_name("part") && part.attribute("id") == Some(id)))
+                .collect(),
+            _ => return Err(Error::Parse),
+        };
+        let mut reader = PartReader::new();
+        for measure in measures {
+            reader.read_measure(measure)?;
+        }
+        parts.push(Part {
+            id: id.to_string(),
+            name: child_text(score_part, "part-name").unwrap_or_default().to_string(),
+            key: reader.key,
+            measures: reader.measures,
+            harmonies: reader.harmonies,
+        });
+    }
+    Ok(Score { title, parts })
+}
+
+/// Reads a compressed MusicXML (.mxl) archive, finding the score through its container file
+pub fn read_mxl(bytes: &[u8]) -> Result<Score> {
+    let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|_| Error::Encoding)?;
+    let read = |archive: &mut ZipArchive<Cursor<&[u8]>>, name: &str| -> Result<String> {
+        let mut text = String::new();
+        let mut file = archive.by_name(name).map_err(|_| Error::Encoding)?;
+        file.read_to_string(&mut text).map_err(|error| Error::IoKind(error.to_string()))?;
+        Ok(text)
+    };
+    let container = read(&mut archive, "META-INF/container.xml")?;
+    let container = Document::parse(&container).map_err(|_| Error::Parse)?;
+    let path = container
+        .descendants()
+        .find(|node| node.has_tag_name("rootfile"))
+        .and_then(|rootfile| rootfile.attribute("full-path"))
+        .ok_or(Error::Parse)?
+        .to_string();
+    read_musicxml(&read(&mut archive, &path)?)
+}
+
+/// Reads a score from a file, compressed or not
+pub fn read_musicxml_file<P: AsRef<Path>>(path: P) -> Result<Score> {
+    let bytes = fs::read(path).map_err(|error| Error::IoKind(error.to_string()))?;
+    // Zip archives start with "PK"
+    if bytes.starts_with(b"PK") {
+        read_mxl(&bytes)
+    } else {
+        read_musicxml(std::str::from_utf8(&bytes).map_err(|_| Error::Encoding)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitch::Octave;
+    use crate::scale::ScaleType;
+    use std::io::Write;
+
+    #[test]
+    fn test_pitches_to_musicxml() {
+        let pitches = Pitches(
+            ["C#4", "Bb3", "E4", "F4", "G4"]
+                .iter()
+                .map(|p| PitchOctave::from_str(p).unwrap())
+                .collect(),
+        );
+        let xml = pitches.to_musicxml(&MusicXmlOptions::default()).unwrap();
+        assert!(xml.contains("<score-partwise version=\"4.0\">"));
+        assert!(xml.contains("<step>C</step>\n          <alter>1</alter>\n          <octave>4</octave>"));
+        assert!(xml.contains("<step>B</step>\n          <alter>-1</alter>\n          <octave>3</octave>"));
+        // The fifth note starts a second bar, padded with rests
+        assert!(xml.contains("<measure number=\"2\">"));
+        assert_eq!(xml.matches("<rest/>").count(), 1);
+        assert_eq!(xml.matches("<note>").count(), xml.matches("</note>").count());
+
+        let options = MusicXmlOptions {
+            title: Some("Scales & modes".to_string()),
+            key: Some(Key::from_str("D major").unwrap()),
+            ..MusicXmlOptions::default()
+        };
+        let scale = Scale::new(PitchOctave::from_str("D4").unwrap(), ScaleType::Ionian);
+        let xml = scale.to_musicxml(&options).unwrap();
+        assert!(xml.contains("<work-title>Scales &amp; modes</work-title>"));
+        assert!(xml.contains("<fifths>2</fifths>"));
+        assert_eq!(xml.matches("<type>quarter</type>").count(), 8);
+    }
+
+    #[test]
+    fn test_chord_to_musicxml() {
+        let chord = Chord::from_symbol("Cm7/Eb", Octave::Octave4).unwrap();
+        let xml = chord.to_musicxml(&MusicXmlOptions::default()).unwrap();
+        assert!(xml.contains("<root-step>C</root-step>"));
+        assert!(xml.contains("<kind text=\"m7\">minor-seventh</kind>"));
+        assert!(xml.contains("<bass-step>E</bass-step>\n          <bass-alter>-1</bass-alter>"));
+        assert_eq!(xml.matches("<chord/>").count(), 3);
+        assert_eq!(xml.matches("<type>whole</type>").count(), 4);
+
+        let melody = Melody::new(vec![
+            Note::new(PitchOctave::from_str("G4").unwrap(), Duration::dotted(NoteValue::Half, 1).unwrap()),
+            Note::new(PitchOctave::from_str("A4").unwrap(), Duration::new(NoteValue::Half)),
+        ]);
+        let xml = melody.to_musicxml(&MusicXmlOptions::default()).unwrap();
+        assert!(xml.contains("<tie type=\"start\"/>"));
+        assert!(xml.contains("<tied type=\"stop\"/>"));
+        assert!(xml.contains("<dot/>"));
+
+        let progression: Vec<Chord> = ["Dm7", "G7", "Cmaj7"]
+            .iter()
+            .map(|symbol| Chord::from_symbol(symbol, Octave::Octave3).unwrap())
+            .collect();
+        let xml = progression.to_musicxml(&MusicXmlOptions::default()).unwrap();
+        assert_eq!(xml.matches("<harmony>").count(), 3);
+        assert!(xml.contains("<clef>\n          <sign>F</sign>"));
+    }
+
+    #[test]
+    fn test_musicxml_edge_cases() {
+        // Harmonies after the last event have nowhere to be written
+        let chord = Chord::from_symbol("C", Octave::Octave4).unwrap();
+        let events = [Event::Rest(Rest::new(Duration::new(NoteValue::Whole)))];
+        let late = [(Ratio::from_integer(8), chord)];
+        assert_eq!(write_musicxml(&events, &late, &MusicXmlOptions::default()), Err(Error::OutofBounds));
+
+        // A quintuplet crossing the bar line is tied over it, keeping its time modification
+        let quintuplet = Duration::new(NoteValue::Eighth).with_tuplet(Tuplet::new(5, 4).unwrap());
+        let mut events = vec![Event::Rest(Rest::new(Duration::dotted(NoteValue::Half, 1).unwrap()))];
+        events.extend((0..5).map(|_| Event::Note(Note::new(PitchOctave::from_str("A4").unwrap(), quintuplet))));
+        let xml = write_musicxml(&events, &[], &MusicXmlOptions::default()).unwrap();
+        assert!(xml.contains("<divisions>5</divisions>"));
+        assert!(xml.contains("<measure number=\"2\">"));
+        assert_eq!(xml.matches("<actual-notes>5</actual-notes>").count(), 6);
+        assert_eq!(xml.matches("<type>16th</type>").count(), 2);
+        assert_eq!(xml.matches("<tie type=\"start\"/>").count(), 1);
+
+        // Quarter tones are written as half semitones
+        let pitches = Pitches(vec![
+            PitchOctave::new(Pitch::new(Step::D, Alter::HalfFlat), Octave::Octave4),
+            PitchOctave::new(Pitch::new(Step::E, Alter::HalfSharp), Octave::Octave4),
+        ]);
+        let xml = pitches.to_musicxml(&MusicXmlOptions::default()).unwrap();
+        assert!(xml.contains("<alter>-0.5</alter>"));
+        assert!(xml.contains("<alter>0.5</alter>"));
+    }
+
+    #[test]
+    fn test_read_musicxml() {
+        // Writing a melody and progression then reading them back keeps the notes and harmonies
+        let melody = Melody::new(vec![
+            Note::new(PitchOctave::from_str("F#4").unwrap(), Duration::dotted(NoteValue::Half, 1).unwrap()),
+            Note::new(PitchOctave::from_str("Bb4").unwrap(), Duration::new(NoteValue::Half)),
+        ]);
+        let options = MusicXmlOptions {
+            key: Some(Key::from_str("G major").unwrap()),
+            ..MusicXmlOptions::default()
+        };
+        let score = read_musicxml(&melody.to_musicxml(&options).unwrap()).unwrap();
+        let part = &score.parts[0];
+        assert_eq!(part.key.unwrap().to_string(), "G major");
+        assert_eq!(part.measures.len(), 2);
+        let Event::Note(tied) = part.measures[1].events[0] else { panic!() };
+        assert_eq!((tied.pitch.to_string(), tied.tie), ("Bb4".to_string(), Tie::Stop));
+
+        let progression: Vec<Chord> = ["Dm7", "G7/B", "Cmaj7"]
+            .iter()
+            .map(|symbol| Chord::from_symbol(symbol, Octave::Octave3).unwrap())
+            .collect();
+        let score = read_musicxml(&progression.to_musicxml(&options).unwrap()).unwrap();
+        let harmonies: Vec<String> = score.parts[0].harmonies.iter().map(|(_, chord)| chord.symbol().unwrap()).collect();
+        assert_eq!(harmonies, vec!["Dm7", "G7/B", "Cmaj7"]);
+        assert_eq!(score.parts[0].harmonies[1].0, Ratio::from_integer(4));
+        assert_eq!(score.parts[0].events()[2].pitches().len(), 4);
+
+        let timewise = r#"<?xml version="1.0" encoding="UTF-8"?>
+<score-timewise version="4.0">
+  <part-list>
+    <score-part id="P1"><part-name>Flute</part-name></score-part>
+  </part-list>
+  <measure number="1">
+    <part id="P1">
+      <attributes><divisions>2</divisions><time><beats>3</beats><beat-type>4</beat-type></time></attributes>
+      <note><pitch><step>E</step><alter>-1</alter><octave>5</octave></pitch><duration>2</duration><type>quarter</type></note>
+      <note><rest/><duration>1</duration><type>eighth</type></note>
+      <note><pitch><step>C</step><octave>5</octave></pitch><duration>3</duration><type>quarter</type><dot/></note>
+    </part>
+  </measure>
+</score-timewise>"#;
+        let score = read_musicxml(timewise).unwrap();
+        assert_eq!(score.parts[0].name, "Flute");
+        let measure = &score.parts[0].measures[0];
+        assert_eq!(measure.time_signature.to_string(), "3/4");
+        assert!(measure.is_full());
+        assert_eq!(measure.events[0].pitches()[0].to_string(), "Eb5");
+
+        let mut bytes = Cursor::new(vec![]);
+        let mut archive = zip::ZipWriter::new(&mut bytes);
+        archive.start_file("META-INF/container.xml", zip::write::FileOptions::default()).unwrap();
+        archive
+            .write_all(br#"<container><rootfiles><rootfile full-path="score.xml"/></rootfiles></container>"#)
+            .unwrap();
+        archive.start_file("score.xml", zip::write::FileOptions::default()).unwrap();
+        archive.write_all(timewise.as_bytes()).unwrap();
+        archive.finish().unwrap();
+        drop(archive);
+        assert_eq!(read_mxl(bytes.get_ref()).unwrap(), score);
+        let path = std::env::temp_dir().join(format!("mulib-{}.mxl", std::process::id()));
+        fs::write(&path, bytes.get_ref()).unwrap();
+        let from_file = read_musicxml_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(from_file.unwrap(), score);
+
+        // Quarter tones survive a round trip
+        let quarter_tones = Pitches(vec![
+            PitchOctave::new(Pitch::new(Step::B, Alter::HalfFlat), Octave::Octave3),
+            PitchOctave::new(Pitch::new(Step::F, Alter::HalfSharp), Octave::Octave4),
+        ]);
+        let score = read_musicxml(&quarter_tones.to_musicxml(&MusicXmlOptions::default()).unwrap()).unwrap();
+        assert_eq!(score.parts[0].events()[0].pitches()[0], quarter_tones.0[0]);
+        assert_eq!(score.parts[0].events()[1].pitches()[0], quarter_tones.0[1]);
+
+        assert_eq!(read_musicxml("<score-partwise>"), Err(Error::Parse));
+        let dots = timewise.replace("<dot/>", &"<dot/>".repeat(40));
+        assert_eq!(read_musicxml(&dots), Err(Error::Unsupported));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::chord::{Chord, ChordQuality};
+use crate::error::{Error, Result};
+use crate::interval::IntervalType;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// The neo-Riemannian operations, each of which maps a major or minor triad onto a triad of
+/// the opposite quality sharing two common tones
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Transformation {
+    // C major <-> C minor
+    Parallel,
+    // C major <-> E minor
+    Leittonwechsel,
+    // C major <-> A minor
+    Relative,
+}
+
+impl Transformation {
+    const ALL: [Transformation; 3] = [
+        Transformation::Parallel,
+        Transformation::Leittonwechsel,
+        Transformation::Relative,
+    ];
+
+    /// Parses a chain of operations such as "PLR", applied from left to right
+    pub fn parse_chain(input: &str) -> Result<Vec<Transformation>> {
+        input
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|c| match c.to_ascii_uppercase() {
+                'P' => Ok(Transformation::Parallel),
+                'L' => Ok(Transformation::Leittonwechsel),
+                'R' => Ok(Transformation::Relative),
+                _ => Err(Error::Parse),
+            })
+            .collect()
+    }
+
+    // Applies the operation to a triad given as a root pitch class and quality
+    fn apply_pitch_class(&self, root: u8, major: bool) -> (u8, bool) {
+        let root = match (self, major) {
+            (Transformation::Parallel, _) => root,
+            (Transformation::Leittonwechsel, true) => root + 4,
+            (Transformation::Leittonwechsel, false) => root + 8,
+            (Transformation::Relative, true) => root + 9,
+            (Transformation::Relative, false) => root + 3,
+        };
+        (root % 12, !major)
+    }
+}
+
+impl fmt::Display for Transformation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Transformation::Parallel => "P",
+            Transformation::Leittonwechsel => "L",
+            Transformation::Relative => "R",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A vertex of the Tonnetz, with fifths along one axis and major thirds along the other
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TonnetzPoint {
+    pub fifths: i8,
+    pub major_thirds: i8,
+}
+
+impl TonnetzPoint {
+    /// Returns the point for a pitch class, with fifths in 0..4 and major thirds in 0..3
+    pub fn from_pitch_class(pitch_class: u8) -> TonnetzPoint {
+        let pitch_class = pitch_class % 12;
+        // 7 * fifths + 4 * major_thirds covers each pitch class once over this range
+        let (fifths, major_thirds): (i8, i8) = (0..4)
+            .flat_map(|fifths| (0..3).map(move |thirds| (fifths, thirds)))
+            .find(|(fifths, thirds)| (7 * fifths + 4 * thirds) % 12 == pitch_class as i8)
+            .unwrap_or_default();
+        TonnetzPoint { fifths, major_thirds }
+    }
+
+    pub fn pitch_class(&self) -> u8 {
+        (7 * self.fifths as i16 + 4 * self.major_thirds as i16).rem_euclid(12) as u8
+    }
+
+    fn offset(&self, fifths: i8, major_thirds: i8) -> TonnetzPoint {
+        TonnetzPoint {
+            fifths: self.fifths + fifths,
+            major_thirds: self.major_thirds + major_thirds,
+        }
+    }
+}
+
+fn triad_pitch_class(chord: &Chord) -> Result<(u8, bool)> {
+    match chord.kind {
+        ChordQuality::Major => Ok((chord.root.pitch.pitch_class(), true)),
+        ChordQuality::Minor => Ok((chord.root.pitch.pitch_class(), false)),
+        _ => Err(Error::Unsupported),
+    }
+}
+
+impl Chord {
+    /// Applies a neo-Riemannian operation, which is only defined for major and minor triads
+    pub fn transform(&self, transformation: Transformation) -> Result<Chord> {
+        let (_, major) = triad_pitch_class(self)?;
+        let root = match (transformation, major) {
+            (Transformation::Parallel, _) => Some(self.root),
+            (Transformation::Leittonwechsel, true) => self.root.checked_add(IntervalType::MajorThird),
+            (Transformation::Leittonwechsel, false) => self.root.checked_sub(IntervalType::MajorThird),
+            (Transformation::Relative, true) => self.root.checked_sub(IntervalType::MinorThird),
+            (Transformation::Relative, false) => self.root.checked_add(IntervalType::MinorThird),
+        }
+        .ok_or(Error::OutofBounds)?;
+        let kind = if major { ChordQuality::Minor } else { ChordQuality::Major };
+        Ok(Chord::new(root, kind))
+    }
+
+    pub fn transform_chain(&self, transformations: &[Transformation]) -> Result<Chord> {
+        transformations
+            .iter()
+            .try_fold(self.clone(), |chord, transformation| chord.transform(*transformation))
+    }
+
+    /// Returns a shortest chain of operations leading from this triad to the other, ignoring
+    /// spelling and octave
+    pub fn transformation_path(&self, other: &Chord) -> Result<Vec<Transformation>> {
+        let start = triad_pitch_class(self)?;
+        let target = triad_pitch_class(other)?;
+        // Breadth first search over the 24 major and minor triads
+        let mut previous: HashMap<(u8, bool), ((u8, bool), Transformation)> = HashMap::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(triad) = queue.pop_front() {
+            if triad == target {
+                break;
+            }
+            for transformation in Transformation::ALL {
+                let next = transformation.apply_pitch_class(triad.0, triad.1);
+                if next != start && !previous.contains_key(&next) {
+                    previous.insert(next, (triad, transformation));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut path = vec![];
+        let mut triad = target;
+        while triad != start {
+            let (prior, transformation) = previous[&triad];
+            path.push(transformation);
+            triad = prior;
+        }
+        path.reverse();
+        Ok(path)
+    }
+
+    /// Returns the vertices of the triad's triangle on the Tonnetz as root, third and fifth
+    pub fn tonnetz(&self) -> Result<[TonnetzPoint; 3]> {
+        let (root, major) = triad_pitch_class(self)?;
+        let root = TonnetzPoint::from_pitch_class(root);
+        let third = if major { root.offset(0, 1) } else { root.offset(1, -1) };
+        Ok([root, third, root.offset(1, 0)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitch::PitchOctave;
+    use std::str::FromStr;
+
+    fn triad(root: &str, kind: ChordQuality) -> Chord {
+        Chord::new(PitchOctave::from_str(root).unwrap(), kind)
+    }
+
+    #[test]
+    fn test_transformations() {
+        let c_major = triad("C3", ChordQuality::Major);
+        assert_eq!(c_major.transform(Transformation::Parallel).unwrap(), triad("C3", ChordQuality::Minor));
+        assert_eq!(c_major.transform(Transformation::Leittonwechsel).unwrap(), triad("E3", ChordQuality::Minor));
+        assert_eq!(c_major.transform(Transformation::Relative).unwrap(), triad("A2", ChordQuality::Minor));
+        // Each operation is an involution
+        for transformation in Transformation::ALL {
+            assert_eq!(c_major.transform(transformation).unwrap().transform(transformation).unwrap(), c_major);
+        }
+        let chain = Transformation::parse_chain("PLR").unwrap();
+        assert_eq!(c_major.transform_chain(&chain).unwrap(), triad("F2", ChordQuality::Minor));
+        assert_eq!(Transformation::parse_chain("PX"), Err(Error::Parse));
+        assert_eq!(triad("C3", ChordQuality::Dom7).transform(Transformation::Parallel), Err(Error::Unsupported));
+    }
+
+    #[test]
+    fn test_path_and_tonnetz() {
+        let c_major = triad("C3", ChordQuality::Major);
+        let path = c_major.transformation_path(&triad("Ab3", ChordQuality::Major)).unwrap();
+        assert_eq!(path.len(), 2);
+        assert_eq!(c_major.transform_chain(&path).unwrap().root.pitch.pitch_class(), 8);
+        assert!(c_major.transformation_path(&c_major).unwrap().is_empty());
+
+        let vertices: Vec<u8> = c_major.tonnetz().unwrap().iter().map(|p| p.pitch_class()).collect();
+        assert_eq!(vertices, vec![0, 4, 7]);
+        let vertices: Vec<u8> = triad("A3", ChordQuality::Minor).tonnetz().unwrap().iter().map(|p| p.pitch_class()).collect();
+        assert_eq!(vertices, vec![9, 0, 4]);
+    }
+}
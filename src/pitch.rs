@@ -1,12 +1,15 @@
 use crate::{error::{Error, Result}, interval::IntervalType};
 use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use strum::EnumString;
 
 use std::{
+    fmt,
     ops::{Add, Sub},
     str::FromStr,
 };
 
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Pitches(pub Vec<PitchOctave>);
 
 // impl Pitches {
@@ -80,23 +83,30 @@ pub enum AccidentalSpelling {
     Sharp = 1,
 }
 
+// Alter values are expressed in quarter tones so that microtonal alterations can be represented
 #[derive(PartialEq, Debug, FromPrimitive, Default, Eq, Copy, Clone)]
 #[repr(i8)]
 pub enum Alter {
-    DoubleFlat = -2,
-    Flat = -1,
+    DoubleFlat = -4,
+    Flat = -2,
+    HalfFlat = -1,
     #[default]
     None = 0,
-    Sharp = 1,
-    DoubleSharp = 2,
+    HalfSharp = 1,
+    Sharp = 2,
+    DoubleSharp = 4,
 }
 
+// Converts the alter to semitones, quarter tone alterations are rounded up like the cents of a
+// scale, so a half flat is the natural and a half sharp the sharp
 impl From<Alter> for i8 {
     fn from(value: Alter) -> Self {
         match value {
             Alter::DoubleFlat => -2,
             Alter::Flat => -1,
+            Alter::HalfFlat => 0,
             Alter::None => 0,
+            Alter::HalfSharp => 1,
             Alter::Sharp => 1,
             Alter::DoubleSharp => 2,
         }
@@ -104,11 +114,15 @@ impl From<Alter> for i8 {
 }
 
 impl Alter {
+    const QUARTER_TONE_CENTS: i16 = 50;
+
     pub fn from_num_string(input: &str) -> Result<Alter> {
         match input {
             "-2" => Ok(Alter::DoubleFlat),
             "-1" => Ok(Alter::Flat),
+            "-0.5" => Ok(Alter::HalfFlat),
             "0" => Ok(Alter::None),
+            "0.5" => Ok(Alter::HalfSharp),
             "1" => Ok(Alter::Sharp),
             "2" => Ok(Alter::DoubleSharp),
             _ => Err(Error::Parse),
@@ -119,11 +133,32 @@ impl Alter {
         match self {
             Alter::DoubleFlat => String::from("-2"),
             Alter::Flat => String::from("-1"),
+            Alter::HalfFlat => String::from("-0.5"),
             Alter::None => String::from("0"),
+            Alter::HalfSharp => String::from("0.5"),
             Alter::Sharp => String::from("1"),
             Alter::DoubleSharp => String::from("2"),
         }
     }
+
+    pub fn from_semitones(semitones: i8) -> Option<Alter> {
+        Alter::from_i8(semitones.checked_mul(2)?)
+    }
+
+    pub fn from_cents(cents: i16) -> Option<Alter> {
+        if cents % Self::QUARTER_TONE_CENTS != 0 {
+            return None;
+        }
+        Alter::from_i16(cents / Self::QUARTER_TONE_CENTS)
+    }
+
+    pub fn cents(&self) -> i16 {
+        *self as i16 * Self::QUARTER_TONE_CENTS
+    }
+
+    pub fn is_microtonal(&self) -> bool {
+        matches!(self, Alter::HalfFlat | Alter::HalfSharp)
+    }
 }
 
 impl FromStr for Alter {
@@ -132,7 +167,9 @@ impl FromStr for Alter {
         match input {
             "bb" => Ok(Alter::DoubleFlat),
             "b" => Ok(Alter::Flat),
+            "d" => Ok(Alter::HalfFlat),
             "" => Ok(Alter::None),
+            "+" => Ok(Alter::HalfSharp),
             "#" => Ok(Alter::Sharp),
             "##" => Ok(Alter::DoubleSharp),
             _ => Err(Error::Parse),
@@ -140,31 +177,23 @@ impl FromStr for Alter {
     }
 }
 
-impl ToString for Alter {
-    fn to_string(&self) -> String {
+impl fmt::Display for Alter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Alter::DoubleFlat => String::from("bb"),
-            Alter::Flat => String::from("b"),
-            Alter::None => String::from(""),
-            Alter::Sharp => String::from("#"),
-            Alter::DoubleSharp => String::from("##"),
+            Alter::DoubleFlat => write!(f, "bb"),
+            Alter::Flat => write!(f, "b"),
+            Alter::HalfFlat => write!(f, "d"),
+            Alter::None => write!(f, ""),
+            Alter::HalfSharp => write!(f, "+"),
+            Alter::Sharp => write!(f, "#"),
+            Alter::DoubleSharp => write!(f, "##"),
         }
     }
 }
 
-impl ToString for Octave {
-    fn to_string(&self) -> String {
-        match self {
-            Octave::Octave0 => "0".to_string(),
-            Octave::Octave1 => "1".to_string(),
-            Octave::Octave2 => "2".to_string(),
-            Octave::Octave3 => "3".to_string(),
-            Octave::Octave4 => "4".to_string(),
-            Octave::Octave5 => "5".to_string(),
-            Octave::Octave6 => "6".to_string(),
-            Octave::Octave7 => "7".to_string(),
-            Octave::Octave8 => "8".to_string(),
-        }
+impl fmt::Display for Octave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", *self as u8)
     }
 }
 
@@ -261,16 +290,16 @@ impl Step {
     }
 }
 
-impl ToString for Step {
-    fn to_string(&self) -> String {
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::C => String::from("C"),
-            Self::D => String::from("D"),
-            Self::E => String::from("E"),
-            Self::F => String::from("F"),
-            Self::G => String::from("G"),
-            Self::A => String::from("A"),
-            Self::B => String::from("B"),
+            Self::C => write!(f, "C"),
+            Self::D => write!(f, "D"),
+            Self::E => write!(f, "E"),
+            Self::F => write!(f, "F"),
+            Self::G => write!(f, "G"),
+            Self::A => write!(f, "A"),
+            Self::B => write!(f, "B"),
         }
     }
 }
@@ -339,7 +368,7 @@ impl FromStr for Pitch {
         let char_vec: Vec<String> = input.chars().map(|inp| inp.to_string()).collect();
 
         if char_vec.len() > 3 {
-            return Err(Error::Parse);
+            Err(Error::Parse)
         } else {
             let step = Step::from_str(char_vec[0].as_str())?;
             let alter_str;
@@ -359,9 +388,20 @@ impl FromStr for Pitch {
     }
 }
 
-impl ToString for Pitch {
-    fn to_string(&self) -> String {
-        [self.step.to_string(), self.alter.to_string()].join("")
+impl Pitch {
+    pub fn new(step: Step, alter: Alter) -> Pitch {
+        Pitch { step, alter }
+    }
+
+    // Pitch class in 12-TET, with C as 0
+    pub fn pitch_class(&self) -> u8 {
+        (i8::from(self.step) + i8::from(self.alter)).rem_euclid(12) as u8
+    }
+}
+
+impl fmt::Display for Pitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.step, self.alter)
     }
 }
 #[derive(Copy, Clone, Default, Debug, Eq, PartialEq)]
@@ -386,7 +426,7 @@ impl PitchOctave {
             AccidentalSpelling::Flat => Alter::Flat,
             AccidentalSpelling::Sharp => Alter::Sharp,
         };
-        let numeric_alter = alter as i8;
+        let numeric_alter = i8::from(alter);
         // Get the note number normalized to middle C octave
         let middle_c_number = semitone - ((numeric_octave - 3) * 12);
         match Step::try_from(middle_c_number) {
@@ -397,7 +437,7 @@ impl PitchOctave {
                         step: diatonic_step,
                         alter: Alter::None,
                     },
-                    octave: octave,
+                    octave,
                 })
             }
             Err(e) => {
@@ -409,9 +449,9 @@ impl PitchOctave {
                             Ok(diatonic_step) => Ok(PitchOctave {
                                 pitch: Pitch {
                                     step: diatonic_step,
-                                    alter: alter,
+                                    alter,
                                 },
-                                octave: octave,
+                                octave,
                             }),
                             Err(_e) => panic!("Error should not be possible here"),
                         }
@@ -424,7 +464,8 @@ impl PitchOctave {
         }
     }
 
-    fn get_semitone_value(&self) -> Result<i8> {
+    // Returns the MIDI note number of the pitch
+    pub fn get_semitone_value(&self) -> Result<i8> {
         let mut pitch_semitones = i8::from(self.pitch.step);
         let alter_semitones = i8::from(self.pitch.alter);
         let numeric_octave = self.octave as i8;
@@ -462,108 +503,93 @@ impl PitchOctave {
         }
     }
     pub fn checked_sub(&self, v: IntervalType) -> Option<Self> {
-        let mut target_semitone = self.get_semitone_value().ok()?;
-        target_semitone -= v.get_semitone_value() as i8;
+        let target_semitone = self.get_semitone_value().ok()? - v.get_semitone_value() as i8;
 
-        let original_octave = self.octave as u8;
+        let mut octave = self.octave as u8;
         let new_diatonic_step = self.pitch.step - v.get_diatonic_value();
-        let new_diatonic_pitch;
         if new_diatonic_step >= self.pitch.step
             && v.get_diatonic_value() != IntervalType::UNISON_DIATONIC_INC
         {
             // The new step is an octave lower than the previous
-            new_diatonic_pitch = PitchOctave {
-                pitch: Pitch {
-                    step: new_diatonic_step,
-                    alter: Alter::None,
-                },
-                octave: Octave::from(original_octave - 1),
-            };
-        } else {
-            new_diatonic_pitch = PitchOctave {
-                pitch: Pitch {
-                    step: new_diatonic_step,
-                    alter: Alter::None,
-                },
-                octave: self.octave,
-            };
-        }
-        let diatonic_semitone = new_diatonic_pitch.get_semitone_value().ok()?;
-
-        // Calculate difference between diatonic target and actual target and use difference to infer
-        // accidentals
-        // println!("ds is {diatonic_semitone} ts is {target_semitone}");
-        let difference = diatonic_semitone - target_semitone;
-        // println!("Difference is {}", difference);
-        let mut pabs = if difference == -1 {
-            PitchOctave::new_from_semitone(target_semitone, AccidentalSpelling::Sharp).ok()?
-        } else if difference == 1 || difference == 0{
-            PitchOctave::new_from_semitone(target_semitone, AccidentalSpelling::Flat).ok()?
-        } else {
-            panic!("Incomplete tbd")
-        };
-        if pabs.pitch.alter == Alter::None {
-            // re-spell pitches that did not contain accidentals to contain accidentals based on difference
-            if difference == 1 {
-                pabs.respell_pitch(Alter::Flat);
-            } else if difference == -1 {
-                pabs.respell_pitch(Alter::Sharp);
-            }
+            octave = octave.checked_sub(1)?;
         }
-        Some(pabs)
+        Self::spell_semitone(target_semitone, new_diatonic_step, octave)
     }
     pub fn checked_add(&self, v: IntervalType) -> Option<Self> {
-        let mut target_semitone = self.get_semitone_value().ok()?;
-        target_semitone += v.get_semitone_value() as i8;
+        let target_semitone = self.get_semitone_value().ok()? + v.get_semitone_value() as i8;
 
-        let original_octave = self.octave as u8;
+        let mut octave = self.octave as u8;
         let new_diatonic_step = self.pitch.step + v.get_diatonic_value();
-        let new_diatonic_pitch;
         if new_diatonic_step <= self.pitch.step
             && v.get_diatonic_value() != IntervalType::UNISON_DIATONIC_INC
         {
             // The new step is an octave higher than the previous
-            new_diatonic_pitch = PitchOctave {
-                pitch: Pitch {
-                    step: new_diatonic_step,
-                    alter: Alter::None,
-                },
-                octave: Octave::from(original_octave + 1),
-            };
-        } else {
-            new_diatonic_pitch = PitchOctave {
-                pitch: Pitch {
-                    step: new_diatonic_step,
-                    alter: Alter::None,
-                },
-                octave: self.octave,
-            };
+            octave += 1;
         }
-        let diatonic_semitone = new_diatonic_pitch.get_semitone_value().ok()?;
-
-        // Calculate difference between diatonic target and actual target and use difference to infer
-        // accidentals
-        // println!("ds is {diatonic_semitone} ts is {target_semitone}");
-        let difference = diatonic_semitone - target_semitone;
-        // println!("Difference is {}", difference);
-        let mut pabs = if difference == -1 {
-            PitchOctave::new_from_semitone(target_semitone, AccidentalSpelling::Sharp).ok()?
-        } else if difference == 1 || difference == 0{
-            PitchOctave::new_from_semitone(target_semitone, AccidentalSpelling::Flat).ok()?
-        } else {
-            panic!("Incomplete tbd")
+        Self::spell_semitone(target_semitone, new_diatonic_step, octave)
+    }
+
+    // Spells the target semitone on the given diatonic step, inferring the accidental from the
+    // difference between the natural step and the target. Returns None if the difference cannot
+    // be expressed with at most a double accidental, or the pitch is out of range.
+    fn spell_semitone(target_semitone: i8, step: Step, octave: u8) -> Option<Self> {
+        if octave > Octave::Octave8 as u8 {
+            return None;
+        }
+        let diatonic_semitone = i8::from(step) + (octave as i8 - 3) * 12;
+        let alter = Alter::from_semitones(target_semitone - diatonic_semitone)?;
+        let spelled = PitchOctave {
+            pitch: Pitch { step, alter },
+            octave: Octave::from(octave),
         };
-        if pabs.pitch.alter == Alter::None {
-            // re-spell pitches that did not contain accidentals to contain accidentals based on difference
-            if difference == 1 {
-                pabs.respell_pitch(Alter::Flat);
-            } else if difference == -1 {
-                pabs.respell_pitch(Alter::Sharp);
-            }
+        spelled.get_semitone_value().ok()?;
+        Some(spelled)
+    }
+
+    pub fn get_cents_value(&self) -> Result<i16> {
+        let natural = PitchOctave::new(Pitch { step: self.pitch.step, alter: Alter::None }, self.octave);
+        Ok(natural.get_semitone_value()? as i16 * 100 + self.pitch.alter.cents())
+    }
+
+    // Adds an interval given in cents, spelling the result on the step the given number of
+    // diatonic steps above this pitch. Used for scales that fall outside of 12-TET.
+    pub fn checked_add_cents(&self, diatonic: u8, cents: u16) -> Option<Self> {
+        let target_cents = self.get_cents_value().ok()? + cents as i16;
+
+        let mut octave = self.octave as u8 + diatonic / 7;
+        let new_diatonic_step = self.pitch.step + diatonic;
+        if new_diatonic_step < self.pitch.step {
+            // The new step is an octave higher than the previous
+            octave += 1;
         }
-        Some(pabs)
+        Self::spell_cents(target_cents, new_diatonic_step, octave)
     }
 
+    pub fn checked_sub_cents(&self, diatonic: u8, cents: u16) -> Option<Self> {
+        let target_cents = self.get_cents_value().ok()? - cents as i16;
+
+        let mut octave = (self.octave as u8).checked_sub(diatonic / 7)?;
+        let new_diatonic_step = self.pitch.step - diatonic;
+        if new_diatonic_step > self.pitch.step {
+            // The new step is an octave lower than the previous
+            octave = octave.checked_sub(1)?;
+        }
+        Self::spell_cents(target_cents, new_diatonic_step, octave)
+    }
+
+    fn spell_cents(target_cents: i16, step: Step, octave: u8) -> Option<Self> {
+        if octave > Octave::Octave8 as u8 {
+            return None;
+        }
+        let diatonic_cents = (i8::from(step) as i16 + (octave as i16 - 3) * 12) * 100;
+        let alter = Alter::from_cents(target_cents - diatonic_cents)?;
+        let spelled = PitchOctave {
+            pitch: Pitch { step, alter },
+            octave: Octave::from(octave),
+        };
+        spelled.get_semitone_value().ok()?;
+        Some(spelled)
+    }
 }
 // impl FromStr for PitchAbsolute {
 //     type Err = Error;
@@ -580,7 +606,7 @@ impl FromStr for PitchOctave {
         let char_vec: Vec<String> = input.chars().map(|inp| inp.to_string()).collect();
 
         if char_vec.len() > 4 {
-            return Err(Error::Parse);
+            Err(Error::Parse)
         } else {
             let octave_str;
             let pitch;
@@ -602,14 +628,9 @@ impl FromStr for PitchOctave {
     }
 }
 
-impl ToString for PitchOctave {
-    fn to_string(&self) -> String {
-        [
-            self.pitch.step.to_string(),
-            self.pitch.alter.to_string(),
-            self.octave.to_string(),
-        ]
-        .join("")
+impl fmt::Display for PitchOctave {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.pitch.step, self.pitch.alter, self.octave)
     }
 }
 
@@ -663,7 +684,9 @@ impl PitchOctave {
 #[cfg(test)]
 mod tests {
 
+    use crate::interval::IntervalType;
     use crate::pitch::{AccidentalSpelling, PitchOctave};
+    use std::str::FromStr;
     #[test]
     fn test_semitone_convert() {
         let pitch = PitchOctave::new_from_semitone(49, AccidentalSpelling::Flat);
@@ -671,4 +694,23 @@ mod tests {
 
         assert_eq!(true, true);
     }
+
+    #[test]
+    fn test_double_accidental_spelling() {
+        let db = PitchOctave::from_str("Db4").unwrap();
+        assert_eq!(db.checked_add(IntervalType::DiminishedThird).unwrap().to_string(), "Fbb4");
+        let c = PitchOctave::from_str("C4").unwrap();
+        assert_eq!(c.checked_sub(IntervalType::AugmentedSecond).unwrap().to_string(), "Bbb3");
+        let low = PitchOctave::from_str("C0").unwrap();
+        assert_eq!(low.checked_sub(IntervalType::MinorSecond), None);
+    }
+
+    #[test]
+    fn test_augmented_sixth() {
+        assert_eq!(IntervalType::AugmentedSixth.get_semitone_value(), 10);
+        let ab = PitchOctave::from_str("Ab3").unwrap();
+        assert_eq!(ab.checked_add(IntervalType::AugmentedSixth).unwrap().to_string(), "F#4");
+        let f = PitchOctave::from_str("F#4").unwrap();
+        assert_eq!(f.checked_sub(IntervalType::AugmentedSixth).unwrap().to_string(), "Ab3");
+    }
 }
@@ -0,0 +1,445 @@
+use crate::chord::ChordQuality;
+use crate::interval::NumericIntervalsSlice;
+use crate::pitch::Pitches;
+use crate::scale::ScaleType;
+use std::fmt;
+use strum::IntoEnumIterator;
+
+const PITCH_CLASSES: u8 = 12;
+const PITCH_CLASS_MASK: u16 = 0xFFF;
+
+/// An unordered set of pitch classes, stored as a 12 bit set with bit 0 as C
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct PitchClassSet(u16);
+
+impl PitchClassSet {
+    pub fn new(bits: u16) -> Self {
+        PitchClassSet(bits & PITCH_CLASS_MASK)
+    }
+
+    pub fn from_pitch_classes(pitch_classes: &[u8]) -> Self {
+        pitch_classes
+            .iter()
+            .fold(PitchClassSet::default(), |set, pc| set.insert(*pc))
+    }
+
+    /// Returns the set formed by a tonic or root and the numeric intervals above it
+    pub fn from_intervals(intervals: &NumericIntervalsSlice) -> Self {
+        PitchClassSet::from_pitch_classes(intervals).insert(0)
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn insert(&self, pitch_class: u8) -> Self {
+        PitchClassSet(self.0 | 1 << (pitch_class % PITCH_CLASSES))
+    }
+
+    pub fn contains(&self, pitch_class: u8) -> bool {
+        self.0 & 1 << (pitch_class % PITCH_CLASSES) != 0
+    }
+
+    pub fn is_subset_of(&self, other: &PitchClassSet) -> bool {
+        self.0 & other.0 == self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns the pitch classes in ascending order
+    pub fn pitch_classes(&self) -> Vec<u8> {
+        (0..PITCH_CLASSES).filter(|pc| self.contains(*pc)).collect()
+    }
+
+    /// Tn
+    pub fn transpose(&self, semitones: u8) -> Self {
+        let n = semitones % PITCH_CLASSES;
+        PitchClassSet::new(self.0 << n | self.0 >> (PITCH_CLASSES - n))
+    }
+
+    /// T0I, inversion around C
+    pub fn invert(&self) -> Self {
+        PitchClassSet::from_pitch_classes(
+            &self
+                .pitch_classes()
+                .iter()
+                .map(|pc| (PITCH_CLASSES - pc) % PITCH_CLASSES)
+                .collect::<Vec<u8>>(),
+        )
+    }
+
+    /// TnI, inversion followed by transposition
+    pub fn transpose_invert(&self, semitones: u8) -> Self {
+        self.invert().transpose(semitones)
+    }
+
+    pub fn complement(&self) -> Self {
+        PitchClassSet::new(!self.0)
+    }
+
+    /// Returns n such that Tn maps this set onto the other
+    pub fn transposition_to(&self, other: &PitchClassSet) -> Option<u8> {
+        (0..PITCH_CLASSES).find(|n| self.transpose(*n) == *other)
+    }
+
+    /// Returns n such that TnI maps this set onto the other
+    pub fn inversion_to(&self, other: &PitchClassSet) -> Option<u8> {
+        (0..PITCH_CLASSES).find(|n| self.transpose_invert(*n) == *other)
+    }
+
+    /// Sets are members of the same set class when related by Tn or TnI
+    pub fn is_equivalent(&self, other: &PitchClassSet) -> bool {
+        self.transposition_to(other).is_some() || self.inversion_to(other).is_some()
+    }
+
+    // Orders rotations by the interval from the first to the last pitch class, then from the
+    // first to the second to last and so on, which is Rahn's packing criterion.
+    fn packing_key(ordering: &[u8]) -> Vec<u8> {
+        let first = ordering.first().copied().unwrap_or_default();
+        ordering
+            .iter()
+            .skip(1)
+            .rev()
+            .map(|pc| (pc + PITCH_CLASSES - first) % PITCH_CLASSES)
+            .collect()
+    }
+
+    /// Returns the most compact ordering of the set
+    pub fn normal_form(&self) -> Vec<u8> {
+        let pitch_classes = self.pitch_classes();
+        (0..pitch_classes.len())
+            .map(|start| {
+                let mut rotation = pitch_classes.clone();
+                rotation.rotate_left(start);
+                rotation
+            })
+            .min_by_key(|rotation| Self::packing_key(rotation))
+            .unwrap_or_default()
+    }
+
+    /// Returns the normal form of the set or its inversion, whichever is more compact,
+    /// transposed to begin on 0
+    pub fn prime_form(&self) -> Vec<u8> {
+        let zero_based = |normal_form: Vec<u8>| -> Vec<u8> {
+            let first = normal_form.first().copied().unwrap_or_default();
+            normal_form
+                .iter()
+                .map(|pc| (pc + PITCH_CLASSES - first) % PITCH_CLASSES)
+                .collect()
+        };
+        let prime = zero_based(self.normal_form());
+        let inverted = zero_based(self.invert().normal_form());
+        if Self::packing_key(&inverted) < Self::packing_key(&prime) {
+            inverted
+        } else {
+            prime
+        }
+    }
+
+    pub fn interval_class_vector(&self) -> [u8; 6] {
+        let pitch_classes = self.pitch_classes();
+        let mut vector = [0; 6];
+        for (i, a) in pitch_classes.iter().enumerate() {
+            for b in &pitch_classes[i + 1..] {
+                let interval = b - a;
+                let interval_class = interval.min(PITCH_CLASSES - interval);
+                vector[interval_class as usize - 1] += 1;
+            }
+        }
+        vector
+    }
+
+    /// Returns the Forte name of the set class, such as "4-Z15"
+    pub fn forte_number(&self) -> Option<String> {
+        if self.len() > 6 {
+            // Larger set classes are named after their complements
+            let name = self.complement().forte_number()?;
+            let (_, ordinal) = name.split_once('-')?;
+            return Some(format!("{}-{}", self.len(), ordinal));
+        }
+        let prime = self.prime_form();
+        FORTE_SET_CLASSES
+            .iter()
+            .find(|(_, prime_form)| *prime_form == prime.as_slice())
+            .map(|(name, _)| name.to_string())
+    }
+
+    /// Sets are Z-related when they share an interval class vector but not a set class
+    pub fn is_z_related(&self, other: &PitchClassSet) -> bool {
+        self.interval_class_vector() == other.interval_class_vector() && !self.is_equivalent(other)
+    }
+
+    /// Returns the prime form of the Z-related set class, if there is one
+    pub fn z_partner(&self) -> Option<PitchClassSet> {
+        (0..=PITCH_CLASS_MASK)
+            .map(PitchClassSet::new)
+            .find(|candidate| candidate.len() == self.len() && self.is_z_related(candidate))
+            .map(|candidate| PitchClassSet::from_pitch_classes(&candidate.prime_form()))
+    }
+
+    /// Returns the chord roots and qualities whose pitch classes are exactly this set
+    pub fn matching_chords(&self) -> Vec<(u8, ChordQuality)> {
+        let mut chords = vec![];
+        for root in self.pitch_classes() {
+            for quality in ChordQuality::iter() {
+                if quality.pitch_class_set().transpose(root) == *self {
+                    chords.push((root, quality));
+                }
+            }
+        }
+        chords
+    }
+
+    /// Returns the tonics and 12-TET scales which contain every pitch class of this set
+    pub fn containing_scales(&self) -> Vec<(u8, ScaleType)> {
+        let mut scales = vec![];
+        for kind in ScaleType::iter().filter(|kind| kind.scale_cents().is_none()) {
+            let scale = kind.pitch_class_set();
+            for tonic in 0..PITCH_CLASSES {
+                if self.is_subset_of(&scale.transpose(tonic)) {
+                    scales.push((tonic, kind));
+                }
+            }
+        }
+        scales
+    }
+}
+
+impl From<&Pitches> for PitchClassSet {
+    fn from(pitches: &Pitches) -> Self {
+        pitches
+            .0
+            .iter()
+            .fold(PitchClassSet::default(), |set, pitch| set.insert(pitch.pitch.pitch_class()))
+    }
+}
+
+impl fmt::Display for PitchClassSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pitch_classes: Vec<String> = self.pitch_classes().iter().map(|pc| pc.to_string()).collect();
+        write!(f, "{{{}}}", pitch_classes.join(", "))
+    }
+}
+
+impl ChordQuality {
+    /// Returns the pitch classes of the chord with its root on C
+    pub fn pitch_class_set(&self) -> PitchClassSet {
+        PitchClassSet::from_intervals(self.root_chord_interval())
+    }
+}
+
+impl ScaleType {
+    /// Returns the pitch classes of the scale with its tonic on C
+    pub fn pitch_class_set(&self) -> PitchClassSet {
+        PitchClassSet::from_intervals(self.scale_interval())
+    }
+}
+
+// Prime forms follow Rahn's ordering. Set classes of seven or more pitch classes are named
+// after their complements.
+const FORTE_SET_CLASSES: [(&str, &[u8]); 137] = [
+    ("0-1", &[]),
+    ("1-1", &[0]),
+    ("2-1", &[0, 1]),
+    ("2-2", &[0, 2]),
+    ("2-3", &[0, 3]),
+    ("2-4", &[0, 4]),
+    ("2-5", &[0, 5]),
+    ("2-6", &[0, 6]),
+    ("3-1", &[0, 1, 2]),
+    ("3-2", &[0, 1, 3]),
+    ("3-3", &[0, 1, 4]),
+    ("3-4", &[0, 1, 5]),
+    ("3-5", &[0, 1, 6]),
+    ("3-6", &[0, 2, 4]),
+    ("3-7", &[0, 2, 5]),
+    ("3-8", &[0, 2, 6]),
+    ("3-9", &[0, 2, 7]),
+    ("3-10", &[0, 3, 6]),
+    ("3-11", &[0, 3, 7]),
+    ("3-12", &[0, 4, 8]),
+    ("4-1", &[0, 1, 2, 3]),
+    ("4-2", &[0, 1, 2, 4]),
+    ("4-3", &[0, 1, 3, 4]),
+    ("4-4", &[0, 1, 2, 5]),
+    ("4-5", &[0, 1, 2, 6]),
+    ("4-6", &[0, 1, 2, 7]),
+    ("4-7", &[0, 1, 4, 5]),
+    ("4-8", &[0, 1, 5, 6]),
+    ("4-9", &[0, 1, 6, 7]),
+    ("4-10", &[0, 2, 3, 5]),
+    ("4-11", &[0, 1, 3, 5]),
+    ("4-12", &[0, 2, 3, 6]),
+    ("4-13", &[0, 1, 3, 6]),
+    ("4-14", &[0, 2, 3, 7]),
+    ("4-Z15", &[0, 1, 4, 6]),
+    ("4-16", &[0, 1, 5, 7]),
+    ("4-17", &[0, 3, 4, 7]),
+    ("4-18", &[0, 1, 4, 7]),
+    ("4-19", &[0, 1, 4, 8]),
+    ("4-20", &[0, 1, 5, 8]),
+    ("4-21", &[0, 2, 4, 6]),
+    ("4-22", &[0, 2, 4, 7]),
+    ("4-23", &[0, 2, 5, 7]),
+    ("4-24", &[0, 2, 4, 8]),
+    ("4-25", &[0, 2, 6, 8]),
+    ("4-26", &[0, 3, 5, 8]),
+    ("4-27", &[0, 2, 5, 8]),
+    ("4-28", &[0, 3, 6, 9]),
+    ("4-Z29", &[0, 1, 3, 7]),
+    ("5-1", &[0, 1, 2, 3, 4]),
+    ("5-2", &[0, 1, 2, 3, 5]),
+    ("5-3", &[0, 1, 2, 4, 5]),
+    ("5-4", &[0, 1, 2, 3, 6]),
+    ("5-5", &[0, 1, 2, 3, 7]),
+    ("5-6", &[0, 1, 2, 5, 6]),
+    ("5-7", &[0, 1, 2, 6, 7]),
+    ("5-8", &[0, 2, 3, 4, 6]),
+    ("5-9", &[0, 1, 2, 4, 6]),
+    ("5-10", &[0, 1, 3, 4, 6]),
+    ("5-11", &[0, 2, 3, 4, 7]),
+    ("5-Z12", &[0, 1, 3, 5, 6]),
+    ("5-13", &[0, 1, 2, 4, 8]),
+    ("5-14", &[0, 1, 2, 5, 7]),
+    ("5-15", &[0, 1, 2, 6, 8]),
+    ("5-16", &[0, 1, 3, 4, 7]),
+    ("5-Z17", &[0, 1, 3, 4, 8]),
+    ("5-Z18", &[0, 1, 4, 5, 7]),
+    ("5-19", &[0, 1, 3, 6, 7]),
+    ("5-20", &[0, 1, 5, 6, 8]),
+    ("5-21", &[0, 1, 4, 5, 8]),
+    ("5-22", &[0, 1, 4, 7, 8]),
+    ("5-23", &[0, 2, 3, 5, 7]),
+    ("5-24", &[0, 1, 3, 5, 7]),
+    ("5-25", &[0, 2, 3, 5, 8]),
+    ("5-26", &[0, 2, 4, 5, 8]),
+    ("5-27", &[0, 1, 3, 5, 8]),
+    ("5-28", &[0, 2, 3, 6, 8]),
+    ("5-29", &[0, 1, 3, 6, 8]),
+    ("5-30", &[0, 1, 4, 6, 8]),
+    ("5-31", &[0, 1, 3, 6, 9]),
+    ("5-32", &[0, 1, 4, 6, 9]),
+    ("5-33", &[0, 2, 4, 6, 8]),
+    ("5-34", &[0, 2, 4, 6, 9]),
+    ("5-35", &[0, 2, 4, 7, 9]),
+    ("5-Z36", &[0, 1, 2, 4, 7]),
+    ("5-Z37", &[0, 3, 4, 5, 8]),
+    ("5-Z38", &[0, 1, 2, 5, 8]),
+    ("6-1", &[0, 1, 2, 3, 4, 5]),
+    ("6-2", &[0, 1, 2, 3, 4, 6]),
+    ("6-Z3", &[0, 1, 2, 3, 5, 6]),
+    ("6-Z4", &[0, 1, 2, 4, 5, 6]),
+    ("6-5", &[0, 1, 2, 3, 6, 7]),
+    ("6-Z6", &[0, 1, 2, 5, 6, 7]),
+    ("6-7", &[0, 1, 2, 6, 7, 8]),
+    ("6-8", &[0, 2, 3, 4, 5, 7]),
+    ("6-9", &[0, 1, 2, 3, 5, 7]),
+    ("6-Z10", &[0, 1, 3, 4, 5, 7]),
+    ("6-Z11", &[0, 1, 2, 4, 5, 7]),
+    ("6-Z12", &[0, 1, 2, 4, 6, 7]),
+    ("6-Z13", &[0, 1, 3, 4, 6, 7]),
+    ("6-14", &[0, 1, 3, 4, 5, 8]),
+    ("6-15", &[0, 1, 2, 4, 5, 8]),
+    ("6-16", &[0, 1, 4, 5, 6, 8]),
+    ("6-Z17", &[0, 1, 2, 4, 7, 8]),
+    ("6-18", &[0, 1, 2, 5, 7, 8]),
+    ("6-Z19", &[0, 1, 3, 4, 7, 8]),
+    ("6-20", &[0, 1, 4, 5, 8, 9]),
+    ("6-21", &[0, 2, 3, 4, 6, 8]),
+    ("6-22", &[0, 1, 2, 4, 6, 8]),
+    ("6-Z23", &[0, 2, 3, 5, 6, 8]),
+    ("6-Z24", &[0, 1, 3, 4, 6, 8]),
+    ("6-Z25", &[0, 1, 3, 5, 6, 8]),
+    ("6-Z26", &[0, 1, 3, 5, 7, 8]),
+    ("6-27", &[0, 1, 3, 4, 6, 9]),
+    ("6-Z28", &[0, 1, 3, 5, 6, 9]),
+    ("6-Z29", &[0, 2, 3, 6, 7, 9]),
+    ("6-30", &[0, 1, 3, 6, 7, 9]),
+    ("6-31", &[0, 1, 4, 5, 7, 9]),
+    ("6-32", &[0, 2, 4, 5, 7, 9]),
+    ("6-33", &[0, 2, 3, 5, 7, 9]),
+    ("6-34", &[0, 1, 3, 5, 7, 9]),
+    ("6-35", &[0, 2, 4, 6, 8, 10]),
+    ("6-Z36", &[0, 1, 2, 3, 4, 7]),
+    ("6-Z37", &[0, 1, 2, 3, 4, 8]),
+    ("6-Z38", &[0, 1, 2, 3, 7, 8]),
+    ("6-Z39", &[0, 2, 3, 4, 5, 8]),
+    ("6-Z40", &[0, 1, 2, 3, 5, 8]),
+    ("6-Z41", &[0, 1, 2, 3, 6, 8]),
+    ("6-Z42", &[0, 1, 2, 3, 6, 9]),
+    ("6-Z43", &[0, 1, 2, 5, 6, 8]),
+    ("6-Z44", &[0, 1, 2, 5, 6, 9]),
+    ("6-Z45", &[0, 2, 3, 4, 6, 9]),
+    ("6-Z46", &[0, 1, 2, 4, 6, 9]),
+    ("6-Z47", &[0, 1, 2, 4, 7, 9]),
+    ("6-Z48", &[0, 1, 2, 5, 7, 9]),
+    ("6-Z49", &[0, 1, 3, 4, 7, 9]),
+    ("6-Z50", &[0, 1, 4, 6, 7, 9]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::Notes;
+    use crate::pitch::PitchOctave;
+    use crate::scale::Scale;
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_forte_table_is_complete() {
+        // Every entry is its own prime form, and every set class is named exactly once
+        let mut names = HashSet::new();
+        for bits in 0..=PITCH_CLASS_MASK {
+            let set = PitchClassSet::new(bits);
+            names.insert(set.forte_number().unwrap());
+        }
+        assert_eq!(names.len(), 224);
+        for (name, prime_form) in FORTE_SET_CLASSES.iter() {
+            assert_eq!(PitchClassSet::from_pitch_classes(prime_form).prime_form(), *prime_form, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_set_class_analysis() {
+        let diatonic = ScaleType::Ionian.pitch_class_set();
+        assert_eq!(diatonic.prime_form(), vec![0, 1, 3, 5, 6, 8, 10]);
+        assert_eq!(diatonic.forte_number().unwrap(), "7-35");
+        assert_eq!(diatonic.complement().forte_number().unwrap(), "5-35");
+        assert_eq!(diatonic.interval_class_vector(), [2, 5, 4, 3, 6, 1]);
+
+        let major = PitchClassSet::from_pitch_classes(&[0, 4, 7]);
+        let minor = PitchClassSet::from_pitch_classes(&[9, 0, 4]);
+        assert_eq!(major.normal_form(), vec![0, 4, 7]);
+        assert_eq!(major.forte_number().unwrap(), "3-11");
+        assert_eq!(major.transposition_to(&minor), None);
+        assert_eq!(major.inversion_to(&minor), Some(4));
+
+        let all_interval = PitchClassSet::from_pitch_classes(&[0, 1, 4, 6]);
+        assert!(all_interval.is_z_related(&PitchClassSet::from_pitch_classes(&[0, 1, 3, 7])));
+        assert_eq!(all_interval.z_partner().unwrap().forte_number().unwrap(), "4-Z29");
+    }
+
+    #[test]
+    fn test_chord_lookup() {
+        let set = PitchClassSet::from_pitch_classes(&[9, 0, 4, 7]);
+        let chords = set.matching_chords();
+        assert!(chords.contains(&(9, ChordQuality::Minor7)));
+        assert!(set.containing_scales().contains(&(0, ScaleType::Ionian)));
+    }
+
+    #[test]
+    fn test_cents_scale_pitch_classes() {
+        // The 12-TET approximation of a scale in cents matches the pitch classes of its notes
+        for kind in ScaleType::iter().filter(|kind| kind.scale_cents().is_some()) {
+            let notes = Scale::new(PitchOctave::from_str("C4").unwrap(), kind).notes().unwrap();
+            assert_eq!(PitchClassSet::from(&notes), kind.pitch_class_set(), "{:?}", kind);
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use crate::chord::Chord;
+use crate::error::{Error, Result};
+use crate::interval::IntervalType;
+use crate::key::Key;
+use crate::notes::Notes;
+use crate::pitch::{Octave, Pitches};
+use crate::roman_numeral::RomanNumeral;
+use num::rational::Ratio;
+use num::Zero;
+use std::fmt;
+use std::str::FromStr;
+
+const BEATS_PER_BAR: u32 = 4;
+const MIDI_TICKS_PER_BEAT: u32 = 480;
+const MIDI_VELOCITY: u8 = 80;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgressionChord {
+    pub chord: Chord,
+    // Duration in quarter note beats
+    pub beats: Ratio<u32>,
+}
+
+/// A sequence of chords, each held for a number of beats
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Progression {
+    pub chords: Vec<ProgressionChord>,
+}
+
+impl Progression {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, chord: Chord, beats: Ratio<u32>) {
+        self.chords.push(ProgressionChord { chord, beats });
+    }
+
+    pub fn len(&self) -> usize {
+        self.chords.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chords.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ProgressionChord> {
+        self.chords.iter()
+    }
+
+    pub fn total_beats(&self) -> Ratio<u32> {
+        self.chords.iter().map(|entry| entry.beats).sum()
+    }
+
+    /// Returns the notes of each chord in order
+    pub fn pitches(&self) -> Result<Vec<Pitches>> {
+        self.chords.iter().map(|entry| entry.chord.notes()).collect()
+    }
+
+    /// Parses lead sheet text such as "| Cmaj7 | Dm7 G7 | C |". Chords within a bar share it
+    /// evenly unless given an explicit duration in beats, as in "Dm7:3 G7:1", and explicit
+    /// durations may not overrun their bar. Without bar lines each chord lasts a bar unless given
+    /// a duration.
+    pub fn from_symbols(input: &str, beats_per_bar: u32) -> Result<Self> {
+        parse_bars(input, beats_per_bar, |symbol| Chord::from_symbol(symbol, Octave::default()))
+    }
+
+    /// Parses Roman numerals such as "| ii7 | V7 | Imaj7 |" and realizes them in the key
+    pub fn from_roman_numerals(input: &str, key: &Key, beats_per_bar: u32) -> Result<Self> {
+        parse_bars(input, beats_per_bar, |numeral| RomanNumeral::from_str(numeral)?.to_chord(key))
+    }
+
+    /// Returns the Roman numeral of each chord in the key
+    pub fn roman_numerals(&self, key: &Key) -> Result<Vec<RomanNumeral>> {
+        self.chords.iter().map(|entry| entry.chord.roman_numeral(key)).collect()
+    }
+
+    /// Transposes every chord up by the interval
+    pub fn checked_add(&self, v: IntervalType) -> Option<Self> {
+        self.map_chords(|chord| chord.checked_add(v))
+    }
+
+    /// Transposes every chord down by the interval
+    pub fn checked_sub(&self, v: IntervalType) -> Option<Self> {
+        self.map_chords(|chord| chord.checked_sub(v))
+    }
+
+    /// Transposes the progression from one key to another, moving up to the new tonic
+    pub fn transpose_to_key(&self, from: &Key, to: &Key) -> Result<Self> {
+        let steps = (to.tonic.pitch.step as u8 + 7 - from.tonic.pitch.step as u8) % 7;
+        let semitones = (to.tonic.pitch.pitch_class() + 12 - from.tonic.pitch.pitch_class()) % 12;
+        let interval = IntervalType::from_diatonic_semitones(steps, semitones).ok_or(Error::Unsupported)?;
+        self.checked_add(interval).ok_or(Error::OutofBounds)
+    }
+
+    fn map_chords<F>(&self, transform: F) -> Option<Self>
+    where
+        F: Fn(&Chord) -> Option<Chord>,
+    {
+        let chords = self
+            .chords
+            .iter()
+            .map(|entry| {
+                Some(ProgressionChord {
+                    chord: transform(&entry.chord)?,
+                    beats: entry.beats,
+                })
+            })
+            .collect::<Option<Vec<ProgressionChord>>>()?;
+        Some(Progression { chords })
+    }
+
+    /// Returns a single track standard MIDI file of the progression at the given tempo
+    pub fn to_midi(&self, beats_per_minute: u32) -> Result<Vec<u8>> {
+        if beats_per_minute == 0 {
+            return Err(Error::OutofBounds);
+        }
+        let mut track = vec![];
+        // Tempo, in microseconds per quarter note
+        let tempo = 60_000_000 / beats_per_minute;
+        track.extend_from_slice(&[0x00, 0xFF, 0x51, 0x03]);
+        track.extend_from_slice(&tempo.to_be_bytes()[1..]);
+
+        for entry in &self.chords {
+            let notes: Vec<u8> = entry
+                .chord
+                .notes()?
+                .0
+                .iter()
+                .map(|pitch| pitch.get_semitone_value().map(|value| value as u8))
+                .collect::<Result<Vec<u8>>>()?;
+            for note in &notes {
+                write_variable_length(&mut track, 0);
+                track.extend_from_slice(&[0x90, *note, MIDI_VELOCITY]);
+            }
+            let ticks = (entry.beats * MIDI_TICKS_PER_BEAT).round().to_integer();
+            for (i, note) in notes.iter().enumerate() {
+                write_variable_length(&mut track, if i == 0 { ticks } else { 0 });
+                track.extend_from_slice(&[0x80, *note, 0]);
+            }
+        }
+        // End of track
+        track.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+
+        let mut midi = vec![];
+        midi.extend_from_slice(b"MThd");
+        midi.extend_from_slice(&6u32.to_be_bytes());
+        // Format 0, one track
+        midi.extend_from_slice(&0u16.to_be_bytes());
+        midi.extend_from_slice(&1u16.to_be_bytes());
+        midi.extend_from_slice(&(MIDI_TICKS_PER_BEAT as u16).to_be_bytes());
+        midi.extend_from_slice(b"MTrk");
+        midi.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        midi.extend(track);
+        Ok(midi)
+    }
+}
+
+fn write_variable_length(bytes: &mut Vec<u8>, value: u32) {
+    let mut groups = vec![(value & 0x7F) as u8];
+    let mut value = value >> 7;
+    while value > 0 {
+        groups.push((value & 0x7F) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.extend(groups.iter().rev());
+}
+
+fn parse_bars<F>(input: &str, beats_per_bar: u32, parse_chord: F) -> Result<Progression>
+where
+    F: Fn(&str) -> Result<Chord>,
+{
+    let barred = input.contains('|');
+    let bars: Vec<Vec<&str>> = if barred {
+        input
+            .split('|')
+            .map(|bar| bar.split_whitespace().collect::<Vec<&str>>())
+            .filter(|bar| !bar.is_empty())
+            .collect()
+    } else {
+        input.split_whitespace().map(|token| vec![token]).collect()
+    };
+
+    let mut progression = Progression::new();
+    for bar in bars {
+        let mut chords = vec![];
+        for token in bar {
+            let (chord, beats) = match token.split_once(':') {
+                Some((chord, beats)) => (chord, Some(Ratio::from_str(beats).map_err(|_| Error::Parse)?)),
+                None => (token, None),
+            };
+            chords.push((parse_chord(chord)?, beats));
+        }
+        // Chords without a duration share whatever the explicit durations leave of the bar
+        let explicit: Ratio<u32> = chords.iter().filter_map(|(_, beats)| *beats).sum();
+        let implicit = chords.iter().filter(|(_, beats)| beats.is_none()).count() as u32;
+        let bar = Ratio::from_integer(beats_per_bar);
+        if barred && explicit > bar {
+            return Err(Error::OutofBounds);
+        }
+        let remaining = if explicit < bar { bar - explicit } else { Ratio::zero() };
+        for (chord, beats) in chords {
+            let beats = beats.unwrap_or_else(|| remaining / implicit);
+            if beats.is_zero() {
+                return Err(Error::OutofBounds);
+            }
+            progression.push(chord, beats);
+        }
+    }
+    Ok(progression)
+}
+
+impl<'a> IntoIterator for &'a Progression {
+    type Item = &'a ProgressionChord;
+    type IntoIter = std::slice::Iter<'a, ProgressionChord>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.chords.iter()
+    }
+}
+
+impl fmt::Display for Progression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chords = self
+            .chords
+            .iter()
+            .map(|entry| Ok(format!("{}:{}", entry.chord.symbol()?, entry.beats)))
+            .collect::<Result<Vec<String>>>()
+            .map_err(|_| fmt::Error)?;
+        write!(f, "{}", chords.join(" "))
+    }
+}
+
+impl FromStr for Progression {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<Progression> {
+        Progression::from_symbols(input, BEATS_PER_BAR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::key::Mode;
+    use crate::pitch::PitchOctave;
+
+    #[test]
+    fn test_progression_parsing() {
+        let progression = Progression::from_str("| Cmaj7 | Dm7 G7 | Am7:3 D7/F#:1 |").unwrap();
+        assert_eq!(progression.len(), 5);
+        assert_eq!(progression.total_beats(), Ratio::from_integer(12));
+        assert_eq!(progression.to_string(), "Cmaj7:4 Dm7:2 G7:2 Am7:3 D7/F#:1");
+        assert_eq!(Progression::from_str(&progression.to_string()).unwrap(), progression);
+
+        let key = Key::new(PitchOctave::from_str("C3").unwrap(), Mode::Major);
+        let numerals = Progression::from_roman_numerals("ii7 V7 Imaj7", &key, 4).unwrap();
+        let numerals = numerals.roman_numerals(&key).unwrap();
+        let numerals: Vec<String> = numerals.iter().map(|numeral| numeral.to_string()).collect();
+        assert_eq!(numerals, vec!["ii7", "V7", "Imaj7"]);
+        assert_eq!(Progression::from_str("| C:5 Dm |"), Err(Error::OutofBounds));
+        // Explicit durations can't overrun a bar line, but without bar lines a chord may last longer
+        assert_eq!(Progression::from_str("| C:5 |"), Err(Error::OutofBounds));
+        assert_eq!(Progression::from_str("| C:3 G:2 | F |"), Err(Error::OutofBounds));
+        assert_eq!(Progression::from_str("C:8").unwrap().total_beats(), Ratio::from_integer(8));
+    }
+
+    #[test]
+    fn test_transposition_and_export() {
+        let progression = Progression::from_str("Dm7 G7 Cmaj7").unwrap();
+        let from = Key::from_str("C major").unwrap();
+        let to = Key::from_str("Eb major").unwrap();
+        let transposed = progression.transpose_to_key(&from, &to).unwrap();
+        assert_eq!(transposed.to_string(), "Fm7:4 Bb7:4 Ebmaj7:4");
+        assert_eq!(transposed.pitches().unwrap()[2].0[0].to_string(), "Eb3");
+
+        let midi = progression.to_midi(120).unwrap();
+        assert_eq!(&midi[..4], b"MThd");
+        assert_eq!(&midi[14..18], b"MTrk");
+        // The first note on after the tempo event is D3, MIDI note 50
+        assert_eq!(&midi[30..33], &[0x90, 50, MIDI_VELOCITY]);
+        assert_eq!(&midi[midi.len() - 3..], &[0xFF, 0x2F, 0x00]);
+    }
+}
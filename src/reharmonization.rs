@@ -0,0 +1,258 @@
+use crate::chord::{Chord, ChordQuality};
+use crate::error::Result;
+use crate::interval::IntervalType;
+use crate::key::{Key, Mode};
+use crate::pitch::Alter;
+use crate::roman_numeral::RomanNumeral;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SubstitutionType {
+    TritoneSubstitution,
+    BackdoorDominant,
+    SecondaryDominant,
+    TwoFiveInterpolation,
+    DiminishedPassing,
+    ModalInterchange,
+}
+
+/// An alternative to a progression, differing from it by a single substitution
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reharmonization {
+    pub kind: SubstitutionType,
+    pub label: String,
+    pub chords: Vec<Chord>,
+}
+
+fn semitones_between(from: &Chord, to: &Chord) -> u8 {
+    (to.root.pitch.pitch_class() + 12 - from.root.pitch.pitch_class()) % 12
+}
+
+fn is_major(quality: ChordQuality) -> bool {
+    matches!(quality, ChordQuality::Major | ChordQuality::Major7 | ChordQuality::Add9 | ChordQuality::Major9)
+}
+
+fn is_minor(quality: ChordQuality) -> bool {
+    matches!(
+        quality,
+        ChordQuality::Minor
+            | ChordQuality::Minor7
+            | ChordQuality::MinorAdd9
+            | ChordQuality::Minor9
+            | ChordQuality::Minor11
+    )
+}
+
+fn transposed(chord: &Chord, up: bool, interval: IntervalType, kind: ChordQuality) -> Option<Chord> {
+    let root = if up { chord.root.checked_add(interval) } else { chord.root.checked_sub(interval) };
+    Some(Chord::new(root?, kind))
+}
+
+fn symbols(chords: &[Chord]) -> Result<String> {
+    Ok(chords.iter().map(|chord| chord.symbol()).collect::<Result<Vec<String>>>()?.join(" "))
+}
+
+// Builds the progression with chords[index..index + remove] replaced by the new chords, or None if a
+// chord can't be named
+fn substitute(
+    chords: &[Chord],
+    index: usize,
+    remove: usize,
+    new_chords: Vec<Chord>,
+    kind: SubstitutionType,
+    description: &str,
+) -> Option<Reharmonization> {
+    let label = if remove == 0 {
+        format!("{}: {} before {}", description, symbols(&new_chords).ok()?, chords[index].symbol().ok()?)
+    } else {
+        let replaced = symbols(&chords[index..index + remove]).ok()?;
+        format!("{}: {} for {}", description, symbols(&new_chords).ok()?, replaced)
+    };
+    let mut reharmonized = chords[..index].to_vec();
+    reharmonized.extend(new_chords);
+    reharmonized.extend_from_slice(&chords[index + remove..]);
+    Some(Reharmonization { kind, label, chords: reharmonized })
+}
+
+// The borrowed chord from the parallel key, if there is a common one for this numeral
+fn modal_interchange(chord: &Chord, key: &Key) -> Option<Chord> {
+    let numeral = chord.roman_numeral(key).ok()?;
+    if numeral.secondary.is_some() || numeral.alter != Alter::None {
+        return None;
+    }
+    let parallel = Key::new(
+        key.tonic,
+        match key.mode {
+            Mode::Major => Mode::Minor,
+            Mode::Minor => Mode::Major,
+        },
+    );
+    let quality = match (key.mode, numeral.degree, numeral.quality) {
+        (Mode::Major, 4, ChordQuality::Major) => ChordQuality::Minor,
+        (Mode::Major, 4, ChordQuality::Major7) => ChordQuality::Minor7,
+        (Mode::Major, 2, ChordQuality::Minor) => ChordQuality::Diminished,
+        (Mode::Major, 2, ChordQuality::Minor7) => ChordQuality::Minor7Flat5,
+        (Mode::Major, 6, ChordQuality::Minor) => ChordQuality::Major,
+        (Mode::Major, 6, ChordQuality::Minor7) => ChordQuality::Major7,
+        (Mode::Major, 3, ChordQuality::Minor) => ChordQuality::Major,
+        (Mode::Major, 3, ChordQuality::Minor7) => ChordQuality::Major7,
+        (Mode::Minor, 4, ChordQuality::Minor) => ChordQuality::Major,
+        (Mode::Minor, 4, ChordQuality::Minor7) => ChordQuality::Dom7,
+        (Mode::Minor, 1, ChordQuality::Minor) => ChordQuality::Major,
+        (Mode::Minor, 1, ChordQuality::Minor7) => ChordQuality::Major7,
+        _ => return None,
+    };
+    let borrowed = RomanNumeral::new(Alter::None, numeral.degree, quality, numeral.inversion).to_chord(&parallel).ok()?;
+    // Keep the register of the original chord
+    let root = if borrowed.root.get_semitone_value().ok()? >= chord.root.get_semitone_value().ok()? + 6 {
+        borrowed.root.checked_sub(IntervalType::Octave)?
+    } else {
+        borrowed.root
+    };
+    Some(Chord::new_as_inversion(root, quality, numeral.inversion))
+}
+
+/// Suggests reharmonizations of a progression in the given key, each applying one substitution.
+/// Substitutions whose chords would fall outside the range of pitches are left out.
+pub fn reharmonize(chords: &[Chord], key: &Key) -> Vec<Reharmonization> {
+    let mut suggestions = vec![];
+    for (i, chord) in chords.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|previous| &chords[previous]);
+        let next = chords.get(i + 1);
+        let is_dominant = chord.kind.is_dominant() && !chord.kind.is_augmented_sixth();
+
+        if let Some(next) = next {
+            if is_dominant && semitones_between(chord, next) == 5 {
+                let tritone = transposed(chord, false, IntervalType::AugmentedFourth, ChordQuality::Dom7);
+                suggestions.extend(tritone.and_then(|substitute_dominant| {
+                    substitute(
+                        chords,
+                        i,
+                        1,
+                        vec![substitute_dominant],
+                        SubstitutionType::TritoneSubstitution,
+                        "tritone substitution",
+                    )
+                }));
+                if is_major(next.kind) {
+                    let four = transposed(next, true, IntervalType::Fourth, ChordQuality::Minor7);
+                    let flat_seven = transposed(next, false, IntervalType::MajorSecond, ChordQuality::Dom7);
+                    suggestions.extend(four.zip(flat_seven).and_then(|(four, flat_seven)| {
+                        substitute(
+                            chords,
+                            i,
+                            1,
+                            vec![four, flat_seven],
+                            SubstitutionType::BackdoorDominant,
+                            "backdoor dominant",
+                        )
+                    }));
+                }
+                let has_two = previous.map(|previous| semitones_between(previous, chord) == 5).unwrap_or(false);
+                if !has_two {
+                    let quality = if is_minor(next.kind) { ChordQuality::Minor7Flat5 } else { ChordQuality::Minor7 };
+                    suggestions.extend(transposed(chord, false, IntervalType::Fourth, quality).and_then(|two| {
+                        substitute(chords, i, 0, vec![two], SubstitutionType::TwoFiveInterpolation, "related ii")
+                    }));
+                }
+            }
+
+            // A diminished seventh on the chromatic step between roots a whole step apart
+            let passing = match semitones_between(chord, next) {
+                2 => transposed(next, false, IntervalType::MinorSecond, ChordQuality::Diminished7),
+                10 => transposed(next, true, IntervalType::MinorSecond, ChordQuality::Diminished7),
+                _ => None,
+            };
+            suggestions.extend(passing.and_then(|passing| {
+                substitute(
+                    chords,
+                    i + 1,
+                    0,
+                    vec![passing],
+                    SubstitutionType::DiminishedPassing,
+                    "diminished passing chord",
+                )
+            }));
+        }
+
+        // Approach chords which are not already approached by their dominant
+        let is_target = is_major(chord.kind) || is_minor(chord.kind) || is_dominant;
+        let is_approached = previous
+            .map(|previous| previous.kind.is_dominant() && semitones_between(previous, chord) == 5)
+            .unwrap_or(false);
+        if let Some(previous) = previous.filter(|_| is_target && !is_approached) {
+            // A minor chord a fifth above already leads to the target, so becomes its dominant
+            if is_minor(previous.kind) && semitones_between(previous, chord) == 5 {
+                suggestions.extend(substitute(
+                    chords,
+                    i - 1,
+                    1,
+                    vec![Chord::new(previous.root, ChordQuality::Dom7)],
+                    SubstitutionType::SecondaryDominant,
+                    "secondary dominant",
+                ));
+            } else if let Some(dominant) = transposed(chord, false, IntervalType::Fourth, ChordQuality::Dom7) {
+                suggestions.extend(substitute(
+                    chords,
+                    i,
+                    0,
+                    vec![dominant.clone()],
+                    SubstitutionType::SecondaryDominant,
+                    "secondary dominant",
+                ));
+                let quality = if is_minor(chord.kind) { ChordQuality::Minor7Flat5 } else { ChordQuality::Minor7 };
+                suggestions.extend(transposed(&dominant, false, IntervalType::Fourth, quality).and_then(|two| {
+                    substitute(chords, i, 0, vec![two, dominant], SubstitutionType::TwoFiveInterpolation, "ii-V")
+                }));
+            }
+        }
+
+        suggestions.extend(modal_interchange(chord, key).and_then(|borrowed| {
+            substitute(chords, i, 1, vec![borrowed], SubstitutionType::ModalInterchange, "modal interchange")
+        }));
+    }
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pitch::Octave;
+    use std::str::FromStr;
+
+    fn chords(symbols: &str, octave: Octave) -> Vec<Chord> {
+        symbols.split_whitespace().map(|symbol| Chord::from_symbol(symbol, octave).unwrap()).collect()
+    }
+
+    fn suggestion(suggestions: &[Reharmonization], kind: SubstitutionType) -> String {
+        let reharmonization = suggestions.iter().find(|s| s.kind == kind).unwrap();
+        symbols(&reharmonization.chords).unwrap()
+    }
+
+    #[test]
+    fn test_two_five_one() {
+        let key = Key::from_str("C major").unwrap();
+        let suggestions = reharmonize(&chords("Dm7 G7 Cmaj7", Octave::Octave3), &key);
+        assert_eq!(suggestion(&suggestions, SubstitutionType::TritoneSubstitution), "Dm7 Db7 Cmaj7");
+        assert_eq!(suggestion(&suggestions, SubstitutionType::BackdoorDominant), "Dm7 Fm7 Bb7 Cmaj7");
+        assert_eq!(suggestion(&suggestions, SubstitutionType::SecondaryDominant), "D7 G7 Cmaj7");
+        assert_eq!(suggestion(&suggestions, SubstitutionType::ModalInterchange), "Dm7b5 G7 Cmaj7");
+        let tritone = suggestions.iter().find(|s| s.kind == SubstitutionType::TritoneSubstitution).unwrap();
+        assert_eq!(tritone.label, "tritone substitution: Db7 for G7");
+
+        // Candidates below the lowest octave are skipped rather than failing the whole analysis
+        let low = reharmonize(&chords("Dm7 G7 Cmaj7", Octave::Octave0), &key);
+        assert!(!low.is_empty() && low.len() < suggestions.len());
+        assert!(low.iter().all(|s| s.kind != SubstitutionType::BackdoorDominant));
+    }
+
+    #[test]
+    fn test_passing_and_interpolation() {
+        let key = Key::from_str("C major").unwrap();
+        let suggestions = reharmonize(&chords("Cmaj7 Dm7", Octave::Octave3), &key);
+        assert_eq!(suggestion(&suggestions, SubstitutionType::DiminishedPassing), "Cmaj7 C#dim7 Dm7");
+        assert_eq!(suggestion(&suggestions, SubstitutionType::TwoFiveInterpolation), "Cmaj7 Em7b5 A7 Dm7");
+
+        let suggestions = reharmonize(&chords("C F", Octave::Octave3), &key);
+        assert_eq!(suggestion(&suggestions, SubstitutionType::ModalInterchange), "C Fm");
+    }
+}
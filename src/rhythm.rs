@@ -0,0 +1,321 @@
+use crate::error::{Error, Result};
+use crate::pitch::PitchOctave;
+use num::rational::Ratio;
+use num::Zero;
+use std::fmt;
+use strum::{EnumIter, IntoEnumIterator};
+
+pub const DEFAULT_VELOCITY: u8 = 80;
+/// The most dots a duration can have
+pub const MAX_DOTS: u8 = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, EnumIter)]
+pub enum NoteValue {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    Sixteenth,
+    ThirtySecond,
+    SixtyFourth,
+    HundredTwentyEighth,
+}
+
+impl NoteValue {
+    /// Returns the length in quarter note beats
+    pub fn beats(&self) -> Ratio<u32> {
+        Ratio::new(4, 1 << *self as u32)
+    }
+
+    /// Returns the number written for the value, 1 for a whole note up to 128
+    pub fn denominator(&self) -> u32 {
+        1 << *self as u32
+    }
+
+    pub fn from_denominator(denominator: u32) -> Result<NoteValue> {
+        NoteValue::iter()
+            .find(|value| value.denominator() == denominator)
+            .ok_or(Error::OutofBounds)
+    }
+}
+
+/// Fits `actual` notes into the time of `normal` notes, so a triplet is 3:2
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tuplet {
+    actual: u32,
+    normal: u32,
+}
+
+impl Tuplet {
+    pub const TRIPLET: Tuplet = Tuplet { actual: 3, normal: 2 };
+
+    pub fn new(actual: u32, normal: u32) -> Result<Tuplet> {
+        if actual == 0 || normal == 0 {
+            return Err(Error::OutofBounds);
+        }
+        Ok(Tuplet { actual, normal })
+    }
+
+    /// Returns the tuplet a length implies, fitting the odd factor of its denominator into the
+    /// next lower power of two, so thirds of a beat are triplets and fifths are 5:4
+    pub fn from_beats(beats: Ratio<u32>) -> Option<Tuplet> {
+        let mut actual = *beats.denom();
+        while actual % 2 == 0 {
+            actual /= 2;
+        }
+        if actual == 1 {
+            return None;
+        }
+        Some(Tuplet { actual, normal: 1 << (u32::BITS - 1 - actual.leading_zeros()) })
+    }
+
+    pub fn actual(&self) -> u32 {
+        self.actual
+    }
+
+    pub fn normal(&self) -> u32 {
+        self.normal
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Duration {
+    value: NoteValue,
+    dots: u8,
+    tuplet: Option<Tuplet>,
+}
+
+impl Duration {
+    pub fn new(value: NoteValue) -> Duration {
+        Duration { value, dots: 0, tuplet: None }
+    }
+
+    pub fn dotted(value: NoteValue, dots: u8) -> Result<Duration> {
+        if dots > MAX_DOTS {
+            return Err(Error::Unsupported);
+        }
+        Ok(Duration { value, dots, tuplet: None })
+    }
+
+    pub fn with_tuplet(self, tuplet: Tuplet) -> Duration {
+        Duration { tuplet: Some(tuplet), ..self }
+    }
+
+    pub fn value(&self) -> NoteValue {
+        self.value
+    }
+
+    pub fn dots(&self) -> u8 {
+        self.dots
+    }
+
+    pub fn tuplet(&self) -> Option<Tuplet> {
+        self.tuplet
+    }
+
+    /// Returns the length in quarter note beats, each dot adding half the previous value
+    pub fn beats(&self) -> Ratio<u32> {
+        let base = self.value.beats();
+        let dotted = base * Ratio::new((1 << (self.dots as u32 + 1)) - 1, 1 << self.dots as u32);
+        match self.tuplet {
+            Some(tuplet) => dotted * Ratio::new(tuplet.normal, tuplet.actual),
+            None => dotted,
+        }
+    }
+
+    /// Finds the simplest duration lasting the given beats, preferring fewer dots and plain
+    /// values over the tuplet the length implies
+    pub fn from_beats(beats: Ratio<u32>) -> Option<Duration> {
+        for tuplet in [None, Tuplet::from_beats(beats)] {
+            for dots in 0..=MAX_DOTS {
+                for value in NoteValue::iter() {
+                    let duration = Duration { value, dots, tuplet };
+                    if duration.beats() == beats {
+                        return Some(duration);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Adds two durations, if a single duration can express the sum
+    pub fn checked_add(&self, other: Duration) -> Option<Duration> {
+        Duration::from_beats(self.beats() + other.beats())
+    }
+
+    /// Subtracts a shorter duration, if a single duration can express the difference
+    pub fn checked_sub(&self, other: Duration) -> Option<Duration> {
+        if other.beats() >= self.beats() {
+            return None;
+        }
+        Duration::from_beats(self.beats() - other.beats())
+    }
+
+    /// Breaks a length into durations to be tied together, longest first. A length a single
+    /// duration can express is kept whole.
+    pub fn tied_from_beats(beats: Ratio<u32>) -> Result<Vec<Duration>> {
+        if let Some(duration) = Duration::from_beats(beats) {
+            return Ok(vec![duration]);
+        }
+        let mut candidates: Vec<Duration> = [None, Tuplet::from_beats(beats)]
+            .into_iter()
+            .flat_map(|tuplet| NoteValue::iter().map(move |value| (value, tuplet)))
+            .flat_map(|(value, tuplet)| (0..=MAX_DOTS).map(move |dots| Duration { value, dots, tuplet }))
+            .collect();
+        candidates.sort_by_key(|duration| std::cmp::Reverse(duration.beats()));
+        let mut durations = vec![];
+        let mut remaining = beats;
+        while !remaining.is_zero() {
+            let duration = candidates
+                .iter()
+                .find(|duration| duration.beats() <= remaining)
+                .ok_or(Error::Unsupported)?;
+            durations.push(*duration);
+            remaining -= duration.beats();
+        }
+        Ok(durations)
+    }
+}
+
+impl From<Duration> for Ratio<u32> {
+    fn from(duration: Duration) -> Ratio<u32> {
+        duration.beats()
+    }
+}
+
+// Written as the note value's number followed by its dots, such as "8." for a dotted eighth,
+// with tuplets appended as in "8(3:2)"
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value.denominator(), ".".repeat(self.dots as usize))?;
+        if let Some(tuplet) = self.tuplet {
+            write!(f, "({}:{})", tuplet.actual, tuplet.normal)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Tie {
+    #[default]
+    None,
+    // Tied into the following note
+    Start,
+    // Tied from the previous note
+    Stop,
+    // Tied from the previous note and into the following one
+    Continue,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Note {
+    pub pitch: PitchOctave,
+    pub duration: Duration,
+    // MIDI velocity, 1 to 127
+    pub velocity: u8,
+    pub tie: Tie,
+}
+
+impl Note {
+    pub fn new(pitch: PitchOctave, duration: Duration) -> Note {
+        Note {
+            pitch,
+            duration,
+            velocity: DEFAULT_VELOCITY,
+            tie: Tie::None,
+        }
+    }
+
+    pub fn beats(&self) -> Ratio<u32> {
+        self.duration.beats()
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rest {
+    pub duration: Duration,
+}
+
+impl Rest {
+    pub fn new(duration: Duration) -> Rest {
+        Rest { duration }
+    }
+}
+
+/// Anything that takes up time in a part, the notes of a chord sharing one duration
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Note(Note),
+    Chord(Vec<Note>),
+    Rest(Rest),
+}
+
+impl Event {
+    pub fn duration(&self) -> Result<Duration> {
+        match self {
+            Event::Note(note) => Ok(note.duration),
+            Event::Chord(notes) => notes.first().map(|note| note.duration).ok_or(Error::OutofBounds),
+            Event::Rest(rest) => Ok(rest.duration),
+        }
+    }
+
+    pub fn beats(&self) -> Ratio<u32> {
+        self.duration().map(|duration| duration.beats()).unwrap_or_else(|_| Ratio::zero())
+    }
+
+    /// Returns the pitches sounding during the event
+    pub fn pitches(&self) -> Vec<PitchOctave> {
+        match self {
+            Event::Note(note) => vec![note.pitch],
+            Event::Chord(notes) => notes.iter().map(|note| note.pitch).collect(),
+            Event::Rest(_) => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_duration_beats() {
+        assert_eq!(Duration::new(NoteValue::Whole).beats(), Ratio::from_integer(4));
+        assert_eq!(Duration::new(NoteValue::HundredTwentyEighth).beats(), Ratio::new(1, 32));
+        assert_eq!(Duration::dotted(NoteValue::Quarter, 1).unwrap().beats(), Ratio::new(3, 2));
+        assert_eq!(Duration::dotted(NoteValue::Half, 2).unwrap().beats(), Ratio::new(7, 2));
+        assert_eq!(Duration::dotted(NoteValue::Half, MAX_DOTS + 1), Err(Error::Unsupported));
+        assert_eq!(Duration::dotted(NoteValue::Half, 40), Err(Error::Unsupported));
+        assert_eq!(Tuplet::new(0, 2), Err(Error::OutofBounds));
+        let triplet = Duration::new(NoteValue::Eighth).with_tuplet(Tuplet::TRIPLET);
+        assert_eq!(triplet.beats(), Ratio::new(1, 3));
+        assert_eq!(triplet.to_string(), "8(3:2)");
+        assert_eq!(Duration::dotted(NoteValue::Eighth, 1).unwrap().to_string(), "8.");
+    }
+
+    #[test]
+    fn test_duration_arithmetic() {
+        let quarter = Duration::new(NoteValue::Quarter);
+        let eighth = Duration::new(NoteValue::Eighth);
+        assert_eq!(quarter.checked_add(eighth), Duration::dotted(NoteValue::Quarter, 1).ok());
+        assert_eq!(quarter.checked_sub(eighth), Some(eighth));
+        assert_eq!(eighth.checked_sub(quarter), None);
+        // Five eighths need a tie
+        assert_eq!(Duration::new(NoteValue::Half).checked_add(eighth), None);
+        assert_eq!(Duration::from_beats(Ratio::new(2, 3)), Some(Duration::new(NoteValue::Quarter).with_tuplet(Tuplet::TRIPLET)));
+        // Fifths of a beat are quintuplet sixteenths, and a length can be tied from plain and tuplet pieces
+        let quintuplet = Duration::new(NoteValue::Sixteenth).with_tuplet(Tuplet::new(5, 4).unwrap());
+        assert_eq!(Duration::from_beats(Ratio::new(1, 5)), Some(quintuplet));
+        assert_eq!(Duration::from_beats(Ratio::new(1, 7)).unwrap().to_string(), "16(7:4)");
+        let tied = Duration::tied_from_beats(Ratio::new(11, 10)).unwrap();
+        assert_eq!(tied, vec![quarter, Duration::new(NoteValue::ThirtySecond).with_tuplet(Tuplet::new(5, 4).unwrap())]);
+
+        let note = Note::new(PitchOctave::from_str("C4").unwrap(), quarter);
+        assert_eq!(note.velocity, DEFAULT_VELOCITY);
+        let chord = Event::Chord(vec![note, Note::new(PitchOctave::from_str("E4").unwrap(), quarter)]);
+        assert_eq!(chord.beats(), Ratio::from_integer(1));
+        assert_eq!(chord.pitches().len(), 2);
+        assert_eq!(Event::Rest(Rest::new(eighth)).beats(), Ratio::new(1, 2));
+    }
+}
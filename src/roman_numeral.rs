@@ -0,0 +1,416 @@
+use crate::chord::{Chord, ChordQuality, Inversion};
+use crate::error::{Error, Result};
+use crate::interval::IntervalType;
+use crate::key::{Key, Mode};
+use crate::pitch::Alter;
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    combinator::{all_consuming, map, map_res, opt, value},
+    sequence::{preceded, tuple},
+    IResult,
+};
+use std::fmt;
+use std::str::FromStr;
+
+const NUMERALS: [&str; 7] = ["I", "II", "III", "IV", "V", "VI", "VII"];
+const TRIAD_FIGURES: [&str; 3] = ["", "6", "6/4"];
+const SEVENTH_FIGURES: [&str; 4] = ["7", "6/5", "4/3", "4/2"];
+
+/// A chord described by its function in a key, such as V6/5, bVI or vii°7/V
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RomanNumeral {
+    // Chromatic alteration of the root relative to the scale degree
+    pub alter: Alter,
+    // Scale degree of the root, counted from 1
+    pub degree: u8,
+    pub quality: ChordQuality,
+    pub inversion: Inversion,
+    // For applied chords, the chord being tonicized, so V/V is the dominant of the dominant
+    pub secondary: Option<Box<RomanNumeral>>,
+}
+
+impl RomanNumeral {
+    pub fn new(alter: Alter, degree: u8, quality: ChordQuality, inversion: Inversion) -> RomanNumeral {
+        RomanNumeral {
+            alter,
+            degree,
+            quality,
+            inversion,
+            secondary: None,
+        }
+    }
+
+    /// Returns the numeral of a chord in the given key. Chords outside the key are read as
+    /// applied dominants or leading tone chords where possible, and as altered degrees otherwise.
+    pub fn analyze(chord: &Chord, key: &Key) -> Result<RomanNumeral> {
+        if chord.kind.is_augmented_sixth() {
+            // Augmented sixth chords are always built on the lowered sixth degree
+            let semitones = (chord.root.pitch.pitch_class() + 12 - key.tonic.pitch.pitch_class()) % 12;
+            if semitones != 8 {
+                return Err(Error::Unsupported);
+            }
+            return Ok(RomanNumeral::new(Alter::None, 6, chord.kind, chord.inversion));
+        }
+        if !is_supported_quality(chord.kind) {
+            return Err(Error::Unsupported);
+        }
+        let chord_set = chord.kind.pitch_class_set().transpose(chord.root.pitch.pitch_class());
+        if chord_set.is_subset_of(&key.pitch_class_set()) {
+            return diatonic_numeral(chord, key);
+        }
+        if let Some(numeral) = applied_numeral(chord, key)? {
+            return Ok(numeral);
+        }
+        diatonic_numeral(chord, key)
+    }
+
+    /// Returns the chord the numeral stands for in the given key, with its root above the tonic
+    pub fn to_chord(&self, key: &Key) -> Result<Chord> {
+        let key = match &self.secondary {
+            Some(target) => {
+                let target = target.to_chord(key)?;
+                let mode = if is_minor_quality(target.kind) {
+                    Mode::Minor
+                } else {
+                    Mode::Major
+                };
+                Key::new(target.root, mode)
+            }
+            None => *key,
+        };
+        let root = if self.quality.is_augmented_sixth() {
+            key.tonic.checked_add(IntervalType::MinorSixth).ok_or(Error::OutofBounds)?
+        } else {
+            key.degree_pitch(self.degree, self.alter, self.quality)?
+        };
+        Ok(Chord::new_as_inversion(root, self.quality, self.inversion))
+    }
+
+    /// Parses a whitespace separated list of numerals such as "ii7 V7 Imaj7"
+    pub fn parse_progression(input: &str) -> Result<Vec<RomanNumeral>> {
+        input.split_whitespace().map(RomanNumeral::from_str).collect()
+    }
+
+    fn is_seventh(&self) -> bool {
+        self.quality.root_chord_interval().len() > 2
+    }
+
+    fn figure(&self) -> &'static str {
+        let index = self.inversion as usize;
+        if self.is_seventh() {
+            SEVENTH_FIGURES.get(index).copied().unwrap_or_default()
+        } else {
+            TRIAD_FIGURES.get(index).copied().unwrap_or_default()
+        }
+    }
+}
+
+fn is_supported_quality(quality: ChordQuality) -> bool {
+    matches!(
+        quality,
+        ChordQuality::Major
+            | ChordQuality::Minor
+            | ChordQuality::Diminished
+            | ChordQuality::Augmented
+            | ChordQuality::Major7
+            | ChordQuality::Minor7
+            | ChordQuality::Dom7
+            | ChordQuality::Dom7Sharp5
+            | ChordQuality::Diminished7
+            | ChordQuality::Minor7Flat5
+            | ChordQuality::MinorMajor7
+    )
+}
+
+fn is_minor_quality(quality: ChordQuality) -> bool {
+    quality.root_chord_interval().contains(&3)
+}
+
+// Reads the chord's root as a scale degree of the key, altered as needed
+fn diatonic_numeral(chord: &Chord, key: &Key) -> Result<RomanNumeral> {
+    let degree = (chord.root.pitch.step as u8 + 7 - key.tonic.pitch.step as u8) % 7 + 1;
+    let semitones = (chord.root.pitch.pitch_class() + 12 - key.tonic.pitch.pitch_class()) % 12;
+    let difference = (semitones as i8 - key.degree_semitones(degree, chord.kind) as i8 + 18).rem_euclid(12) - 6;
+    let alter = Alter::from_semitones(difference).ok_or(Error::Unsupported)?;
+    Ok(RomanNumeral::new(alter, degree, chord.kind, chord.inversion))
+}
+
+// Returns the triad built from the key's own scale on a degree, if it is major or minor
+fn diatonic_triad(key: &Key, degree: u8) -> Option<ChordQuality> {
+    let scale_type = key.scale_type();
+    let scale = scale_type.scale_interval();
+    let tone = |offset: u8| -> u8 {
+        let index = (degree - 1 + offset) as usize % 7;
+        if index == 0 { 0 } else { scale[index - 1] }
+    };
+    let third = (tone(2) + 12 - tone(0)) % 12;
+    let fifth = (tone(4) + 12 - tone(0)) % 12;
+    match (third, fifth) {
+        (4, 7) => Some(ChordQuality::Major),
+        (3, 7) => Some(ChordQuality::Minor),
+        _ => None,
+    }
+}
+
+// Reads the chord as the dominant or leading tone chord of a major or minor triad in the key
+fn applied_numeral(chord: &Chord, key: &Key) -> Result<Option<RomanNumeral>> {
+    let (degree, target_root) = match chord.kind {
+        ChordQuality::Major | ChordQuality::Dom7 => (5, chord.root.checked_sub(IntervalType::Fifth)),
+        ChordQuality::Diminished | ChordQuality::Diminished7 | ChordQuality::Minor7Flat5 => {
+            (7, chord.root.checked_add(IntervalType::MinorSecond))
+        }
+        _ => return Ok(None),
+    };
+    let target_root = target_root.ok_or(Error::OutofBounds)?;
+    for target_degree in 2..=7 {
+        let Some(quality) = diatonic_triad(key, target_degree) else {
+            continue;
+        };
+        let target = Chord::new(target_root, quality);
+        let numeral = diatonic_numeral(&target, key)?;
+        if numeral.degree == target_degree && numeral.alter == Alter::None {
+            let mut applied = RomanNumeral::new(Alter::None, degree, chord.kind, chord.inversion);
+            applied.secondary = Some(Box::new(numeral));
+            return Ok(Some(applied));
+        }
+    }
+    Ok(None)
+}
+
+impl Chord {
+    pub fn roman_numeral(&self, key: &Key) -> Result<RomanNumeral> {
+        RomanNumeral::analyze(self, key)
+    }
+}
+
+impl Key {
+    /// Returns the chords of a progression of numerals such as "ii7 V7 Imaj7"
+    pub fn realize(&self, numerals: &str) -> Result<Vec<Chord>> {
+        RomanNumeral::parse_progression(numerals)?
+            .iter()
+            .map(|numeral| numeral.to_chord(self))
+            .collect()
+    }
+
+    /// Returns the numeral of each chord in this key
+    pub fn analyze(&self, chords: &[Chord]) -> Result<Vec<RomanNumeral>> {
+        chords.iter().map(|chord| chord.roman_numeral(self)).collect()
+    }
+}
+
+impl fmt::Display for RomanNumeral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let special = match self.quality {
+            ChordQuality::ItalianSixth => Some("It"),
+            ChordQuality::FrenchSixth => Some("Fr"),
+            ChordQuality::GermanSixth => Some("Ger"),
+            _ => None,
+        };
+        if let Some(name) = special {
+            write!(f, "{}+6", name)?;
+        } else if self.alter == Alter::Flat && self.degree == 2 && self.quality == ChordQuality::Major {
+            // The Neapolitan
+            write!(f, "N{}", self.figure())?;
+        } else {
+            let numeral = NUMERALS.get(self.degree as usize - 1).copied().unwrap_or_default();
+            let numeral = if is_minor_quality(self.quality) {
+                numeral.to_lowercase()
+            } else {
+                numeral.to_string()
+            };
+            let symbol = match self.quality {
+                ChordQuality::Diminished | ChordQuality::Diminished7 => "°",
+                ChordQuality::Minor7Flat5 => "ø",
+                ChordQuality::Augmented | ChordQuality::Dom7Sharp5 => "+",
+                ChordQuality::Major7 | ChordQuality::MinorMajor7 => "maj",
+                _ => "",
+            };
+            write!(f, "{}{}{}{}", self.alter, numeral, symbol, self.figure())?;
+        }
+        if let Some(target) = &self.secondary {
+            write!(f, "/{}", target)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Symbol {
+    Diminished,
+    HalfDiminished,
+    Augmented,
+    Major,
+}
+
+fn parse_alter(input: &str) -> IResult<&str, Alter> {
+    map(opt(alt((tag("bb"), tag("b"), tag("##"), tag("#")))), |alter: Option<&str>| {
+        Alter::from_str(alter.unwrap_or_default()).unwrap_or_default()
+    })(input)
+}
+
+// Returns the degree and whether the numeral was upper case
+fn parse_degree(input: &str) -> IResult<&str, (u8, bool)> {
+    map_res(take_while1(|c: char| "IViv".contains(c)), |numeral: &str| {
+        let upper = numeral.to_uppercase();
+        let degree = NUMERALS.iter().position(|n| *n == upper).ok_or(Error::Parse)?;
+        if numeral != upper && numeral != upper.to_lowercase() {
+            return Err(Error::Parse);
+        }
+        Ok((degree as u8 + 1, numeral == upper))
+    })(input)
+}
+
+fn parse_symbol(input: &str) -> IResult<&str, Option<Symbol>> {
+    opt(alt((
+        value(Symbol::Diminished, alt((tag("°"), tag("o")))),
+        value(Symbol::HalfDiminished, alt((tag("ø"), tag("%")))),
+        value(Symbol::Augmented, tag("+")),
+        value(Symbol::Major, alt((tag("maj"), tag("M")))),
+    )))(input)
+}
+
+// Returns whether the figure belongs to a seventh chord, and the inversion it denotes
+fn parse_figure(input: &str) -> IResult<&str, Option<(bool, Inversion)>> {
+    opt(alt((
+        value((true, Inversion::First), alt((tag("6/5"), tag("65")))),
+        value((true, Inversion::Second), alt((tag("4/3"), tag("43")))),
+        value((true, Inversion::Third), alt((tag("4/2"), tag("42"), tag("2")))),
+        value((false, Inversion::Second), alt((tag("6/4"), tag("64")))),
+        value((true, Inversion::Root), tag("7")),
+        value((false, Inversion::First), tag("6")),
+    )))(input)
+}
+
+fn numeral_quality(upper: bool, symbol: Option<Symbol>, seventh: bool) -> Option<ChordQuality> {
+    let quality = match (upper, symbol, seventh) {
+        (true, None, false) => ChordQuality::Major,
+        (true, None, true) => ChordQuality::Dom7,
+        (true, Some(Symbol::Augmented), false) => ChordQuality::Augmented,
+        (true, Some(Symbol::Augmented), true) => ChordQuality::Dom7Sharp5,
+        (true, Some(Symbol::Major), _) => ChordQuality::Major7,
+        (false, None, false) => ChordQuality::Minor,
+        (false, None, true) => ChordQuality::Minor7,
+        (false, Some(Symbol::Diminished), false) => ChordQuality::Diminished,
+        (false, Some(Symbol::Diminished), true) => ChordQuality::Diminished7,
+        (false, Some(Symbol::HalfDiminished), _) => ChordQuality::Minor7Flat5,
+        (false, Some(Symbol::Major), _) => ChordQuality::MinorMajor7,
+        _ => return None,
+    };
+    Some(quality)
+}
+
+fn parse_scale_degree_numeral(input: &str) -> IResult<&str, RomanNumeral> {
+    map_res(
+        tuple((parse_alter, parse_degree, parse_symbol, parse_figure)),
+        |(alter, (degree, upper), symbol, figure)| {
+            // The major and half diminished symbols imply a seventh chord
+            let implied_seventh = matches!(symbol, Some(Symbol::Major) | Some(Symbol::HalfDiminished));
+            let (seventh, inversion) = figure.unwrap_or((implied_seventh, Inversion::Root));
+            let quality = numeral_quality(upper, symbol, seventh || implied_seventh).ok_or(Error::Parse)?;
+            Ok::<RomanNumeral, Error>(RomanNumeral::new(alter, degree, quality, inversion))
+        },
+    )(input)
+}
+
+fn parse_special_numeral(input: &str) -> IResult<&str, RomanNumeral> {
+    alt((
+        map(preceded(tag("N"), parse_figure), |figure| {
+            let inversion = figure.map(|(_, inversion)| inversion).unwrap_or_default();
+            RomanNumeral::new(Alter::Flat, 2, ChordQuality::Major, inversion)
+        }),
+        map(
+            tuple((
+                alt((
+                    value(ChordQuality::ItalianSixth, tag("It")),
+                    value(ChordQuality::FrenchSixth, tag("Fr")),
+                    value(ChordQuality::GermanSixth, tag("Ger")),
+                )),
+                opt(tag("+6")),
+            )),
+            |(quality, _)| RomanNumeral::new(Alter::None, 6, quality, Inversion::Root),
+        ),
+    ))(input)
+}
+
+fn parse_numeral(input: &str) -> IResult<&str, RomanNumeral> {
+    let (input, mut numeral) = alt((parse_special_numeral, parse_scale_degree_numeral))(input)?;
+    let (input, target) = opt(preceded(tag("/"), parse_numeral))(input)?;
+    numeral.secondary = target.map(Box::new);
+    Ok((input, numeral))
+}
+
+impl FromStr for RomanNumeral {
+    type Err = Error;
+    fn from_str(input: &str) -> Result<RomanNumeral> {
+        all_consuming(parse_numeral)(input.trim())
+            .map(|(_, numeral)| numeral)
+            .map_err(|_| Error::Parse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notes::Notes;
+    use crate::pitch::PitchOctave;
+
+    fn chord(root: &str, kind: ChordQuality, inversion: Inversion) -> Chord {
+        Chord::new_as_inversion(PitchOctave::from_str(root).unwrap(), kind, inversion)
+    }
+
+    #[test]
+    fn test_analysis() {
+        let c_major = Key::from_str("C major").unwrap();
+        let cases = [
+            (chord("G3", ChordQuality::Dom7, Inversion::First), "V6/5"),
+            (chord("B3", ChordQuality::Minor7Flat5, Inversion::Root), "viiø7"),
+            (chord("Ab3", ChordQuality::Major, Inversion::Root), "bVI"),
+            (chord("D3", ChordQuality::Major, Inversion::Root), "V/V"),
+            (chord("F#3", ChordQuality::Diminished7, Inversion::Root), "vii°7/V"),
+            (chord("Db3", ChordQuality::Major, Inversion::First), "N6"),
+            (chord("Ab3", ChordQuality::ItalianSixth, Inversion::Root), "It+6"),
+            (chord("C3", ChordQuality::Major7, Inversion::Root), "Imaj7"),
+            (chord("F3", ChordQuality::Minor, Inversion::Second), "iv6/4"),
+        ];
+        for (chord, numeral) in cases.iter() {
+            assert_eq!(chord.roman_numeral(&c_major).unwrap().to_string(), *numeral);
+        }
+
+        let c_minor = Key::from_str("C minor").unwrap();
+        let numerals: Vec<String> = [
+            chord("B3", ChordQuality::Diminished7, Inversion::Root),
+            chord("Bb3", ChordQuality::Major, Inversion::Root),
+            chord("Ab3", ChordQuality::Major, Inversion::Root),
+            chord("G3", ChordQuality::Dom7, Inversion::Third),
+        ]
+        .iter()
+        .map(|chord| chord.roman_numeral(&c_minor).unwrap().to_string())
+        .collect();
+        assert_eq!(numerals, vec!["vii°7", "VII", "VI", "V4/2"]);
+    }
+
+    #[test]
+    fn test_realization() {
+        let key = Key::from_str("C major").unwrap();
+        let chords = key.realize("ii7 V7 Imaj7").unwrap();
+        assert_eq!(chords[0], chord("D3", ChordQuality::Minor7, Inversion::Root));
+        assert_eq!(chords[1], chord("G3", ChordQuality::Dom7, Inversion::Root));
+        assert_eq!(chords[2], chord("C3", ChordQuality::Major7, Inversion::Root));
+
+        let numerals = ["V6/5", "vii°7", "bVI", "V/V", "vii°7/V", "N6", "It+6", "Ger+6", "iv6/4", "V7/ii"];
+        for numeral in numerals {
+            let parsed = RomanNumeral::from_str(numeral).unwrap();
+            assert_eq!(parsed.to_string(), numeral);
+            // Analyzing the realized chord gives back the numeral
+            let chord = parsed.to_chord(&key).unwrap();
+            assert_eq!(chord.roman_numeral(&key).unwrap(), parsed, "{}", numeral);
+        }
+        let german = RomanNumeral::from_str("Ger+6").unwrap().to_chord(&key).unwrap();
+        let german: Vec<String> = german.notes().unwrap().0.iter().map(|p| p.to_string()).collect();
+        assert_eq!(german, vec!["Ab3", "C4", "Eb4", "F#4"]);
+        assert_eq!(RomanNumeral::from_str("vii°7/V").unwrap().to_chord(&key).unwrap().root.to_string(), "F#4");
+        assert_eq!(RomanNumeral::from_str("IX"), Err(Error::Parse));
+        assert_eq!(RomanNumeral::from_str("Vi"), Err(Error::Parse));
+    }
+}
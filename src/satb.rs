@@ -0,0 +1,245 @@
+use crate::error::{Error, Result};
+use crate::key::Key;
+use crate::pitch::PitchOctave;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Voice {
+    Soprano,
+    Alto,
+    Tenor,
+    Bass,
+}
+
+impl Voice {
+    const ALL: [Voice; 4] = [Voice::Soprano, Voice::Alto, Voice::Tenor, Voice::Bass];
+
+    /// Returns the lowest and highest MIDI notes the voice is expected to sing
+    pub fn range(&self) -> (i8, i8) {
+        match self {
+            // C4 to G5
+            Voice::Soprano => (60, 79),
+            // G3 to D5
+            Voice::Alto => (55, 74),
+            // C3 to G4
+            Voice::Tenor => (48, 67),
+            // E2 to C4
+            Voice::Bass => (40, 60),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FourPartChord {
+    pub soprano: PitchOctave,
+    pub alto: PitchOctave,
+    pub tenor: PitchOctave,
+    pub bass: PitchOctave,
+}
+
+impl FourPartChord {
+    pub fn new(soprano: PitchOctave, alto: PitchOctave, tenor: PitchOctave, bass: PitchOctave) -> Self {
+        FourPartChord { soprano, alto, tenor, bass }
+    }
+
+    /// Zips four voice parts into chords, the parts must all be the same length
+    pub fn from_voices(
+        soprano: &[PitchOctave],
+        alto: &[PitchOctave],
+        tenor: &[PitchOctave],
+        bass: &[PitchOctave],
+    ) -> Result<Vec<FourPartChord>> {
+        if alto.len() != soprano.len() || tenor.len() != soprano.len() || bass.len() != soprano.len() {
+            return Err(Error::OutofBounds);
+        }
+        Ok((0..soprano.len())
+            .map(|i| FourPartChord::new(soprano[i], alto[i], tenor[i], bass[i]))
+            .collect())
+    }
+
+    pub fn voice(&self, voice: Voice) -> PitchOctave {
+        match voice {
+            Voice::Soprano => self.soprano,
+            Voice::Alto => self.alto,
+            Voice::Tenor => self.tenor,
+            Voice::Bass => self.bass,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ViolationType {
+    ParallelFifths,
+    ParallelOctaves,
+    HiddenFifths,
+    HiddenOctaves,
+    VoiceCrossing,
+    VoiceOverlap,
+    Spacing,
+    Range,
+    UnresolvedLeadingTone,
+    DoubledLeadingTone,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub kind: ViolationType,
+    // Index of the chord the violation occurs at, for motion errors the chord moved to
+    pub index: usize,
+    pub voices: Vec<Voice>,
+}
+
+// MIDI notes of each voice, soprano first
+fn midi(chord: &FourPartChord) -> Result<[i8; 4]> {
+    Ok([
+        chord.soprano.get_semitone_value()?,
+        chord.alto.get_semitone_value()?,
+        chord.tenor.get_semitone_value()?,
+        chord.bass.get_semitone_value()?,
+    ])
+}
+
+fn check_chord(index: usize, notes: &[i8; 4], leading_tone: i8, violations: &mut Vec<Violation>) {
+    for (voice, note) in Voice::ALL.iter().zip(notes) {
+        let (low, high) = voice.range();
+        if !(low..=high).contains(note) {
+            violations.push(Violation { kind: ViolationType::Range, index, voices: vec![*voice] });
+        }
+    }
+    for upper in 0..3 {
+        let lower = upper + 1;
+        let voices = vec![Voice::ALL[upper], Voice::ALL[lower]];
+        if notes[upper] < notes[lower] {
+            violations.push(Violation { kind: ViolationType::VoiceCrossing, index, voices: voices.clone() });
+        }
+        // Only the upper three voices need to stay within an octave of each other
+        if lower != 3 && notes[upper] - notes[lower] > 12 {
+            violations.push(Violation { kind: ViolationType::Spacing, index, voices });
+        }
+    }
+    let leading_tones: Vec<Voice> = Voice::ALL
+        .iter()
+        .zip(notes)
+        .filter(|(_, note)| note.rem_euclid(12) == leading_tone)
+        .map(|(voice, _)| *voice)
+        .collect();
+    if leading_tones.len() > 1 {
+        violations.push(Violation { kind: ViolationType::DoubledLeadingTone, index, voices: leading_tones });
+    }
+}
+
+fn check_motion(index: usize, from: &[i8; 4], to: &[i8; 4], key: (i8, i8), violations: &mut Vec<Violation>) {
+    for upper in 0..4 {
+        for lower in upper + 1..4 {
+            let voices = vec![Voice::ALL[upper], Voice::ALL[lower]];
+            let before = (from[upper] - from[lower]).rem_euclid(12);
+            let after = (to[upper] - to[lower]).rem_euclid(12);
+            let upper_motion = (to[upper] - from[upper]).signum();
+            let lower_motion = (to[lower] - from[lower]).signum();
+            let similar = upper_motion != 0 && upper_motion == lower_motion;
+            if similar && before == after && (after == 7 || after == 0) {
+                let kind = if after == 7 { ViolationType::ParallelFifths } else { ViolationType::ParallelOctaves };
+                violations.push(Violation { kind, index, voices });
+            } else if similar && upper == 0 && lower == 3 && (after == 7 || after == 0) {
+                // Outer voices approaching a perfect interval in similar motion with a leap above
+                if (to[upper] - from[upper]).abs() > 2 {
+                    let kind = if after == 7 { ViolationType::HiddenFifths } else { ViolationType::HiddenOctaves };
+                    violations.push(Violation { kind, index, voices });
+                }
+            }
+        }
+    }
+    for upper in 0..3 {
+        let lower = upper + 1;
+        if to[lower] > from[upper] || to[upper] < from[lower] {
+            violations.push(Violation {
+                kind: ViolationType::VoiceOverlap,
+                index,
+                voices: vec![Voice::ALL[upper], Voice::ALL[lower]],
+            });
+        }
+    }
+    // A leading tone in an outer voice must rise to the tonic when the next chord holds it.
+    // Inner voices are free to fall to the fifth.
+    let (tonic, leading_tone) = key;
+    if to.iter().any(|note| note.rem_euclid(12) == tonic) {
+        for voice in [0, 3] {
+            if from[voice].rem_euclid(12) == leading_tone && to[voice] - from[voice] != 1 {
+                violations.push(Violation {
+                    kind: ViolationType::UnresolvedLeadingTone,
+                    index,
+                    voices: vec![Voice::ALL[voice]],
+                });
+            }
+        }
+    }
+}
+
+/// Checks four part writing against the common practice rules taught in harmony classes. The
+/// leading tone is the raised seventh degree in both major and minor keys.
+pub fn check_satb(chords: &[FourPartChord], key: &Key) -> Result<Vec<Violation>> {
+    let tonic = key.tonic.pitch.pitch_class() as i8;
+    let leading_tone = (tonic + 11) % 12;
+    let mut violations = vec![];
+    let notes = chords.iter().map(midi).collect::<Result<Vec<[i8; 4]>>>()?;
+    for (i, chord) in notes.iter().enumerate() {
+        check_chord(i, chord, leading_tone, &mut violations);
+        if i > 0 {
+            check_motion(i, &notes[i - 1], chord, (tonic, leading_tone), &mut violations);
+        }
+    }
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn chord(voices: &str) -> FourPartChord {
+        let pitches: Vec<PitchOctave> = voices
+            .split_whitespace()
+            .map(|pitch| PitchOctave::from_str(pitch).unwrap())
+            .collect();
+        FourPartChord::new(pitches[0], pitches[1], pitches[2], pitches[3])
+    }
+
+    fn kinds(chords: &[FourPartChord]) -> Vec<ViolationType> {
+        let key = Key::from_str("C major").unwrap();
+        check_satb(chords, &key).unwrap().iter().map(|v| v.kind).collect()
+    }
+
+    #[test]
+    fn test_correct_cadence() {
+        // I IV V I with common tones held
+        let chords = [
+            chord("C5 G4 E4 C3"),
+            chord("C5 A4 F4 F3"),
+            chord("B4 G4 D4 G3"),
+            chord("C5 G4 E4 C3"),
+        ];
+        assert!(kinds(&chords).is_empty());
+    }
+
+    #[test]
+    fn test_violations() {
+        let key = Key::from_str("C major").unwrap();
+        let violations = check_satb(&[chord("C5 E4 G3 C3"), chord("D5 F4 A3 D3")], &key).unwrap();
+        assert!(violations.contains(&Violation {
+            kind: ViolationType::ParallelOctaves,
+            index: 1,
+            voices: vec![Voice::Soprano, Voice::Bass],
+        }));
+        assert!(violations.contains(&Violation {
+            kind: ViolationType::ParallelFifths,
+            index: 1,
+            voices: vec![Voice::Tenor, Voice::Bass],
+        }));
+
+        assert_eq!(
+            kinds(&[chord("B4 D4 D4 G2"), chord("G4 E4 C4 C3")]),
+            vec![ViolationType::UnresolvedLeadingTone]
+        );
+        assert_eq!(kinds(&[chord("B4 D4 B3 G2")]), vec![ViolationType::DoubledLeadingTone]);
+        assert_eq!(kinds(&[chord("E4 G4 C4 C3")]), vec![ViolationType::VoiceCrossing]);
+    }
+}
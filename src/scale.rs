@@ -1,9 +1,9 @@
-use crate::interval::{IntervalType, NumericIntervalsSlice};
+use crate::interval::{CentsIntervalsSlice, IntervalType, NumericIntervalsSlice};
 use crate::pitch::PitchOrder;
 use super::pitch::{PitchOctave, Pitches};
 use crate::notes::Notes;
 use crate::error::{Error, Result};
-use strum::EnumString;
+use strum::{EnumIter, EnumString};
 
 
 pub struct Scale {
@@ -28,12 +28,17 @@ impl Scale {
         }
     }
     fn gen_notes(&self) -> Result<Pitches> {
+        if let Some(cents) = self.kind.scale_cents() {
+            return self.gen_cents_notes(cents);
+        }
         let intervals = self.kind.scale_interval();
+        let steps = self.kind.scale_steps();
         let mut scale = Pitches(vec![self.tonic]);
         match self.pitch_order {
             PitchOrder::Ascending => {
-                for pitch in intervals {
-                    if let Some(scale_tone) = self.tonic.checked_add(IntervalType::from(*pitch)) {
+                for (pitch, step) in intervals.iter().zip(steps) {
+                    let interval = IntervalType::from_diatonic_semitones(*step, *pitch).ok_or(Error::Unsupported)?;
+                    if let Some(scale_tone) = self.tonic.checked_add(interval) {
                         scale.0.push(scale_tone);
                     } else {
                         return Err(Error::OutofBounds);
@@ -47,8 +52,16 @@ impl Scale {
                 }
             }
             PitchOrder::Descending => {
-                for pitch in intervals.iter().rev() {
-                    if let Some(scale_tone) = self.tonic.checked_sub(IntervalType::from(12 - *pitch)) {
+                let descending = self.kind.scale_interval_descending().unwrap_or(intervals);
+                let steps = self.kind.scale_steps_descending();
+                for (pitch, step) in descending.iter().zip(steps).rev() {
+                    // Descend by the inversion of the ascending interval so the spelling is preserved
+                    let interval = IntervalType::from_diatonic_semitones(
+                        IntervalType::EIGHTH_DIATONIC_INC - *step,
+                        12 - *pitch,
+                    )
+                    .ok_or(Error::Unsupported)?;
+                    if let Some(scale_tone) = self.tonic.checked_sub(interval) {
                         scale.0.push(scale_tone);
                     } else {
                         return Err(Error::OutofBounds);
@@ -68,6 +81,37 @@ impl Scale {
 
 
     }
+
+    fn gen_cents_notes(&self, ascending: &CentsIntervalsSlice) -> Result<Pitches> {
+        let mut scale = Pitches(vec![self.tonic]);
+        match self.pitch_order {
+            PitchOrder::Ascending => {
+                for (cents, step) in ascending.iter().zip(self.kind.scale_steps()) {
+                    let scale_tone = self.tonic.checked_add_cents(*step, *cents).ok_or(Error::OutofBounds)?;
+                    scale.0.push(scale_tone);
+                }
+                let octave = self.tonic.checked_add_cents(IntervalType::EIGHTH_DIATONIC_INC, Self::OCTAVE_CENTS);
+                scale.0.push(octave.ok_or(Error::OutofBounds)?);
+            }
+            PitchOrder::Descending => {
+                let descending = self.kind.scale_cents_descending().unwrap_or(ascending);
+                for (cents, step) in descending.iter().zip(self.kind.scale_steps_descending()).rev() {
+                    let scale_tone = self
+                        .tonic
+                        .checked_sub_cents(IntervalType::EIGHTH_DIATONIC_INC - *step, Self::OCTAVE_CENTS - *cents)
+                        .ok_or(Error::OutofBounds)?;
+                    scale.0.push(scale_tone);
+                }
+                let octave = self.tonic.checked_sub_cents(IntervalType::EIGHTH_DIATONIC_INC, Self::OCTAVE_CENTS);
+                scale.0.push(octave.ok_or(Error::OutofBounds)?);
+            }
+        }
+        Ok(scale)
+    }
+}
+
+impl Scale {
+    const OCTAVE_CENTS: u16 = 1200;
 }
 
 impl Notes for Scale {
@@ -76,7 +120,7 @@ impl Notes for Scale {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, EnumString)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, EnumString, EnumIter)]
 pub enum ScaleType {
     Chromatic,
     Blues,
@@ -89,9 +133,13 @@ pub enum ScaleType {
     DorianFlat2,
     DorianSharp4,
     Phrygian,
+    PhrygianDominant,
     Lydian,
     LydianMinor,
+    LydianDominant,
+    LydianAugmented,
     Mixolydian,
+    MixolydianFlat6,
     Aeolian,
     AeolianSharp7,
     MelodicMinor,
@@ -102,25 +150,428 @@ pub enum ScaleType {
     WholeTone,
     PentatonicMinor,
     PentatonicMajor,
+    HarmonicMinor,
+    HarmonicMajor,
+    DoubleHarmonic,
+    HungarianMinor,
+    NeapolitanMajor,
+    NeapolitanMinor,
+    Enigmatic,
+    BebopDominant,
+    BebopMajor,
+    BebopMinor,
+    BebopMelodicMinor,
+    DiminishedHalfWhole,
+    DiminishedWholeHalf,
+    MessiaenMode1,
+    MessiaenMode2,
+    MessiaenMode3,
+    MessiaenMode4,
+    MessiaenMode5,
+    MessiaenMode6,
+    MessiaenMode7,
+    MaqamRast,
+    MaqamBayati,
+    MaqamSikah,
+    MaqamHijaz,
+    MaqamSaba,
+    MaqamNahawand,
+    MaqamKurd,
+    MaqamAjam,
+    ThaatBilawal,
+    ThaatKhamaj,
+    ThaatKafi,
+    ThaatAsavari,
+    ThaatBhairavi,
+    ThaatBhairav,
+    ThaatKalyan,
+    ThaatMarwa,
+    ThaatPurvi,
+    ThaatTodi,
+    RagaYaman,
+    RagaBhupali,
+    RagaBhimpalasi,
+    RagaKhamaj,
+    RagaMalkauns,
+}
+
+// Approximates intervals in cents to the nearest semitone, quarter tones are rounded up
+const fn approximate_semitones<const N: usize>(cents: [u16; N]) -> [u8; N] {
+    let mut semitones = [0; N];
+    let mut i = 0;
+    while i < N {
+        semitones[i] = ((cents[i] + 50) / 100) as u8;
+        i += 1;
+    }
+    semitones
 }
 
 //type IntervalsSlice = [Interval];
 
 impl ScaleType {
+    const CHROMATIC_SCALE: [u8; 11] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+    const BLUES_SCALE: [u8; 5] = [3, 5, 6, 7, 10];
+    const AUGMENTED_SCALE: [u8; 5] = [3, 4, 7, 8, 11];
+    const ALTERED_SCALE: [u8; 6] = [1, 3, 4, 6, 8, 10];
+    const ALTERED_FLAT_7_SCALE: [u8; 6] = [1, 3, 4, 6, 8, 9];
     const IONIAN_SCALE: [u8; 6] = [2, 4, 5, 7, 9, 11];
-    const CHROMATIC_SCALE: [u8; 10] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+    const IONIAN_AUG_SCALE: [u8; 6] = [2, 4, 5, 8, 9, 11];
+    const DORIAN_SCALE: [u8; 6] = [2, 3, 5, 7, 9, 10];
+    const DORIAN_FLAT_2_SCALE: [u8; 6] = [1, 3, 5, 7, 9, 10];
+    const DORIAN_SHARP_4_SCALE: [u8; 6] = [2, 3, 6, 7, 9, 10];
+    const PHRYGIAN_SCALE: [u8; 6] = [1, 3, 5, 7, 8, 10];
+    const PHRYGIAN_DOMINANT_SCALE: [u8; 6] = [1, 4, 5, 7, 8, 10];
+    const LYDIAN_SCALE: [u8; 6] = [2, 4, 6, 7, 9, 11];
+    const LYDIAN_MINOR_SCALE: [u8; 6] = [2, 4, 6, 7, 8, 10];
+    const LYDIAN_DOMINANT_SCALE: [u8; 6] = [2, 4, 6, 7, 9, 10];
+    const LYDIAN_AUGMENTED_SCALE: [u8; 6] = [2, 4, 6, 8, 9, 11];
+    const MIXOLYDIAN_SCALE: [u8; 6] = [2, 4, 5, 7, 9, 10];
+    const MIXOLYDIAN_FLAT_6_SCALE: [u8; 6] = [2, 4, 5, 7, 8, 10];
     const AEOLIAN_SCALE: [u8; 6] = [2, 3, 5, 7, 8, 10];
+    const AEOLIAN_SHARP_7_SCALE: [u8; 6] = [2, 3, 5, 7, 8, 11];
+    const MELODIC_MINOR_SCALE: [u8; 6] = [2, 3, 5, 7, 9, 11];
+    // Classical melodic minor descends as the natural minor
+    const MELODIC_MINOR_DESC_SCALE: [u8; 6] = Self::AEOLIAN_SCALE;
+    const LOCRIAN_SCALE: [u8; 6] = [1, 3, 5, 6, 8, 10];
+    const LOCRIAN_TWO_SCALE: [u8; 6] = [2, 3, 5, 6, 8, 10];
+    const LOCRIAN_SIX_SCALE: [u8; 6] = [1, 3, 5, 6, 9, 10];
+    const LEADING_WHOLE_TONE_SCALE: [u8; 6] = [2, 4, 6, 8, 10, 11];
+    const WHOLE_TONE_SCALE: [u8; 5] = [2, 4, 6, 8, 10];
+    const PENTATONIC_MINOR_SCALE: [u8; 4] = [3, 5, 7, 10];
+    const PENTATONIC_MAJOR_SCALE: [u8; 4] = [2, 4, 7, 9];
+    const HARMONIC_MINOR_SCALE: [u8; 6] = [2, 3, 5, 7, 8, 11];
+    const HARMONIC_MAJOR_SCALE: [u8; 6] = [2, 4, 5, 7, 8, 11];
+    const DOUBLE_HARMONIC_SCALE: [u8; 6] = [1, 4, 5, 7, 8, 11];
+    const HUNGARIAN_MINOR_SCALE: [u8; 6] = [2, 3, 6, 7, 8, 11];
+    const NEAPOLITAN_MAJOR_SCALE: [u8; 6] = [1, 3, 5, 7, 9, 11];
+    const NEAPOLITAN_MINOR_SCALE: [u8; 6] = [1, 3, 5, 7, 8, 11];
+    const ENIGMATIC_SCALE: [u8; 6] = [1, 4, 6, 8, 10, 11];
+    const BEBOP_DOMINANT_SCALE: [u8; 7] = [2, 4, 5, 7, 9, 10, 11];
+    const BEBOP_MAJOR_SCALE: [u8; 7] = [2, 4, 5, 7, 8, 9, 11];
+    const BEBOP_MINOR_SCALE: [u8; 7] = [2, 3, 4, 5, 7, 9, 10];
+    const BEBOP_MELODIC_MINOR_SCALE: [u8; 7] = [2, 3, 5, 7, 8, 9, 11];
+    const DIMINISHED_HALF_WHOLE_SCALE: [u8; 7] = [1, 3, 4, 6, 7, 9, 10];
+    const DIMINISHED_WHOLE_HALF_SCALE: [u8; 7] = [2, 3, 5, 6, 8, 9, 11];
+    // Messiaen's modes of limited transposition, the first two are the whole tone and octatonic scales
+    const MESSIAEN_MODE_3_SCALE: [u8; 8] = [2, 3, 4, 6, 7, 8, 10, 11];
+    const MESSIAEN_MODE_4_SCALE: [u8; 7] = [1, 2, 5, 6, 7, 8, 11];
+    const MESSIAEN_MODE_5_SCALE: [u8; 5] = [1, 5, 6, 7, 11];
+    const MESSIAEN_MODE_6_SCALE: [u8; 7] = [2, 4, 5, 6, 8, 10, 11];
+    const MESSIAEN_MODE_7_SCALE: [u8; 9] = [1, 2, 3, 5, 6, 7, 8, 9, 11];
+    const THAAT_MARWA_SCALE: [u8; 6] = [1, 4, 6, 7, 9, 11];
+    const THAAT_PURVI_SCALE: [u8; 6] = [1, 4, 6, 7, 8, 11];
+    const THAAT_TODI_SCALE: [u8; 6] = [1, 3, 6, 7, 8, 11];
+
+    // Maqamat and ragas are defined in cents, with a separate descending form (avaroha) where it
+    // differs from the ascending one (aroha). Quarter tones are 50 cents.
+    const MAQAM_RAST_CENTS: [u16; 6] = [200, 350, 500, 700, 900, 1050];
+    const MAQAM_RAST_DESC_CENTS: [u16; 6] = [200, 350, 500, 700, 900, 1000];
+    const MAQAM_BAYATI_CENTS: [u16; 6] = [150, 300, 500, 700, 800, 1000];
+    const MAQAM_SIKAH_CENTS: [u16; 6] = [150, 350, 550, 700, 850, 1050];
+    const MAQAM_HIJAZ_CENTS: [u16; 6] = [100, 400, 500, 700, 800, 1000];
+    const MAQAM_SABA_CENTS: [u16; 6] = [150, 300, 400, 700, 800, 1000];
+    const MAQAM_NAHAWAND_CENTS: [u16; 6] = [200, 300, 500, 700, 800, 1100];
+    const MAQAM_NAHAWAND_DESC_CENTS: [u16; 6] = [200, 300, 500, 700, 800, 1000];
+    const MAQAM_KURD_CENTS: [u16; 6] = [100, 300, 500, 700, 800, 1000];
+    const MAQAM_AJAM_CENTS: [u16; 6] = [200, 400, 500, 700, 900, 1100];
+    const RAGA_YAMAN_CENTS: [u16; 5] = [200, 400, 600, 900, 1100];
+    const RAGA_YAMAN_DESC_CENTS: [u16; 6] = [200, 400, 600, 700, 900, 1100];
+    const RAGA_BHUPALI_CENTS: [u16; 4] = [200, 400, 700, 900];
+    const RAGA_BHIMPALASI_CENTS: [u16; 4] = [300, 500, 700, 1000];
+    const RAGA_BHIMPALASI_DESC_CENTS: [u16; 6] = [200, 300, 500, 700, 900, 1000];
+    const RAGA_KHAMAJ_CENTS: [u16; 5] = [400, 500, 700, 900, 1100];
+    const RAGA_KHAMAJ_DESC_CENTS: [u16; 6] = [200, 400, 500, 700, 900, 1000];
+    const RAGA_MALKAUNS_CENTS: [u16; 4] = [300, 500, 800, 1000];
 
+    const MAQAM_RAST_SCALE: [u8; 6] = approximate_semitones(Self::MAQAM_RAST_CENTS);
+    const MAQAM_BAYATI_SCALE: [u8; 6] = approximate_semitones(Self::MAQAM_BAYATI_CENTS);
+    const MAQAM_SIKAH_SCALE: [u8; 6] = approximate_semitones(Self::MAQAM_SIKAH_CENTS);
+    const MAQAM_HIJAZ_SCALE: [u8; 6] = approximate_semitones(Self::MAQAM_HIJAZ_CENTS);
+    const MAQAM_SABA_SCALE: [u8; 6] = approximate_semitones(Self::MAQAM_SABA_CENTS);
+    const MAQAM_NAHAWAND_SCALE: [u8; 6] = approximate_semitones(Self::MAQAM_NAHAWAND_CENTS);
+    const MAQAM_KURD_SCALE: [u8; 6] = approximate_semitones(Self::MAQAM_KURD_CENTS);
+    const MAQAM_AJAM_SCALE: [u8; 6] = approximate_semitones(Self::MAQAM_AJAM_CENTS);
+    const RAGA_YAMAN_SCALE: [u8; 5] = approximate_semitones(Self::RAGA_YAMAN_CENTS);
+    const RAGA_BHUPALI_SCALE: [u8; 4] = approximate_semitones(Self::RAGA_BHUPALI_CENTS);
+    const RAGA_BHIMPALASI_SCALE: [u8; 4] = approximate_semitones(Self::RAGA_BHIMPALASI_CENTS);
+    const RAGA_KHAMAJ_SCALE: [u8; 5] = approximate_semitones(Self::RAGA_KHAMAJ_CENTS);
+    const RAGA_MALKAUNS_SCALE: [u8; 4] = approximate_semitones(Self::RAGA_MALKAUNS_CENTS);
 
-    fn scale_interval(&self) -> &NumericIntervalsSlice {
+    // Diatonic steps above the tonic of each scale tone, which determine the letter each tone is
+    // spelled with. Seven note scales use one letter per degree.
+    const HEPTATONIC_STEPS: [u8; 6] = [1, 2, 3, 4, 5, 6];
+    const CHROMATIC_STEPS: [u8; 11] = [1, 1, 2, 2, 3, 3, 4, 5, 5, 6, 6];
+    const BLUES_STEPS: [u8; 5] = [2, 3, 4, 4, 6];
+    const AUGMENTED_STEPS: [u8; 5] = [2, 2, 4, 5, 6];
+    const WHOLE_TONE_STEPS: [u8; 5] = [1, 2, 3, 4, 5];
+    const PENTATONIC_MINOR_STEPS: [u8; 4] = [2, 3, 4, 6];
+    const PENTATONIC_MAJOR_STEPS: [u8; 4] = [1, 2, 4, 5];
+    const BEBOP_DOMINANT_STEPS: [u8; 7] = [1, 2, 3, 4, 5, 6, 6];
+    const BEBOP_MAJOR_STEPS: [u8; 7] = [1, 2, 3, 4, 4, 5, 6];
+    const BEBOP_MINOR_STEPS: [u8; 7] = [1, 2, 2, 3, 4, 5, 6];
+    const BEBOP_MELODIC_MINOR_STEPS: [u8; 7] = [1, 2, 3, 4, 4, 5, 6];
+    const DIMINISHED_HALF_WHOLE_STEPS: [u8; 7] = [1, 2, 2, 3, 4, 5, 6];
+    const DIMINISHED_WHOLE_HALF_STEPS: [u8; 7] = [1, 2, 3, 3, 4, 5, 6];
+    const MESSIAEN_MODE_3_STEPS: [u8; 8] = [1, 2, 2, 3, 4, 5, 6, 6];
+    const MESSIAEN_MODE_4_STEPS: [u8; 7] = [1, 1, 3, 3, 4, 5, 6];
+    const MESSIAEN_MODE_5_STEPS: [u8; 5] = [1, 3, 3, 4, 6];
+    const MESSIAEN_MODE_6_STEPS: [u8; 7] = [1, 2, 3, 3, 4, 5, 6];
+    const MESSIAEN_MODE_7_STEPS: [u8; 9] = [1, 1, 2, 3, 3, 4, 5, 5, 6];
+    const RAGA_YAMAN_STEPS: [u8; 5] = [1, 2, 3, 5, 6];
+    const RAGA_KHAMAJ_STEPS: [u8; 5] = [2, 3, 4, 5, 6];
+    const RAGA_MALKAUNS_STEPS: [u8; 4] = [2, 3, 5, 6];
+
+    /// Returns the numeric intervals of the scale above the tonic, excluding the tonic and its octave
+    pub fn scale_interval(&self) -> &NumericIntervalsSlice {
         match self {
-            ScaleType::Ionian => &Self::IONIAN_SCALE,
             ScaleType::Chromatic => &Self::CHROMATIC_SCALE,
+            ScaleType::Blues => &Self::BLUES_SCALE,
+            ScaleType::Augmented => &Self::AUGMENTED_SCALE,
+            ScaleType::Altered => &Self::ALTERED_SCALE,
+            ScaleType::AlteredFlat7 => &Self::ALTERED_FLAT_7_SCALE,
+            ScaleType::Ionian => &Self::IONIAN_SCALE,
+            ScaleType::IonianAug => &Self::IONIAN_AUG_SCALE,
+            ScaleType::Dorian => &Self::DORIAN_SCALE,
+            ScaleType::DorianFlat2 => &Self::DORIAN_FLAT_2_SCALE,
+            ScaleType::DorianSharp4 => &Self::DORIAN_SHARP_4_SCALE,
+            ScaleType::Phrygian => &Self::PHRYGIAN_SCALE,
+            ScaleType::PhrygianDominant => &Self::PHRYGIAN_DOMINANT_SCALE,
+            ScaleType::Lydian => &Self::LYDIAN_SCALE,
+            ScaleType::LydianMinor => &Self::LYDIAN_MINOR_SCALE,
+            ScaleType::LydianDominant => &Self::LYDIAN_DOMINANT_SCALE,
+            ScaleType::LydianAugmented => &Self::LYDIAN_AUGMENTED_SCALE,
+            ScaleType::Mixolydian => &Self::MIXOLYDIAN_SCALE,
+            ScaleType::MixolydianFlat6 => &Self::MIXOLYDIAN_FLAT_6_SCALE,
             ScaleType::Aeolian => &Self::AEOLIAN_SCALE,
-            _ => &Self::IONIAN_SCALE,
+            ScaleType::AeolianSharp7 => &Self::AEOLIAN_SHARP_7_SCALE,
+            ScaleType::MelodicMinor => &Self::MELODIC_MINOR_SCALE,
+            ScaleType::Locrian => &Self::LOCRIAN_SCALE,
+            ScaleType::LocrianTwo => &Self::LOCRIAN_TWO_SCALE,
+            ScaleType::LocrianSix => &Self::LOCRIAN_SIX_SCALE,
+            ScaleType::LeadingWholeTone => &Self::LEADING_WHOLE_TONE_SCALE,
+            ScaleType::WholeTone => &Self::WHOLE_TONE_SCALE,
+            ScaleType::PentatonicMinor => &Self::PENTATONIC_MINOR_SCALE,
+            ScaleType::PentatonicMajor => &Self::PENTATONIC_MAJOR_SCALE,
+            ScaleType::HarmonicMinor => &Self::HARMONIC_MINOR_SCALE,
+            ScaleType::HarmonicMajor => &Self::HARMONIC_MAJOR_SCALE,
+            ScaleType::DoubleHarmonic => &Self::DOUBLE_HARMONIC_SCALE,
+            ScaleType::HungarianMinor => &Self::HUNGARIAN_MINOR_SCALE,
+            ScaleType::NeapolitanMajor => &Self::NEAPOLITAN_MAJOR_SCALE,
+            ScaleType::NeapolitanMinor => &Self::NEAPOLITAN_MINOR_SCALE,
+            ScaleType::Enigmatic => &Self::ENIGMATIC_SCALE,
+            ScaleType::BebopDominant => &Self::BEBOP_DOMINANT_SCALE,
+            ScaleType::BebopMajor => &Self::BEBOP_MAJOR_SCALE,
+            ScaleType::BebopMinor => &Self::BEBOP_MINOR_SCALE,
+            ScaleType::BebopMelodicMinor => &Self::BEBOP_MELODIC_MINOR_SCALE,
+            ScaleType::DiminishedHalfWhole => &Self::DIMINISHED_HALF_WHOLE_SCALE,
+            ScaleType::DiminishedWholeHalf => &Self::DIMINISHED_WHOLE_HALF_SCALE,
+            ScaleType::MessiaenMode1 => &Self::WHOLE_TONE_SCALE,
+            ScaleType::MessiaenMode2 => &Self::DIMINISHED_HALF_WHOLE_SCALE,
+            ScaleType::MessiaenMode3 => &Self::MESSIAEN_MODE_3_SCALE,
+            ScaleType::MessiaenMode4 => &Self::MESSIAEN_MODE_4_SCALE,
+            ScaleType::MessiaenMode5 => &Self::MESSIAEN_MODE_5_SCALE,
+            ScaleType::MessiaenMode6 => &Self::MESSIAEN_MODE_6_SCALE,
+            ScaleType::MessiaenMode7 => &Self::MESSIAEN_MODE_7_SCALE,
+            ScaleType::MaqamRast => &Self::MAQAM_RAST_SCALE,
+            ScaleType::MaqamBayati => &Self::MAQAM_BAYATI_SCALE,
+            ScaleType::MaqamSikah => &Self::MAQAM_SIKAH_SCALE,
+            ScaleType::MaqamHijaz => &Self::MAQAM_HIJAZ_SCALE,
+            ScaleType::MaqamSaba => &Self::MAQAM_SABA_SCALE,
+            ScaleType::MaqamNahawand => &Self::MAQAM_NAHAWAND_SCALE,
+            ScaleType::MaqamKurd => &Self::MAQAM_KURD_SCALE,
+            ScaleType::MaqamAjam => &Self::MAQAM_AJAM_SCALE,
+            ScaleType::ThaatBilawal => &Self::IONIAN_SCALE,
+            ScaleType::ThaatKhamaj => &Self::MIXOLYDIAN_SCALE,
+            ScaleType::ThaatKafi => &Self::DORIAN_SCALE,
+            ScaleType::ThaatAsavari => &Self::AEOLIAN_SCALE,
+            ScaleType::ThaatBhairavi => &Self::PHRYGIAN_SCALE,
+            ScaleType::ThaatBhairav => &Self::DOUBLE_HARMONIC_SCALE,
+            ScaleType::ThaatKalyan => &Self::LYDIAN_SCALE,
+            ScaleType::ThaatMarwa => &Self::THAAT_MARWA_SCALE,
+            ScaleType::ThaatPurvi => &Self::THAAT_PURVI_SCALE,
+            ScaleType::ThaatTodi => &Self::THAAT_TODI_SCALE,
+            ScaleType::RagaYaman => &Self::RAGA_YAMAN_SCALE,
+            ScaleType::RagaBhupali => &Self::RAGA_BHUPALI_SCALE,
+            ScaleType::RagaBhimpalasi => &Self::RAGA_BHIMPALASI_SCALE,
+            ScaleType::RagaKhamaj => &Self::RAGA_KHAMAJ_SCALE,
+            ScaleType::RagaMalkauns => &Self::RAGA_MALKAUNS_SCALE,
+        }
+    }
+
+    /// Returns the descending form, for scales whose descent differs from their ascent
+    pub fn scale_interval_descending(&self) -> Option<&NumericIntervalsSlice> {
+        match self {
+            ScaleType::MelodicMinor => Some(&Self::MELODIC_MINOR_DESC_SCALE),
+            _ => None,
+        }
+    }
+
+    pub fn has_distinct_descending(&self) -> bool {
+        self.scale_interval_descending().is_some() || self.scale_cents_descending().is_some()
+    }
+
+    /// Returns the ascending intervals in cents for scales defined outside of 12-TET semitones.
+    /// For these scales `scale_interval` returns a 12-TET approximation.
+    pub fn scale_cents(&self) -> Option<&CentsIntervalsSlice> {
+        match self {
+            ScaleType::MaqamRast => Some(&Self::MAQAM_RAST_CENTS),
+            ScaleType::MaqamBayati => Some(&Self::MAQAM_BAYATI_CENTS),
+            ScaleType::MaqamSikah => Some(&Self::MAQAM_SIKAH_CENTS),
+            ScaleType::MaqamHijaz => Some(&Self::MAQAM_HIJAZ_CENTS),
+            ScaleType::MaqamSaba => Some(&Self::MAQAM_SABA_CENTS),
+            ScaleType::MaqamNahawand => Some(&Self::MAQAM_NAHAWAND_CENTS),
+            ScaleType::MaqamKurd => Some(&Self::MAQAM_KURD_CENTS),
+            ScaleType::MaqamAjam => Some(&Self::MAQAM_AJAM_CENTS),
+            ScaleType::RagaYaman => Some(&Self::RAGA_YAMAN_CENTS),
+            ScaleType::RagaBhupali => Some(&Self::RAGA_BHUPALI_CENTS),
+            ScaleType::RagaBhimpalasi => Some(&Self::RAGA_BHIMPALASI_CENTS),
+            ScaleType::RagaKhamaj => Some(&Self::RAGA_KHAMAJ_CENTS),
+            ScaleType::RagaMalkauns => Some(&Self::RAGA_MALKAUNS_CENTS),
+            _ => None,
+        }
+    }
+
+    /// Returns the descending form in cents, for scales whose descent differs from their ascent
+    pub fn scale_cents_descending(&self) -> Option<&CentsIntervalsSlice> {
+        match self {
+            ScaleType::MaqamRast => Some(&Self::MAQAM_RAST_DESC_CENTS),
+            ScaleType::MaqamNahawand => Some(&Self::MAQAM_NAHAWAND_DESC_CENTS),
+            ScaleType::RagaYaman => Some(&Self::RAGA_YAMAN_DESC_CENTS),
+            ScaleType::RagaBhimpalasi => Some(&Self::RAGA_BHIMPALASI_DESC_CENTS),
+            ScaleType::RagaKhamaj => Some(&Self::RAGA_KHAMAJ_DESC_CENTS),
+            _ => None,
+        }
+    }
+
+    /// Returns the diatonic steps above the tonic for each interval returned by `scale_interval`
+    pub fn scale_steps(&self) -> &NumericIntervalsSlice {
+        match self {
+            ScaleType::Chromatic => &Self::CHROMATIC_STEPS,
+            ScaleType::Blues => &Self::BLUES_STEPS,
+            ScaleType::Augmented => &Self::AUGMENTED_STEPS,
+            ScaleType::WholeTone => &Self::WHOLE_TONE_STEPS,
+            ScaleType::PentatonicMinor => &Self::PENTATONIC_MINOR_STEPS,
+            ScaleType::PentatonicMajor => &Self::PENTATONIC_MAJOR_STEPS,
+            ScaleType::BebopDominant => &Self::BEBOP_DOMINANT_STEPS,
+            ScaleType::BebopMajor => &Self::BEBOP_MAJOR_STEPS,
+            ScaleType::BebopMinor => &Self::BEBOP_MINOR_STEPS,
+            ScaleType::BebopMelodicMinor => &Self::BEBOP_MELODIC_MINOR_STEPS,
+            ScaleType::DiminishedHalfWhole => &Self::DIMINISHED_HALF_WHOLE_STEPS,
+            ScaleType::DiminishedWholeHalf => &Self::DIMINISHED_WHOLE_HALF_STEPS,
+            ScaleType::MessiaenMode1 => &Self::WHOLE_TONE_STEPS,
+            ScaleType::MessiaenMode2 => &Self::DIMINISHED_HALF_WHOLE_STEPS,
+            ScaleType::MessiaenMode3 => &Self::MESSIAEN_MODE_3_STEPS,
+            ScaleType::MessiaenMode4 => &Self::MESSIAEN_MODE_4_STEPS,
+            ScaleType::MessiaenMode5 => &Self::MESSIAEN_MODE_5_STEPS,
+            ScaleType::MessiaenMode6 => &Self::MESSIAEN_MODE_6_STEPS,
+            ScaleType::MessiaenMode7 => &Self::MESSIAEN_MODE_7_STEPS,
+            ScaleType::RagaYaman => &Self::RAGA_YAMAN_STEPS,
+            ScaleType::RagaBhupali => &Self::PENTATONIC_MAJOR_STEPS,
+            ScaleType::RagaBhimpalasi => &Self::PENTATONIC_MINOR_STEPS,
+            ScaleType::RagaKhamaj => &Self::RAGA_KHAMAJ_STEPS,
+            ScaleType::RagaMalkauns => &Self::RAGA_MALKAUNS_STEPS,
+            _ => &Self::HEPTATONIC_STEPS,
+        }
+    }
+
+    /// Returns the diatonic steps of the descending form
+    pub fn scale_steps_descending(&self) -> &NumericIntervalsSlice {
+        match self {
+            ScaleType::RagaYaman | ScaleType::RagaBhimpalasi | ScaleType::RagaKhamaj => &Self::HEPTATONIC_STEPS,
+            _ => self.scale_steps(),
         }
     }
     // pub fn get_intervals(scale_type: ScaleType) -> &'static IntervalsSlice {
     //     return &Self::IONIAN_SCALE;
     // }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use strum::IntoEnumIterator;
+
+    fn scale_names(tonic: &str, kind: ScaleType, pitch_order: PitchOrder) -> Vec<String> {
+        let scale = Scale::new_with_order(PitchOctave::from_str(tonic).unwrap(), kind, pitch_order);
+        scale.notes().unwrap().0.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_chromatic_scale() {
+        let scale = Scale::new(PitchOctave::from_str("C4").unwrap(), ScaleType::Chromatic);
+        let notes = scale.notes().unwrap();
+        assert_eq!(notes.0.len(), 13);
+        assert_eq!(notes.0[1].to_string(), "Db4");
+    }
+
+    #[test]
+    fn test_every_scale_is_spelled() {
+        for kind in ScaleType::iter() {
+            assert_eq!(kind.scale_interval().len(), kind.scale_steps().len(), "{:?}", kind);
+            if let Some(descending) = kind.scale_interval_descending() {
+                assert_eq!(descending.len(), kind.scale_steps_descending().len(), "{:?}", kind);
+            }
+            if let Some(descending) = kind.scale_cents_descending() {
+                assert_eq!(descending.len(), kind.scale_steps_descending().len(), "{:?}", kind);
+            }
+            Scale::new(PitchOctave::from_str("C3").unwrap(), kind).notes().unwrap();
+            Scale::new_with_order(PitchOctave::from_str("C4").unwrap(), kind, PitchOrder::Descending)
+                .notes()
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_scale_spelling() {
+        assert_eq!(
+            scale_names("G#3", ScaleType::HarmonicMinor, PitchOrder::Ascending),
+            vec!["G#3", "A#3", "B3", "C#4", "D#4", "E4", "F##4", "G#4"]
+        );
+        assert_eq!(
+            scale_names("C3", ScaleType::Locrian, PitchOrder::Ascending),
+            vec!["C3", "Db3", "Eb3", "F3", "Gb3", "Ab3", "Bb3", "C4"]
+        );
+        assert_eq!(
+            scale_names("G3", ScaleType::BebopDominant, PitchOrder::Descending),
+            vec!["G3", "F#3", "F3", "E3", "D3", "C3", "B2", "A2", "G2"]
+        );
+    }
+    #[test]
+    fn test_melodic_minor_descends_as_natural_minor() {
+        assert_eq!(
+            scale_names("A3", ScaleType::MelodicMinor, PitchOrder::Ascending),
+            vec!["A3", "B3", "C4", "D4", "E4", "F#4", "G#4", "A4"]
+        );
+        assert_eq!(
+            scale_names("A4", ScaleType::MelodicMinor, PitchOrder::Descending),
+            vec!["A4", "G4", "F4", "E4", "D4", "C4", "B3", "A3"]
+        );
+        assert!(ScaleType::MelodicMinor.has_distinct_descending());
+        assert!(!ScaleType::Dorian.has_distinct_descending());
+    }
+
+    #[test]
+    fn test_microtonal_scales() {
+        assert_eq!(
+            scale_names("C3", ScaleType::MaqamRast, PitchOrder::Ascending),
+            vec!["C3", "D3", "Ed3", "F3", "G3", "A3", "Bd3", "C4"]
+        );
+        assert_eq!(
+            scale_names("C4", ScaleType::MaqamRast, PitchOrder::Descending),
+            vec!["C4", "Bb3", "A3", "G3", "F3", "Ed3", "D3", "C3"]
+        );
+        assert_eq!(
+            scale_names("D3", ScaleType::MaqamSaba, PitchOrder::Ascending),
+            vec!["D3", "Ed3", "F3", "Gb3", "A3", "Bb3", "C4", "D4"]
+        );
+        assert_eq!(
+            scale_names("C3", ScaleType::RagaYaman, PitchOrder::Ascending),
+            vec!["C3", "D3", "E3", "F#3", "A3", "B3", "C4"]
+        );
+        assert_eq!(
+            scale_names("C4", ScaleType::RagaYaman, PitchOrder::Descending),
+            vec!["C4", "B3", "A3", "G3", "F#3", "E3", "D3", "C3"]
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::interval::NumericIntervalsSlice;
+use crate::scale::ScaleType;
+
+const OCTAVE_SEMITONES: u8 = 12;
+const PITCH_CLASS_MASK: u16 = 0xFFF;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaleSymmetry {
+    // Number of distinct transpositions, 12 for a scale with no transpositional symmetry
+    pub transpositions: u8,
+    // Transpositions in semitones that map the scale onto itself, always starting with 0
+    pub invariant_transpositions: Vec<u8>,
+    // Number of distinct modes obtained by starting the scale on each of its degrees
+    pub distinct_modes: usize,
+    // Whether some inversion of the scale maps it onto itself
+    pub inversionally_symmetric: bool,
+}
+
+/// Returns the scale as a 12 bit mask, bit 0 being the tonic
+pub fn interval_mask(intervals: &NumericIntervalsSlice) -> u16 {
+    intervals
+        .iter()
+        .fold(1, |mask, interval| mask | 1 << (interval % OCTAVE_SEMITONES))
+}
+
+fn transpose_mask(mask: u16, semitones: u8) -> u16 {
+    let semitones = semitones % OCTAVE_SEMITONES;
+    ((mask << semitones) | (mask >> (OCTAVE_SEMITONES - semitones))) & PITCH_CLASS_MASK
+}
+
+fn invert_mask(mask: u16) -> u16 {
+    (0..OCTAVE_SEMITONES)
+        .filter(|pc| mask & 1 << pc != 0)
+        .fold(0, |inverted, pc| inverted | 1 << ((OCTAVE_SEMITONES - pc) % OCTAVE_SEMITONES))
+}
+
+/// Returns the intervals of the mode starting on the given degree, where degree 0 is the tonic
+pub fn mode_intervals(intervals: &NumericIntervalsSlice, degree: usize) -> Vec<u8> {
+    let mut tones: Vec<u8> = std::iter::once(0).chain(intervals.iter().copied()).collect();
+    let degree = degree % tones.len();
+    let start = tones[degree];
+    tones.rotate_left(degree);
+    tones
+        .iter()
+        .skip(1)
+        .map(|tone| (tone + OCTAVE_SEMITONES - start) % OCTAVE_SEMITONES)
+        .collect()
+}
+
+pub fn symmetry(intervals: &NumericIntervalsSlice) -> ScaleSymmetry {
+    let mask = interval_mask(intervals);
+    let invariant_transpositions: Vec<u8> = (0..OCTAVE_SEMITONES)
+        .filter(|semitones| transpose_mask(mask, *semitones) == mask)
+        .collect();
+    // The smallest non zero invariant transposition is the period of the scale
+    let transpositions = invariant_transpositions
+        .get(1)
+        .copied()
+        .unwrap_or(OCTAVE_SEMITONES);
+
+    let mut modes: Vec<u16> = (0..=intervals.len())
+        .map(|degree| interval_mask(&mode_intervals(intervals, degree)))
+        .collect();
+    modes.sort_unstable();
+    modes.dedup();
+
+    let inverted = invert_mask(mask);
+    let inversionally_symmetric =
+        (0..OCTAVE_SEMITONES).any(|semitones| transpose_mask(inverted, semitones) == mask);
+
+    ScaleSymmetry {
+        transpositions,
+        invariant_transpositions,
+        distinct_modes: modes.len(),
+        inversionally_symmetric,
+    }
+}
+
+impl ScaleType {
+    /// Returns None for scales measured in cents, whose microtones a semitone mask can't hold
+    pub fn symmetry(&self) -> Option<ScaleSymmetry> {
+        if self.scale_cents().is_some() {
+            return None;
+        }
+        Some(symmetry(self.scale_interval()))
+    }
+
+    /// Returns true for scales with fewer than twelve distinct transpositions
+    pub fn is_limited_transposition(&self) -> bool {
+        self.symmetry().is_some_and(|symmetry| symmetry.transpositions < OCTAVE_SEMITONES)
+    }
+
+    pub fn mode_intervals(&self, degree: usize) -> Vec<u8> {
+        mode_intervals(self.scale_interval(), degree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messiaen_transpositions() {
+        let modes = [
+            ScaleType::MessiaenMode1,
+            ScaleType::MessiaenMode2,
+            ScaleType::MessiaenMode3,
+            ScaleType::MessiaenMode4,
+            ScaleType::MessiaenMode5,
+            ScaleType::MessiaenMode6,
+            ScaleType::MessiaenMode7,
+        ];
+        let transpositions: Vec<u8> = modes.iter().map(|mode| mode.symmetry().unwrap().transpositions).collect();
+        assert_eq!(transpositions, vec![2, 3, 4, 6, 6, 6, 6]);
+        let mode2 = ScaleType::MessiaenMode2.symmetry().unwrap();
+        assert_eq!(mode2.invariant_transpositions, vec![0, 3, 6, 9]);
+        assert_eq!(mode2.distinct_modes, 2);
+        assert!(!ScaleType::Ionian.is_limited_transposition());
+        // Scales in cents aren't reduced to semitones
+        assert_eq!(ScaleType::MaqamRast.symmetry(), None);
+        assert!(!ScaleType::MaqamRast.is_limited_transposition());
+    }
+
+    #[test]
+    fn test_modes_and_inversion() {
+        assert_eq!(ScaleType::Ionian.mode_intervals(1), ScaleType::Dorian.scale_interval());
+        assert_eq!(ScaleType::Ionian.symmetry().unwrap().distinct_modes, 7);
+        // The diatonic collection maps onto itself under inversion, harmonic minor does not
+        assert!(ScaleType::Dorian.symmetry().unwrap().inversionally_symmetric);
+        assert!(!ScaleType::HarmonicMinor.symmetry().unwrap().inversionally_symmetric);
+    }
+}
@@ -0,0 +1,31 @@
+use crate::chord::Chord;
+use crate::key::Key;
+use crate::meter::Measure;
+use crate::rhythm::Event;
+use num::rational::Ratio;
+
+/// A part of a score, in whatever format it was read from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Part {
+    pub id: String,
+    pub name: String,
+    // The first key signature in the part, if any
+    pub key: Option<Key>,
+    pub measures: Vec<Measure>,
+    // Chord symbols with their offsets in quarter note beats from the start of the part
+    pub harmonies: Vec<(Ratio<u32>, Chord)>,
+}
+
+impl Part {
+    /// Returns the events of every measure in order
+    pub fn events(&self) -> Vec<Event> {
+        self.measures.iter().flat_map(|measure| measure.events.iter().cloned()).collect()
+    }
+}
+
+/// The parts of a piece, with its title if the file gives one
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub title: Option<String>,
+    pub parts: Vec<Part>,
+}
@@ -0,0 +1,255 @@
+use crate::error::{Error, Result};
+use crate::pitch::Pitches;
+use crate::pitch_class::PitchClassSet;
+use std::fmt;
+
+const ROW_LENGTH: usize = 12;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RowFormType {
+    Prime,
+    Retrograde,
+    Inversion,
+    RetrogradeInversion,
+}
+
+/// A row form labelled relative to the first pitch class of the original row, so P0 is the row
+/// itself. Retrogrades take the label of the form they reverse, R3 being P3 backwards.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RowForm {
+    pub kind: RowFormType,
+    pub transposition: u8,
+}
+
+impl RowForm {
+    pub fn new(kind: RowFormType, transposition: u8) -> RowForm {
+        RowForm {
+            kind,
+            transposition: transposition % 12,
+        }
+    }
+}
+
+impl fmt::Display for RowForm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.kind {
+            RowFormType::Prime => "P",
+            RowFormType::Retrograde => "R",
+            RowFormType::Inversion => "I",
+            RowFormType::RetrogradeInversion => "RI",
+        };
+        write!(f, "{}{}", label, self.transposition)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ToneRow([u8; ROW_LENGTH]);
+
+impl ToneRow {
+    /// Creates a row from twelve pitch classes, each of which must appear exactly once
+    pub fn new(pitch_classes: &[u8]) -> Result<ToneRow> {
+        if pitch_classes.len() != ROW_LENGTH {
+            return Err(Error::InvalidToneRow);
+        }
+        let set = PitchClassSet::from_pitch_classes(pitch_classes);
+        if set.len() != ROW_LENGTH || pitch_classes.iter().any(|pc| *pc >= 12) {
+            return Err(Error::InvalidToneRow);
+        }
+        let mut row = [0; ROW_LENGTH];
+        row.copy_from_slice(pitch_classes);
+        Ok(ToneRow(row))
+    }
+
+    pub fn pitch_classes(&self) -> [u8; ROW_LENGTH] {
+        self.0
+    }
+
+    /// Returns the ordered intervals between successive pitch classes, mod 12
+    pub fn intervals(&self) -> [u8; ROW_LENGTH - 1] {
+        let mut intervals = [0; ROW_LENGTH - 1];
+        for (i, interval) in intervals.iter_mut().enumerate() {
+            *interval = (self.0[i + 1] + 12 - self.0[i]) % 12;
+        }
+        intervals
+    }
+
+    /// An all-interval row contains each of the eleven ordered intervals once
+    pub fn is_all_interval(&self) -> bool {
+        let intervals = PitchClassSet::from_pitch_classes(&self.intervals());
+        intervals.len() == ROW_LENGTH - 1 && !intervals.contains(0)
+    }
+
+    pub fn form(&self, form: RowForm) -> [u8; ROW_LENGTH] {
+        let first = self.0[0];
+        let mut pitch_classes = self.0.map(|pc| match form.kind {
+            RowFormType::Prime | RowFormType::Retrograde => (pc + form.transposition) % 12,
+            RowFormType::Inversion | RowFormType::RetrogradeInversion => {
+                (2 * first + 24 - pc + form.transposition) % 12
+            }
+        });
+        if matches!(form.kind, RowFormType::Retrograde | RowFormType::RetrogradeInversion) {
+            pitch_classes.reverse();
+        }
+        pitch_classes
+    }
+
+    /// Returns all 48 forms of the row, ordered P, R, I then RI
+    pub fn forms(&self) -> Vec<(RowForm, [u8; ROW_LENGTH])> {
+        let kinds = [
+            RowFormType::Prime,
+            RowFormType::Retrograde,
+            RowFormType::Inversion,
+            RowFormType::RetrogradeInversion,
+        ];
+        kinds
+            .iter()
+            .flat_map(|kind| (0..12).map(move |n| RowForm::new(*kind, n)))
+            .map(|form| (form, self.form(form)))
+            .collect()
+    }
+
+    /// Returns the label of a twelve note sequence if it is one of the forms of this row
+    pub fn identify(&self, pitch_classes: &[u8]) -> Option<RowForm> {
+        self.forms()
+            .into_iter()
+            .find(|(_, form)| form.as_slice() == pitch_classes)
+            .map(|(form, _)| form)
+    }
+
+    pub fn matrix(&self) -> RowMatrix {
+        let inversion = self.form(RowForm::new(RowFormType::Inversion, 0));
+        let first = self.0[0];
+        let mut rows = [[0; ROW_LENGTH]; ROW_LENGTH];
+        for (row, start) in rows.iter_mut().zip(inversion) {
+            *row = self.form(RowForm::new(RowFormType::Prime, (start + 12 - first) % 12));
+        }
+        RowMatrix { first, rows }
+    }
+
+    /// Returns the forms whose first hexachord completes the aggregate with the first hexachord
+    /// of P0. R0 is always included, so R combinatoriality is trivial for every row.
+    pub fn combinatorial_forms(&self) -> Vec<RowForm> {
+        let hexachord = |pitch_classes: &[u8]| PitchClassSet::from_pitch_classes(&pitch_classes[..6]);
+        let complement = hexachord(&self.0).complement();
+        self.forms()
+            .into_iter()
+            .filter(|(_, pitch_classes)| hexachord(pitch_classes) == complement)
+            .map(|(form, _)| form)
+            .collect()
+    }
+
+    pub fn is_combinatorial(&self, kind: RowFormType) -> bool {
+        self.combinatorial_forms().iter().any(|form| form.kind == kind)
+    }
+
+    /// A row is all-combinatorial when it is P, R, I and RI combinatorial
+    pub fn is_all_combinatorial(&self) -> bool {
+        [
+            RowFormType::Prime,
+            RowFormType::Retrograde,
+            RowFormType::Inversion,
+            RowFormType::RetrogradeInversion,
+        ]
+        .iter()
+        .all(|kind| self.is_combinatorial(*kind))
+    }
+}
+
+impl TryFrom<&Pitches> for ToneRow {
+    type Error = Error;
+
+    fn try_from(pitches: &Pitches) -> Result<Self> {
+        let pitch_classes: Vec<u8> = pitches.0.iter().map(|p| p.pitch.pitch_class()).collect();
+        ToneRow::new(&pitch_classes)
+    }
+}
+
+impl fmt::Display for ToneRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pitch_classes: Vec<String> = self.0.iter().map(|pc| pitch_class_name(*pc)).collect();
+        write!(f, "{}", pitch_classes.join(" "))
+    }
+}
+
+/// The 12x12 matrix of a row, with prime forms read left to right and inversions top to bottom
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RowMatrix {
+    first: u8,
+    pub rows: [[u8; ROW_LENGTH]; ROW_LENGTH],
+}
+
+impl RowMatrix {
+    fn label(&self, pc: u8) -> u8 {
+        (pc + 12 - self.first) % 12
+    }
+}
+
+impl fmt::Display for RowMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inversions: Vec<String> = self.rows[0]
+            .iter()
+            .map(|pc| format!("{:>4}", format!("I{}", self.label(*pc))))
+            .collect();
+        writeln!(f, "     {}", inversions.join(""))?;
+        for row in &self.rows {
+            let label = self.label(row[0]);
+            let pitch_classes: Vec<String> = row.iter().map(|pc| format!("{:>4}", pitch_class_name(*pc))).collect();
+            writeln!(f, "{:<4} {} {:>4}", format!("P{}", label), pitch_classes.join(""), format!("R{}", label))?;
+        }
+        let retrograde_inversions: Vec<String> = self.rows[0]
+            .iter()
+            .map(|pc| format!("{:>4}", format!("RI{}", self.label(*pc))))
+            .collect();
+        write!(f, "     {}", retrograde_inversions.join(""))
+    }
+}
+
+// Pitch classes 10 and 11 are conventionally written T and E
+fn pitch_class_name(pc: u8) -> String {
+    match pc {
+        10 => "T".to_string(),
+        11 => "E".to_string(),
+        _ => pc.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Schoenberg, Suite for Piano Op. 25
+    const OP_25: [u8; 12] = [4, 5, 7, 1, 6, 3, 8, 2, 11, 0, 9, 10];
+
+    #[test]
+    fn test_row_forms() {
+        let row = ToneRow::new(&OP_25).unwrap();
+        assert_eq!(row.forms().len(), 48);
+        assert_eq!(row.form(RowForm::new(RowFormType::Inversion, 0)), [4, 3, 1, 7, 2, 5, 0, 6, 9, 8, 11, 10]);
+        assert_eq!(row.form(RowForm::new(RowFormType::Retrograde, 2)), [0, 11, 2, 1, 4, 10, 5, 8, 3, 9, 7, 6]);
+        let matrix = row.matrix();
+        assert_eq!(matrix.rows[0], OP_25);
+        assert_eq!(matrix.rows.map(|r| r[0]), row.form(RowForm::new(RowFormType::Inversion, 0)));
+        assert_eq!(
+            row.identify(&row.form(RowForm::new(RowFormType::RetrogradeInversion, 7))).unwrap().to_string(),
+            "RI7"
+        );
+        assert_eq!(ToneRow::new(&[0, 1, 2]), Err(Error::InvalidToneRow));
+        assert_eq!(ToneRow::new(&[0; 12]), Err(Error::InvalidToneRow));
+    }
+
+    #[test]
+    fn test_combinatoriality_and_all_interval() {
+        // Berg, Lyric Suite
+        let lyric_suite = ToneRow::new(&[5, 4, 0, 9, 7, 2, 8, 1, 3, 6, 10, 11]).unwrap();
+        assert!(lyric_suite.is_all_interval());
+        assert!(!ToneRow::new(&OP_25).unwrap().is_all_interval());
+
+        // A chromatic hexachord row is all-combinatorial
+        let chromatic = ToneRow::new(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]).unwrap();
+        assert!(chromatic.is_all_combinatorial());
+        let forms: Vec<String> = chromatic.combinatorial_forms().iter().map(|f| f.to_string()).collect();
+        assert_eq!(forms, vec!["P6", "R0", "I11", "RI5"]);
+        // Its first hexachord is 6-32, which is also all-combinatorial
+        assert!(lyric_suite.is_all_combinatorial());
+    }
+}
@@ -0,0 +1,245 @@
+use crate::chord::{Chord, Inversion};
+use crate::error::{Error, Result};
+use crate::notes::Notes;
+use crate::pitch::{Octave, PitchOctave, Pitches};
+use std::cmp::Reverse;
+
+const INVERSIONS: [Inversion; 5] = [
+    Inversion::Root,
+    Inversion::First,
+    Inversion::Second,
+    Inversion::Third,
+    Inversion::Fourth,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VoiceMotion {
+    pub from: PitchOctave,
+    pub to: PitchOctave,
+    // Signed distance moved, positive upwards
+    pub semitones: i8,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VoiceLeadingOptions {
+    /// Prefer voicings that hold common tones in the same voice over smaller total movement
+    pub keep_common_tones: bool,
+    /// Lowest and highest notes any voice may sound
+    pub range: Option<(PitchOctave, PitchOctave)>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VoicedChord {
+    pub chord: Chord,
+    pub pitches: Pitches,
+    // One entry per voice of this chord, empty for the first chord of a sequence
+    pub motion: Vec<VoiceMotion>,
+}
+
+impl VoicedChord {
+    /// Total distance moved by all voices, in semitones
+    pub fn total_motion(&self) -> u32 {
+        self.motion.iter().map(|motion| motion.semitones.unsigned_abs() as u32).sum()
+    }
+
+    /// Number of voices holding their note from the previous chord
+    pub fn common_tones(&self) -> usize {
+        self.motion.iter().filter(|motion| motion.semitones == 0).count()
+    }
+}
+
+fn semitones(pitch: &PitchOctave) -> Result<i8> {
+    pitch.get_semitone_value()
+}
+
+fn shift_octaves(pitches: &Pitches, octaves: i8) -> Option<Pitches> {
+    pitches
+        .0
+        .iter()
+        .map(|pitch| {
+            let octave = pitch.octave as i8 + octaves;
+            if !(0..=8).contains(&octave) {
+                return None;
+            }
+            let shifted = PitchOctave::new(pitch.pitch, Octave::from(octave as u8));
+            shifted.get_semitone_value().ok().map(|_| shifted)
+        })
+        .collect::<Option<Vec<PitchOctave>>>()
+        .map(Pitches)
+}
+
+fn in_range(pitches: &Pitches, range: Option<(PitchOctave, PitchOctave)>) -> Result<bool> {
+    let Some((low, high)) = range else {
+        return Ok(true);
+    };
+    let (low, high) = (semitones(&low)?, semitones(&high)?);
+    for pitch in &pitches.0 {
+        if !(low..=high).contains(&semitones(pitch)?) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Every inversion of the chord in every octave that fits the range
+fn candidate_voicings(chord: &Chord, range: Option<(PitchOctave, PitchOctave)>) -> Result<Vec<(Chord, Pitches)>> {
+    let mut candidates = vec![];
+    let inversions = INVERSIONS.iter().take(chord.kind.root_chord_interval().len() + 1);
+    for inversion in inversions {
+        let inverted = Chord::new_as_inversion(chord.root, chord.kind, *inversion);
+        let Ok(pitches) = inverted.notes() else {
+            continue;
+        };
+        for octaves in -8..=8 {
+            // The root of an inversion sits below its bass, so may leave the note range first
+            let root = shift_octaves(&Pitches(vec![chord.root]), octaves);
+            if let (Some(root), Some(shifted)) = (root, shift_octaves(&pitches, octaves)) {
+                if in_range(&shifted, range)? {
+                    candidates.push((Chord::new_as_inversion(root.0[0], chord.kind, *inversion), shifted));
+                }
+            }
+        }
+    }
+    Ok(candidates)
+}
+
+// Voices are paired from the bass up. When the chords have different sizes, each extra voice is
+// paired with the nearest note of the other chord.
+fn voice_motion(from: &Pitches, to: &Pitches) -> Result<Vec<VoiceMotion>> {
+    let mut motion = vec![];
+    for (i, pitch) in to.0.iter().enumerate() {
+        let target = semitones(pitch)?;
+        let source = match from.0.get(i) {
+            Some(source) => *source,
+            None => {
+                let mut nearest = None;
+                for source in &from.0 {
+                    let distance = (semitones(source)? - target).abs();
+                    if nearest.map(|(d, _)| distance < d).unwrap_or(true) {
+                        nearest = Some((distance, *source));
+                    }
+                }
+                nearest.ok_or(Error::OutofBounds)?.1
+            }
+        };
+        motion.push(VoiceMotion {
+            from: source,
+            to: *pitch,
+            semitones: target - semitones(&source)?,
+        });
+    }
+    // Voices of the previous chord left without a partner still have to move somewhere
+    for source in from.0.iter().skip(to.0.len()) {
+        let origin = semitones(source)?;
+        let mut nearest = None;
+        for pitch in &to.0 {
+            let distance = semitones(pitch)? - origin;
+            if nearest.map(|(d, _): (i8, PitchOctave)| distance.abs() < d.abs()).unwrap_or(true) {
+                nearest = Some((distance, *pitch));
+            }
+        }
+        let (distance, pitch) = nearest.ok_or(Error::OutofBounds)?;
+        motion.push(VoiceMotion {
+            from: *source,
+            to: pitch,
+            semitones: distance,
+        });
+    }
+    Ok(motion)
+}
+
+impl Chord {
+    /// Picks the inversion and octave of this chord that moves least from the previous voicing
+    pub fn voice_lead_from(&self, previous: &Pitches, options: &VoiceLeadingOptions) -> Result<VoicedChord> {
+        let mut best: Option<VoicedChord> = None;
+        for (chord, pitches) in candidate_voicings(self, options.range)? {
+            let motion = voice_motion(previous, &pitches)?;
+            let candidate = VoicedChord { chord, pitches, motion };
+            let is_better = match &best {
+                None => true,
+                Some(best) => {
+                    let key = |voiced: &VoicedChord| {
+                        let held = if options.keep_common_tones { voiced.common_tones() } else { 0 };
+                        (Reverse(held), voiced.total_motion())
+                    };
+                    key(&candidate) < key(best)
+                }
+            };
+            if is_better {
+                best = Some(candidate);
+            }
+        }
+        // The motion only covers this chord's voices, the previous chord's extra voices are dropped
+        best.map(|mut best| {
+            best.motion.truncate(best.pitches.0.len());
+            best
+        })
+        .ok_or(Error::OutofBounds)
+    }
+}
+
+/// Voices a chord sequence, keeping the first chord as given and leading each following chord
+/// from the voicing chosen before it
+pub fn voice_lead(chords: &[Chord], options: &VoiceLeadingOptions) -> Result<Vec<VoicedChord>> {
+    let mut voiced: Vec<VoicedChord> = vec![];
+    for chord in chords {
+        let next = match voiced.last() {
+            Some(previous) => chord.voice_lead_from(&previous.pitches, options)?,
+            None => {
+                let pitches = chord.notes()?;
+                if !in_range(&pitches, options.range)? {
+                    return Err(Error::OutofBounds);
+                }
+                VoicedChord {
+                    chord: chord.clone(),
+                    pitches,
+                    motion: vec![],
+                }
+            }
+        };
+        voiced.push(next);
+    }
+    Ok(voiced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn chords(symbols: &str) -> Vec<Chord> {
+        symbols
+            .split_whitespace()
+            .map(|symbol| Chord::from_symbol(symbol, Octave::Octave3).unwrap())
+            .collect()
+    }
+
+    fn names(pitches: &Pitches) -> Vec<String> {
+        pitches.0.iter().map(|pitch| pitch.to_string()).collect()
+    }
+
+    #[test]
+    fn test_minimal_motion() {
+        let voiced = voice_lead(&chords("C F G C"), &VoiceLeadingOptions::default()).unwrap();
+        assert_eq!(names(&voiced[1].pitches), vec!["C3", "F3", "A3"]);
+        assert_eq!(voiced[1].chord.inversion, Inversion::Second);
+        assert_eq!(names(&voiced[2].pitches), vec!["B2", "D3", "G3"]);
+        assert_eq!(voiced[1].total_motion(), 3);
+        assert_eq!(voiced[1].common_tones(), 1);
+        assert!(voiced[0].motion.is_empty());
+
+        let seventh = voice_lead(&chords("Dm7 G7"), &VoiceLeadingOptions::default()).unwrap();
+        assert_eq!(seventh[1].motion.len(), 4);
+    }
+
+    #[test]
+    fn test_range() {
+        let options = VoiceLeadingOptions {
+            keep_common_tones: true,
+            range: Some((PitchOctave::from_str("E3").unwrap(), PitchOctave::from_str("E4").unwrap())),
+        };
+        let voiced = voice_lead(&chords("C/E F"), &options).unwrap();
+        assert_eq!(names(&voiced[1].pitches), vec!["F3", "A3", "C4"]);
+        assert_eq!(voice_lead(&chords("C"), &options), Err(Error::OutofBounds));
+    }
+}